@@ -0,0 +1,1164 @@
+use std::{
+    collections::{HashMap, HashSet, hash_map::Entry},
+    fs,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    GitRepository,
+    helpers::date::{format_offset, now},
+};
+
+use git2::{
+    Blame, BlameOptions, DiffOptions, Error, Oid, Patch, Repository, Signature, Sort, Time,
+};
+
+/// A struct used to specify various details about the `git blame` command.
+pub struct BlameConfig {
+    path: String,
+    revision: Option<String>,
+    flags: BlameFlagsInternal,
+}
+
+impl BlameConfig {
+    /// Creates a BlameConfig object blaming the file at `path`, relative to the root of the
+    /// working tree. Without a revision the working tree contents are blamed, lines that are
+    /// not committed yet are attributed to the zero commit.
+    pub fn new(path: impl Into<String>) -> Self {
+        BlameConfig {
+            path: path.into(),
+            revision: None,
+            flags: BlameFlagsInternal::default(),
+        }
+    }
+
+    pub fn get_path(&self) -> &str {
+        &self.path
+    }
+
+    /// Blames the file as of `revision`, same as `git blame <rev> -- <path>`.
+    pub fn set_revision(&mut self, revision: Option<String>) -> &Self {
+        self.revision = revision;
+        self
+    }
+
+    pub fn add_flag(&mut self, flag: BlameFlags) -> &Self {
+        match flag {
+            BlameFlags::Lines(start, end) => self.flags.ranges.push((start, end)),
+            BlameFlags::IgnoreRev(rev) => self.flags.ignore_revs.push(rev),
+            BlameFlags::IgnoreRevsFile(file) => {
+                if file.is_empty() {
+                    self.flags.ignore_revs_files.clear();
+                    self.flags.ignore_config_file = false;
+                } else {
+                    self.flags.ignore_revs_files.push(file);
+                }
+            }
+            BlameFlags::IgnoreWhitespace(ignore) => self.flags.ignore_whitespace = ignore,
+            BlameFlags::DetectMoves(moves) => self.flags.moves = moves,
+            BlameFlags::DetectCopies(level) => self.flags.copies = level,
+            BlameFlags::FirstParent(first_parent) => self.flags.first_parent = first_parent,
+            BlameFlags::Root(root) => self.flags.root = Some(root),
+            BlameFlags::Contents(file) => self.flags.contents = file,
+        };
+        self
+    }
+}
+
+#[derive(Clone)]
+pub(crate) struct BlameFlagsInternal {
+    ranges: Vec<(usize, usize)>,
+    ignore_revs: Vec<String>,
+    ignore_revs_files: Vec<String>,
+    ignore_config_file: bool,
+    ignore_whitespace: bool,
+    moves: bool,
+    copies: u8,
+    first_parent: bool,
+    root: Option<bool>,
+    contents: Option<String>,
+}
+
+impl Default for BlameFlagsInternal {
+    fn default() -> Self {
+        BlameFlagsInternal {
+            ranges: vec![],
+            ignore_revs: vec![],
+            ignore_revs_files: vec![],
+            ignore_config_file: true,
+            ignore_whitespace: false,
+            moves: false,
+            copies: 0,
+            first_parent: false,
+            root: None,
+            contents: None,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum BlameFlags {
+    /// `-L <start>,<end>` flag for `git blame`.
+    /// Only shows the lines from `start` to `end`, both 1-based and inclusive. Can be passed
+    /// multiple times.
+    Lines(usize, usize),
+
+    /// `--ignore-rev <rev>` flag for `git blame`.
+    /// Attributes the lines changed by the revision to the lines they replaced. Can be passed
+    /// multiple times.
+    IgnoreRev(String),
+
+    /// `--ignore-revs-file <file>` flag for `git blame`.
+    /// Ignores the revisions listed in the file, one per line, `#` starts a comment.
+    /// Added to `blame.ignoreRevsFile`, an empty string clears all files passed so far
+    /// including the configured one.
+    IgnoreRevsFile(String),
+
+    /// `-w` flag for `git blame`.
+    /// Ignores whitespace when finding the lines a commit changed.
+    /// Defaults to false.
+    IgnoreWhitespace(bool),
+
+    /// `-M` flag for `git blame`.
+    /// Detects lines moved within the file.
+    /// Defaults to false.
+    DetectMoves(bool),
+
+    /// `-C` flag for `git blame`, the value is how many times it is passed:
+    /// - 1 detects lines moved from files modified in the same commit
+    /// - 2 also detects lines copied from files modified in the same commit
+    /// - 3 also detects lines copied from files in any commit
+    ///
+    /// Defaults to 0.
+    DetectCopies(u8),
+
+    /// `--first-parent` flag for `git blame`.
+    /// Only follows the first parent of merges.
+    /// Defaults to false.
+    FirstParent(bool),
+
+    /// `--root` flag for `git blame`.
+    /// Does not treat root commits as boundaries.
+    /// Defaults to `blame.showRoot` or false.
+    Root(bool),
+
+    /// `--contents <file>` flag for `git blame`.
+    /// Blames the contents of the file instead of the working tree file. Only used when no
+    /// revision is set.
+    Contents(Option<String>),
+}
+
+/// The commit a line of a blamed file comes from.
+#[derive(Clone)]
+pub struct BlameLineRecord {
+    /// Line number in the blamed file, 1-based.
+    pub line: usize,
+    /// The commit that introduced the line, zero for lines that are not committed yet.
+    pub commit: Oid,
+    /// Path of the file in `commit`.
+    pub original_path: PathBuf,
+    /// Line number in the file in `commit`, 1-based.
+    pub original_line: usize,
+    pub author: Signature<'static>,
+    pub committer: Signature<'static>,
+    pub summary: String,
+    /// Whether `commit` is a root commit and `--root` is not set.
+    pub boundary: bool,
+    /// The first parent of `commit` containing the file and its path there.
+    pub previous: Option<(Oid, PathBuf)>,
+    /// The line, without the line terminator.
+    pub content: String,
+    /// Whether the line was changed by an ignored revision and passed on to an older commit.
+    pub ignored: bool,
+    /// Whether the line was added by an ignored revision and could not be passed on.
+    pub unblamable: bool,
+}
+
+/// The blamed lines of a file.
+pub struct BlameOutput {
+    lines: Vec<BlameLineRecord>,
+    /// The blamed commits in the order the history is walked, newest first.
+    commits: Vec<Oid>,
+}
+
+impl GitRepository {
+    /// Finds the commit that last changed each line of a file, same as `git blame`.
+    pub fn git_blame(&self, config: BlameConfig) -> Result<BlameOutput, Error> {
+        if let Some(repository) = &self.repository {
+            let path = PathBuf::from(&config.path);
+            let flags = &config.flags;
+            let show_root = match flags.root {
+                Some(root) => root,
+                None => repository
+                    .config()?
+                    .get_bool("blame.showRoot")
+                    .unwrap_or(false),
+            };
+            let mut blamer = Blamer {
+                repository,
+                flags,
+                ignored: ignored_revisions(repository, flags)?,
+                cache: HashMap::new(),
+            };
+
+            let (origins, content, worktree, tip) = match &config.revision {
+                Some(revision) => {
+                    let commit = repository.revparse_single(revision)?.peel_to_commit()?;
+                    let blob = commit.tree()?.get_path(&path)?.to_object(repository)?;
+                    let content = blob.peel_to_blob()?.content().to_vec();
+                    let origins = blamer.blame_commit(commit.id(), &path)?;
+                    (origins, content, None, commit.id())
+                }
+                None => {
+                    let (content, source) = match &flags.contents {
+                        Some(file) => (read(Path::new(file))?, file.clone()),
+                        None => {
+                            let workdir = repository.workdir().ok_or_else(|| {
+                                Error::from_str("blaming the working tree needs a working tree")
+                            })?;
+                            (read(&workdir.join(&path))?, config.path.clone())
+                        }
+                    };
+                    let head = repository.head()?.peel_to_commit()?.id();
+                    let mut options = blamer.options(head);
+                    let blame = repository.blame_file(&path, Some(&mut options))?;
+                    let blame = blame.blame_buffer(&content)?;
+                    let mut origins = origins(&blame, &path, line_count(&content));
+                    blamer.resolve_ignored(&mut origins)?;
+                    (origins, content, Some((head, source)), head)
+                }
+            };
+
+            let lines: Vec<&[u8]> = split_lines(&content);
+            let ranges = line_ranges(&flags.ranges, lines.len(), &config.path)?;
+            let mut commits: HashMap<(Oid, PathBuf), CommitInfo> = HashMap::new();
+            let mut records = vec![];
+            for (index, origin) in origins.into_iter().enumerate() {
+                let number = index + 1;
+                if !ranges.is_empty()
+                    && !ranges
+                        .iter()
+                        .any(|(start, end)| *start <= number && number <= *end)
+                {
+                    continue;
+                }
+                let key = (origin.commit, origin.path.clone());
+                if let Entry::Vacant(entry) = commits.entry(key.clone()) {
+                    entry.insert(match &worktree {
+                        Some((head, source)) if origin.commit.is_zero() => {
+                            not_committed(repository, *head, &path, source)?
+                        }
+                        _ => commit_info(repository, origin.commit, &origin.path, flags)?,
+                    });
+                }
+                let info = &commits[&key];
+                let line = lines.get(index).copied().unwrap_or_default();
+                let line = line.strip_suffix(b"\n").unwrap_or(line);
+                records.push(BlameLineRecord {
+                    line: number,
+                    commit: origin.commit,
+                    original_path: origin.path,
+                    original_line: if origin.commit.is_zero() {
+                        number
+                    } else {
+                        origin.line
+                    },
+                    author: info.author.clone(),
+                    committer: info.committer.clone(),
+                    summary: info.summary.clone(),
+                    boundary: info.root && !show_root,
+                    previous: info.previous.clone(),
+                    content: String::from_utf8_lossy(line).to_string(),
+                    ignored: origin.ignored,
+                    unblamable: origin.unblamable,
+                });
+            }
+
+            // same order as the queue git blame walks, newest commit first
+            let mut pending: HashSet<Oid> = commits.keys().map(|(commit, _)| *commit).collect();
+            let mut walked = vec![];
+            if pending.remove(&Oid::zero()) {
+                walked.push(Oid::zero());
+            }
+            let mut revwalk = repository.revwalk()?;
+            revwalk.set_sorting(Sort::TOPOLOGICAL | Sort::TIME)?;
+            revwalk.push(tip)?;
+            for oid in revwalk {
+                if pending.is_empty() {
+                    break;
+                }
+                let oid = oid?;
+                if pending.remove(&oid) {
+                    walked.push(oid);
+                }
+            }
+
+            return Ok(BlameOutput {
+                lines: records,
+                commits: walked,
+            });
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+}
+
+impl BlameOutput {
+    pub fn lines(&self) -> &Vec<BlameLineRecord> {
+        &self.lines
+    }
+
+    /// Renders the lines the way `git blame --porcelain` prints them, the commit details are
+    /// shown the first time a commit appears.
+    pub fn porcelain(&self) -> String {
+        self.render(false)
+    }
+
+    /// Renders the lines the way `git blame --line-porcelain` prints them, the commit details
+    /// are shown for every line.
+    pub fn line_porcelain(&self) -> String {
+        self.render(true)
+    }
+
+    /// Renders the lines the way `git blame --incremental` prints them, one group of lines at
+    /// a time in the order the commits are found, newest first. The commit details are shown
+    /// the first time a commit appears and the lines themselves are left out.
+    pub fn incremental(&self) -> String {
+        let rank = |commit: &Oid| {
+            self.commits
+                .iter()
+                .position(|walked| walked == commit)
+                .unwrap_or(self.commits.len())
+        };
+        let mut groups = self.groups();
+        groups.sort_by_key(|(index, _)| rank(&self.lines[*index].commit));
+
+        let mut out = String::new();
+        let mut shown = HashSet::new();
+        for (index, count) in groups {
+            let line = &self.lines[index];
+            out.push_str(&format!(
+                "{} {} {} {}\n",
+                line.commit, line.original_line, line.line, count
+            ));
+            if shown.insert(line.commit) {
+                push_details(&mut out, line);
+            }
+            push_filename(&mut out, line);
+        }
+        out
+    }
+
+    /// Splits the lines into groups of consecutive lines coming from consecutive lines of the
+    /// same commit and path, as `(index, count)`.
+    fn groups(&self) -> Vec<(usize, usize)> {
+        let mut groups = vec![];
+        let mut index = 0;
+        while index < self.lines.len() {
+            let first = &self.lines[index];
+            let mut count = 1;
+            while let Some(next) = self.lines.get(index + count)
+                && next.commit == first.commit
+                && next.original_path == first.original_path
+                && next.original_line == first.original_line + count
+                && next.line == first.line + count
+                && next.ignored == first.ignored
+                && next.unblamable == first.unblamable
+            {
+                count += 1;
+            }
+            groups.push((index, count));
+            index += count;
+        }
+        groups
+    }
+
+    fn render(&self, every_line: bool) -> String {
+        // commits attributed lines of more than one path always show the filename
+        let mut paths: HashMap<Oid, HashSet<&Path>> = HashMap::new();
+        for line in &self.lines {
+            paths
+                .entry(line.commit)
+                .or_default()
+                .insert(&line.original_path);
+        }
+
+        let mut out = String::new();
+        let mut shown = HashSet::new();
+        for (index, count) in self.groups() {
+            for (offset, line) in self.lines[index..index + count].iter().enumerate() {
+                if offset == 0 {
+                    out.push_str(&format!(
+                        "{} {} {} {}\n",
+                        line.commit, line.original_line, line.line, count
+                    ));
+                } else {
+                    out.push_str(&format!(
+                        "{} {} {}\n",
+                        line.commit, line.original_line, line.line
+                    ));
+                }
+                let details = every_line || shown.insert(line.commit);
+                if details {
+                    push_details(&mut out, line);
+                }
+                if details || (offset == 0 && paths[&line.commit].len() > 1) {
+                    push_filename(&mut out, line);
+                }
+                out.push('\t');
+                out.push_str(&line.content);
+                out.push('\n');
+            }
+        }
+        out
+    }
+}
+
+fn push_details(out: &mut String, line: &BlameLineRecord) {
+    for (kind, signature) in [("author", &line.author), ("committer", &line.committer)] {
+        out.push_str(&format!(
+            "{} {}\n{}-mail <{}>\n{}-time {}\n{}-tz {}\n",
+            kind,
+            String::from_utf8_lossy(signature.name_bytes()),
+            kind,
+            String::from_utf8_lossy(signature.email_bytes()),
+            kind,
+            signature.when().seconds(),
+            kind,
+            format_offset(signature.when().offset_minutes(), false)
+        ));
+    }
+    out.push_str(&format!("summary {}\n", line.summary));
+    if line.boundary {
+        out.push_str("boundary\n");
+    }
+}
+
+fn push_filename(out: &mut String, line: &BlameLineRecord) {
+    if let Some((parent, path)) = &line.previous {
+        out.push_str(&format!("previous {} {}\n", parent, path.display()));
+    }
+    out.push_str(&format!("filename {}\n", line.original_path.display()));
+}
+
+/// Where a line of a blamed file comes from.
+#[derive(Clone)]
+struct Origin {
+    commit: Oid,
+    path: PathBuf,
+    line: usize,
+    ignored: bool,
+    unblamable: bool,
+}
+
+struct CommitInfo {
+    author: Signature<'static>,
+    committer: Signature<'static>,
+    summary: String,
+    root: bool,
+    previous: Option<(Oid, PathBuf)>,
+}
+
+struct Blamer<'a> {
+    repository: &'a Repository,
+    flags: &'a BlameFlagsInternal,
+    ignored: HashSet<Oid>,
+    /// Blames of the files in the parents of ignored commits.
+    cache: HashMap<(Oid, PathBuf), Vec<Origin>>,
+}
+
+impl Blamer<'_> {
+    fn options(&self, newest: Oid) -> BlameOptions {
+        let mut options = BlameOptions::new();
+        options
+            .newest_commit(newest)
+            .ignore_whitespace(self.flags.ignore_whitespace)
+            .track_copies_same_file(self.flags.moves)
+            .track_copies_same_commit_moves(self.flags.copies >= 1)
+            .track_copies_same_commit_copies(self.flags.copies >= 2)
+            .track_copies_any_commit_copies(self.flags.copies >= 3)
+            .first_parent(self.flags.first_parent);
+        options
+    }
+
+    fn blame_commit(&mut self, commit: Oid, path: &Path) -> Result<Vec<Origin>, Error> {
+        let key = (commit, path.to_path_buf());
+        if let Some(origins) = self.cache.get(&key) {
+            return Ok(origins.clone());
+        }
+        let blob = self
+            .repository
+            .find_commit(commit)?
+            .tree()?
+            .get_path(path)?
+            .to_object(self.repository)?
+            .peel_to_blob()?;
+        let mut options = self.options(commit);
+        let blame = self.repository.blame_file(path, Some(&mut options))?;
+        let mut origins = origins(&blame, path, line_count(blob.content()));
+        self.resolve_ignored(&mut origins)?;
+        self.cache.insert(key, origins.clone());
+        Ok(origins)
+    }
+
+    /// Passes the lines attributed to ignored commits on to the lines they replaced in the
+    /// first parent, the same way git matches them up.
+    fn resolve_ignored(&mut self, origins: &mut [Origin]) -> Result<(), Error> {
+        let mut groups: HashMap<(Oid, PathBuf), Vec<usize>> = HashMap::new();
+        for (index, origin) in origins.iter().enumerate() {
+            if self.ignored.contains(&origin.commit) && !origin.unblamable {
+                groups
+                    .entry((origin.commit, origin.path.clone()))
+                    .or_default()
+                    .push(index);
+            }
+        }
+
+        for ((commit, path), indexes) in groups {
+            let mapped = match self.parent_lines(commit, &path)? {
+                Some((parent, lines)) => Some((self.blame_commit(parent, &path)?, lines)),
+                None => None,
+            };
+            for index in indexes {
+                let line = origins[index].line - 1;
+                match &mapped {
+                    Some((parent, lines))
+                        if let Some(Some((parent_line, changed))) = lines.get(line)
+                            && let Some(origin) = parent.get(*parent_line) =>
+                    {
+                        let mut origin = origin.clone();
+                        origin.ignored |= *changed;
+                        origins[index] = origin;
+                    }
+                    _ => origins[index].unblamable = true,
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Maps the lines of the file in `commit` to the lines of the file in its first parent,
+    /// with whether the line was changed by the commit.
+    fn parent_lines(&self, commit: Oid, path: &Path) -> Result<Option<ParentLines>, Error> {
+        let commit = self.repository.find_commit(commit)?;
+        if commit.parent_count() == 0 {
+            return Ok(None);
+        }
+        let parent = commit.parent(0)?;
+        let parent_blob = match parent.tree()?.get_path(path) {
+            Ok(entry) => entry.to_object(self.repository)?.peel_to_blob()?,
+            Err(_) => return Ok(None),
+        };
+        let blob = commit
+            .tree()?
+            .get_path(path)?
+            .to_object(self.repository)?
+            .peel_to_blob()?;
+
+        let mut options = DiffOptions::new();
+        options
+            .context_lines(0)
+            .ignore_whitespace(self.flags.ignore_whitespace);
+        let patch = Patch::from_buffers(
+            parent_blob.content(),
+            None,
+            blob.content(),
+            None,
+            Some(&mut options),
+        )?;
+
+        let parent_lines = split_lines(parent_blob.content());
+        let target_lines = split_lines(blob.content());
+        let mut fingerprints_a: Vec<Fingerprint> =
+            parent_lines.iter().map(|line| fingerprint(line)).collect();
+        let fingerprints_b: Vec<Fingerprint> =
+            target_lines.iter().map(|line| fingerprint(line)).collect();
+
+        let mut lines = vec![None; target_lines.len()];
+        let (mut a, mut b) = (0, 0);
+        for index in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(index)?;
+            let (start_a, length_a) = hunk_range(hunk.old_start(), hunk.old_lines());
+            let (start_b, length_b) = hunk_range(hunk.new_start(), hunk.new_lines());
+            while b < start_b {
+                lines[b] = Some((a, false));
+                a += 1;
+                b += 1;
+            }
+            let matches = fuzzy_find_matching_lines(
+                &mut fingerprints_a,
+                &fingerprints_b,
+                start_a,
+                length_a,
+                start_b,
+                length_b,
+            );
+            for (offset, matched) in matches.into_iter().enumerate() {
+                lines[start_b + offset] = matched.map(|line| (line, true));
+            }
+            a = start_a + length_a;
+            b = start_b + length_b;
+        }
+        while b < lines.len() {
+            lines[b] = Some((a, false));
+            a += 1;
+            b += 1;
+        }
+        Ok(Some((parent.id(), lines)))
+    }
+}
+
+type ParentLines = (Oid, Vec<Option<(usize, bool)>>);
+
+/// Converts the 1-based start of a unified diff hunk side into a 0-based index.
+fn hunk_range(start: u32, lines: u32) -> (usize, usize) {
+    if lines == 0 {
+        (start as usize, 0)
+    } else {
+        (start as usize - 1, lines as usize)
+    }
+}
+
+fn origins(blame: &Blame, path: &Path, count: usize) -> Vec<Origin> {
+    let mut origins = vec![
+        Origin {
+            commit: Oid::zero(),
+            path: path.to_path_buf(),
+            line: 0,
+            ignored: false,
+            unblamable: false,
+        };
+        count
+    ];
+    for hunk in blame.iter() {
+        let path = hunk.path().unwrap_or(path);
+        for offset in 0..hunk.lines_in_hunk() {
+            let index = hunk.final_start_line() + offset - 1;
+            if let Some(origin) = origins.get_mut(index) {
+                origin.commit = hunk.final_commit_id();
+                origin.path = path.to_path_buf();
+                origin.line = hunk.orig_start_line() + offset;
+            }
+        }
+    }
+    origins
+}
+
+/// Collects the revisions from `blame.ignoreRevsFile`, the `--ignore-revs-file` files and
+/// the `--ignore-rev` flags.
+fn ignored_revisions(
+    repository: &Repository,
+    flags: &BlameFlagsInternal,
+) -> Result<HashSet<Oid>, Error> {
+    let mut revisions = vec![];
+    let base = repository.workdir().unwrap_or(repository.path());
+    let mut files = vec![];
+    if flags.ignore_config_file
+        && let Ok(file) = repository.config()?.get_path("blame.ignoreRevsFile")
+    {
+        // a missing configured file is skipped, unlike the ones passed as flags
+        let file = base.join(file);
+        if file.exists() {
+            files.push(file);
+        }
+    }
+    files.extend(flags.ignore_revs_files.iter().map(|file| base.join(file)));
+    for file in files {
+        let content = fs::read_to_string(&file).map_err(|error| {
+            Error::from_str(&format!(
+                "could not open object name list: {}: {}",
+                file.display(),
+                error
+            ))
+        })?;
+        revisions.extend(
+            content
+                .lines()
+                .map(str::trim)
+                .filter(|line| !line.is_empty() && !line.starts_with('#'))
+                .map(str::to_string),
+        );
+    }
+    revisions.extend(flags.ignore_revs.iter().cloned());
+
+    revisions
+        .iter()
+        .map(|revision| {
+            repository
+                .revparse_single(revision)
+                .and_then(|object| object.peel_to_commit())
+                .map(|commit| commit.id())
+                .map_err(|_| {
+                    Error::from_str(&format!("cannot find revision {} to ignore", revision))
+                })
+        })
+        .collect()
+}
+
+fn commit_info(
+    repository: &Repository,
+    id: Oid,
+    path: &Path,
+    flags: &BlameFlagsInternal,
+) -> Result<CommitInfo, Error> {
+    let commit = repository.find_commit(id)?;
+    let message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+    let summary = match message.trim_start_matches('\n').lines().next() {
+        Some(line) if !line.is_empty() => line.to_string(),
+        _ => format!("({})", id),
+    };
+    let parents = if flags.first_parent {
+        commit.parents().take(1).collect::<Vec<_>>()
+    } else {
+        commit.parents().collect()
+    };
+    let previous = parents
+        .iter()
+        .find(|parent| parent.tree().is_ok_and(|tree| tree.get_path(path).is_ok()))
+        .map(|parent| (parent.id(), path.to_path_buf()));
+    Ok(CommitInfo {
+        author: commit.author().to_owned(),
+        committer: commit.committer().to_owned(),
+        summary,
+        root: commit.parent_count() == 0,
+        previous,
+    })
+}
+
+/// Details git shows for lines of the working tree that are not committed yet.
+fn not_committed(
+    repository: &Repository,
+    head: Oid,
+    path: &Path,
+    source: &str,
+) -> Result<CommitInfo, Error> {
+    let when = Time::new(now(), 0);
+    let signature = Signature::new("Not Committed Yet", "not.committed.yet", &when)?;
+    let previous = repository
+        .find_commit(head)?
+        .tree()?
+        .get_path(path)
+        .ok()
+        .map(|_| (head, path.to_path_buf()));
+    Ok(CommitInfo {
+        author: signature.clone(),
+        committer: signature,
+        summary: format!("Version of {} from {}", path.display(), source),
+        root: false,
+        previous,
+    })
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, Error> {
+    fs::read(path)
+        .map_err(|error| Error::from_str(&format!("cannot read {}: {}", path.display(), error)))
+}
+
+/// Splits the content into lines, keeping the line terminators.
+fn split_lines(content: &[u8]) -> Vec<&[u8]> {
+    content.split_inclusive(|byte| *byte == b'\n').collect()
+}
+
+fn line_count(content: &[u8]) -> usize {
+    split_lines(content).len()
+}
+
+/// Validates the `-L` ranges against the number of lines, swapping reversed ranges and
+/// clipping them to the end of the file.
+fn line_ranges(
+    ranges: &[(usize, usize)],
+    count: usize,
+    path: &str,
+) -> Result<Vec<(usize, usize)>, Error> {
+    ranges
+        .iter()
+        .map(|&(start, end)| {
+            let (start, end) = if end < start {
+                (end, start)
+            } else {
+                (start, end)
+            };
+            if start == 0 {
+                return Err(Error::from_str("-L invalid line range, lines start at 1"));
+            }
+            if start > count {
+                return Err(Error::from_str(&format!(
+                    "file {} has only {} line{}",
+                    path,
+                    count,
+                    if count == 1 { "" } else { "s" }
+                )));
+            }
+            Ok((start, end.min(count)))
+        })
+        .collect()
+}
+
+/// A multiset of the lower-cased byte pairs of a line, whitespace counts as `\0` and pairs of
+/// whitespace are left out. Used to guess which line an ignored commit replaced.
+type Fingerprint = HashMap<u16, usize>;
+
+const CERTAIN_NOTHING_MATCHES: i64 = -2;
+const CERTAINTY_NOT_CALCULATED: i64 = -1;
+
+fn fingerprint(line: &[u8]) -> Fingerprint {
+    let mut fingerprint = HashMap::new();
+    let mut previous = 0u8;
+    for index in 0..=line.len() {
+        let byte = match line.get(index) {
+            Some(byte) if !matches!(byte, b' ' | b'\t' | b'\n' | b'\r') => {
+                byte.to_ascii_lowercase()
+            }
+            _ => 0,
+        };
+        let pair = previous as u16 | (byte as u16) << 8;
+        previous = byte;
+        if pair != 0 {
+            *fingerprint.entry(pair).or_insert(0) += 1;
+        }
+    }
+    fingerprint
+}
+
+fn fingerprint_similarity(a: &Fingerprint, b: &Fingerprint) -> i64 {
+    a.iter()
+        .map(|(pair, count)| b.get(pair).map_or(0, |other| *count.min(other)) as i64)
+        .sum()
+}
+
+fn fingerprint_subtract(a: &mut Fingerprint, b: &Fingerprint) {
+    for (pair, count) in b {
+        if let Some(other) = a.get_mut(pair) {
+            *other -= (*other).min(*count);
+        }
+    }
+}
+
+/// State of the fuzzy line matching, a port of git's `fuzzy_find_matching_lines`. Line
+/// numbers are 0-based indexes into the whole parent (A) and target (B) files.
+struct FuzzyMatcher<'a> {
+    fingerprints_a: &'a mut [Fingerprint],
+    fingerprints_b: &'a [Fingerprint],
+    /// Similarities scaled by the distance to the closest line, keyed by (B, A) line.
+    similarities: HashMap<(i64, i64), i64>,
+    certainties: Vec<i64>,
+    second_best: Vec<i64>,
+    result: Vec<i64>,
+    base_b: i64,
+    max_search_distance_a: i64,
+    max_search_distance_b: i64,
+    start_a: i64,
+    length_a: i64,
+    length_b: i64,
+}
+
+/// Finds the lines of the parent hunk that most closely match each line of the target hunk,
+/// keeping their order.
+fn fuzzy_find_matching_lines(
+    fingerprints_a: &mut [Fingerprint],
+    fingerprints_b: &[Fingerprint],
+    start_a: usize,
+    length_a: usize,
+    start_b: usize,
+    length_b: usize,
+) -> Vec<Option<usize>> {
+    if length_a == 0 {
+        return vec![None; length_b];
+    }
+    let (length_a, length_b) = (length_a as i64, length_b as i64);
+    let max_search_distance_a = 10.min(length_a - 1);
+    let mut matcher = FuzzyMatcher {
+        fingerprints_a,
+        fingerprints_b,
+        similarities: HashMap::new(),
+        certainties: vec![CERTAINTY_NOT_CALCULATED; length_b as usize],
+        second_best: vec![-1; length_b as usize],
+        result: vec![-1; length_b as usize],
+        base_b: start_b as i64,
+        max_search_distance_a,
+        max_search_distance_b: ((2 * max_search_distance_a + 1) * length_b - 1) / length_a,
+        start_a: start_a as i64,
+        length_a,
+        length_b,
+    };
+    matcher.recurse(start_a as i64, start_b as i64, length_a, length_b);
+    matcher
+        .result
+        .iter()
+        .map(|line| (*line >= 0).then_some(*line as usize))
+        .collect()
+}
+
+impl FuzzyMatcher<'_> {
+    /// Maps a line of B proportionally into the whole range of A.
+    fn closest_line(&self, line_b: i64) -> i64 {
+        ((line_b - self.base_b) * 2 + 1) * self.length_a / (self.length_b * 2) + self.start_a
+    }
+
+    fn find_best_line_matches(&mut self, start_a: i64, length_a: i64, line_b: i64) {
+        let local_b = (line_b - self.base_b) as usize;
+        if self.certainties[local_b] != CERTAINTY_NOT_CALCULATED {
+            return;
+        }
+        let closest = self.closest_line(line_b) - start_a;
+        let search_start = (closest - self.max_search_distance_a).max(0);
+        let search_end = (closest + self.max_search_distance_a + 1).min(length_a);
+
+        let (mut best, mut second) = (0, 0);
+        let (mut best_index, mut second_index) = (0, 0);
+        for index in search_start..search_end {
+            let line_a = start_a + index;
+            let similarity = match self.similarities.get(&(line_b, line_a)) {
+                Some(similarity) => *similarity,
+                None => {
+                    // nearer lines win between lines that are otherwise equally similar
+                    let similarity = fingerprint_similarity(
+                        &self.fingerprints_b[line_b as usize],
+                        &self.fingerprints_a[line_a as usize],
+                    ) * (1000 - (index - closest).abs());
+                    self.similarities.insert((line_b, line_a), similarity);
+                    similarity
+                }
+            };
+            if similarity > best {
+                second = best;
+                second_index = best_index;
+                best = similarity;
+                best_index = index;
+            } else if similarity > second {
+                second = similarity;
+                second_index = index;
+            }
+        }
+
+        if best == 0 {
+            self.certainties[local_b] = CERTAIN_NOTHING_MATCHES;
+            self.result[local_b] = -1;
+        } else {
+            self.certainties[local_b] = best * 2 - second;
+            self.result[local_b] = start_a + best_index;
+            self.second_best[local_b] = start_a + second_index;
+        }
+    }
+
+    /// Matches the line of B that matches with the most certainty and recurses into the lines
+    /// on either side of it, so lines never appear out of order.
+    fn recurse(&mut self, start_a: i64, start_b: i64, length_a: i64, length_b: i64) {
+        let mut most_certain_b = -1;
+        let mut most_certainty = -1;
+        for index in 0..length_b {
+            self.find_best_line_matches(start_a, length_a, start_b + index);
+            let certainty = self.certainties[(start_b + index - self.base_b) as usize];
+            if certainty > most_certainty {
+                most_certainty = certainty;
+                most_certain_b = index;
+            }
+        }
+        if most_certain_b == -1 {
+            return;
+        }
+
+        let local = |line: i64| (line - self.base_b) as usize;
+        let line_b = start_b + most_certain_b;
+        let most_certain_a = self.result[local(line_b)];
+        // other lines of B can't match the same parts of the line of A
+        fingerprint_subtract(
+            &mut self.fingerprints_a[most_certain_a as usize],
+            &self.fingerprints_b[line_b as usize],
+        );
+
+        let invalidate_min = (most_certain_b - self.max_search_distance_b).max(0);
+        let invalidate_max = (most_certain_b + self.max_search_distance_b + 1).min(length_b);
+        for index in invalidate_min..invalidate_max {
+            let closest = self.closest_line(start_b + index);
+            if (most_certain_a - closest).abs() <= self.max_search_distance_a {
+                self.similarities.remove(&(start_b + index, most_certain_a));
+            }
+        }
+        // discard matches contradicting the order imposed by the most certain line
+        for index in (invalidate_min..most_certain_b).rev() {
+            let local_b = local(start_b + index);
+            if self.certainties[local_b] >= 0
+                && (self.result[local_b] >= most_certain_a
+                    || self.second_best[local_b] >= most_certain_a)
+            {
+                self.certainties[local_b] = CERTAINTY_NOT_CALCULATED;
+            }
+        }
+        for index in most_certain_b + 1..invalidate_max {
+            let local_b = local(start_b + index);
+            if self.certainties[local_b] >= 0
+                && (self.result[local_b] <= most_certain_a
+                    || self.second_best[local_b] <= most_certain_a)
+            {
+                self.certainties[local_b] = CERTAINTY_NOT_CALCULATED;
+            }
+        }
+
+        if most_certain_b > 0 {
+            self.recurse(
+                start_a,
+                start_b,
+                most_certain_a + 1 - start_a,
+                most_certain_b,
+            );
+        }
+        if most_certain_b + 1 < length_b {
+            self.recurse(
+                most_certain_a,
+                line_b + 1,
+                start_a + length_a - most_certain_a,
+                length_b - most_certain_b - 1,
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod blame_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{BlameConfig, BlameFlags};
+    use crate::{GitRepository, helpers::test_util::git};
+
+    #[test]
+    fn git_blame_matches_cli_test() {
+        let dir_name = "./temp_test/blame/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        let path = env::current_dir().unwrap().join(dir_name);
+        let write = |lines: &[&str]| {
+            let mut content = lines.join("\n");
+            content.push('\n');
+            fs::write(path.join("a.txt"), content).unwrap();
+        };
+
+        git(&path, &["init", "-b", "main"]);
+        git(&path, &["config", "user.name", "Committer"]);
+        git(&path, &["config", "user.email", "committer@example.com"]);
+        write(&["one", "two", "three", "four", "five", "six"]);
+        git(&path, &["add", "."]);
+        git(&path, &["commit", "-m", "root"]);
+        write(&["one", "two", "THREE", "four", "five", "six", "seven"]);
+        git(&path, &["commit", "-am", "second\n\nwith a body"]);
+        // reformatting commit to be ignored
+        write(&[
+            "one", "Two", "THREE", "extra", "  four", "five", "six", "seven",
+        ]);
+        git(&path, &["commit", "-am", "reformat"]);
+        write(&[
+            "one", "Two", "THREE", "extra", "  four", "five", "6", "seven", "eight",
+        ]);
+        git(&path, &["commit", "-am", "fourth"]);
+        let reformat = git(&path, &["rev-parse", "HEAD~1"]).trim().to_string();
+        fs::write(
+            path.join(".ignore-revs"),
+            format!("# formatting\n{}\n", reformat),
+        )
+        .unwrap();
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let cases: Vec<(Vec<BlameFlags>, Vec<&str>)> = vec![
+            (vec![], vec![]),
+            (
+                vec![BlameFlags::Lines(2, 3), BlameFlags::Lines(6, 7)],
+                vec!["-L", "2,3", "-L", "6,7"],
+            ),
+            (
+                vec![BlameFlags::IgnoreRev(reformat.clone())],
+                vec!["--ignore-rev", &reformat],
+            ),
+            (
+                vec![BlameFlags::IgnoreRevsFile(".ignore-revs".to_string())],
+                vec!["--ignore-revs-file", ".ignore-revs"],
+            ),
+            (vec![BlameFlags::IgnoreWhitespace(true)], vec!["-w"]),
+            (vec![BlameFlags::Root(true)], vec!["--root"]),
+        ];
+        let mut results = vec![];
+        for (flags, args) in &cases {
+            let mut config = BlameConfig::new("a.txt");
+            config.set_revision(Some("HEAD".to_string()));
+            for flag in flags {
+                config.add_flag(flag.clone());
+            }
+            let blame = repo.git_blame(config).unwrap();
+            for (format, ours) in [
+                ("--porcelain", blame.porcelain()),
+                ("--incremental", blame.incremental()),
+            ] {
+                let mut cli = vec!["blame", format];
+                cli.extend(args);
+                cli.extend(["HEAD", "--", "a.txt"]);
+                let theirs = git(&path, &cli);
+                results.push((format!("{} {}", format, args.join(" ")), ours, theirs));
+            }
+        }
+
+        // the configured file is used unless cleared
+        git(&path, &["config", "blame.ignoreRevsFile", ".ignore-revs"]);
+        for (flags, args) in [
+            (vec![], vec![]),
+            (
+                vec![BlameFlags::IgnoreRevsFile(String::new())],
+                vec!["--ignore-revs-file", ""],
+            ),
+        ] {
+            let mut config = BlameConfig::new("a.txt");
+            config.set_revision(Some("HEAD".to_string()));
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            let ours = repo.git_blame(config).unwrap().porcelain();
+            let mut cli = vec!["blame", "--porcelain"];
+            cli.extend(&args);
+            cli.extend(["HEAD", "--", "a.txt"]);
+            let theirs = git(&path, &cli);
+            results.push((format!("config {}", args.join(" ")), ours, theirs));
+        }
+
+        // uncommitted lines in the working tree, with the time fields masked
+        write(&[
+            "one", "Two", "new", "THREE", "extra", "  four", "five", "6", "seven", "eight",
+        ]);
+        let mask = |out: String| -> String {
+            out.lines()
+                .map(|line| {
+                    if line.contains("-time ") {
+                        "time"
+                    } else {
+                        line
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        let ours = repo.git_blame(BlameConfig::new("a.txt")).unwrap();
+        let worktree = (
+            mask(ours.porcelain()),
+            mask(git(&path, &["blame", "--porcelain", "a.txt"])),
+        );
+        let uncommitted = ours.lines()[2].commit.is_zero();
+        let out_of_range = {
+            let mut config = BlameConfig::new("a.txt");
+            config.add_flag(BlameFlags::Lines(20, 21));
+            repo.git_blame(config).is_err()
+        };
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (args, ours, theirs) in &results {
+            assert_eq!(ours, theirs, "{}", args);
+        }
+        assert_eq!(worktree.0, worktree.1);
+        assert!(uncommitted);
+        assert!(out_of_range);
+    }
+}
@@ -1,32 +1,215 @@
-use crate::GitRepository;
-use git2::{CertificateCheckStatus, Error, RemoteCallbacks, build::CheckoutBuilder};
+use std::path::Path;
+
+use crate::{GitRepository, helpers::hooks};
+use git2::{
+    BranchType, CertificateCheckStatus, Commit, Error, ErrorCode, Index, IndexEntry, IndexTime,
+    Object, ObjectType, Oid, Pathspec, PathspecFlags, RemoteCallbacks, Repository, Tree,
+    TreeWalkMode, TreeWalkResult, build::CheckoutBuilder,
+};
 
 #[derive(Clone)]
 pub struct CheckoutConfig {
     pub(crate) spec: String,
+    pub(crate) paths: Vec<String>,
     pub(crate) flags: CheckoutFlagsInternal,
 }
 
 impl CheckoutConfig {
+    /// Creates a CheckoutConfig object switching to `spec`. With `CheckoutFlags::NewBranch`,
+    /// `CheckoutFlags::ResetBranch` or `CheckoutFlags::Orphan` the spec is the start point of the
+    /// new branch, an empty spec starts at `HEAD`.
     pub fn new(spec: String) -> Self {
         CheckoutConfig {
             spec: spec,
+            paths: vec![],
             flags: CheckoutFlagsInternal::default(),
         }
     }
 
+    /// Limits the checkout to the paths, same as `git checkout <spec> -- <path>...`. The files
+    /// are taken from the spec into the index and working tree or, with an empty spec, from the
+    /// index into the working tree. `HEAD` is left untouched.
+    pub fn set_paths(&mut self, paths: Vec<String>) -> &Self {
+        self.paths = paths;
+        self
+    }
+
     pub fn add_flags(&mut self, flag: CheckoutFlags) {
-        match flag {}
+        match flag {
+            CheckoutFlags::NewBranch(name) => self.flags.new_branch = Some((name, false)),
+            CheckoutFlags::ResetBranch(name) => self.flags.new_branch = Some((name, true)),
+            CheckoutFlags::Track(track) => self.flags.track = Some(track),
+            CheckoutFlags::NoTrack(no_track) => self.flags.track = Some(!no_track),
+            CheckoutFlags::Orphan(name) => self.flags.orphan = Some(name),
+            CheckoutFlags::Detach(detach) => self.flags.detach = detach,
+            CheckoutFlags::Force(force) => self.flags.force = force,
+            CheckoutFlags::Merge(merge) => self.flags.merge = merge,
+            CheckoutFlags::Conflict(style) => {
+                // --conflict implies --merge
+                self.flags.merge = true;
+                self.flags.conflict = Some(style);
+            }
+            CheckoutFlags::Guess(guess) => self.flags.guess = Some(guess),
+            CheckoutFlags::NoGuess(no_guess) => self.flags.guess = Some(!no_guess),
+            CheckoutFlags::RemoteLookup(lookup) => self.flags.remote_lookup = lookup,
+            CheckoutFlags::Ours(ours) => self.flags.stage = if ours { Some(2) } else { None },
+            CheckoutFlags::Theirs(theirs) => self.flags.stage = if theirs { Some(3) } else { None },
+        }
     }
 }
 
 #[derive(Default, Clone)]
-pub(crate) struct CheckoutFlagsInternal {}
+pub(crate) struct CheckoutFlagsInternal {
+    new_branch: Option<(String, bool)>,
+    track: Option<bool>,
+    orphan: Option<String>,
+    detach: bool,
+    force: bool,
+    merge: bool,
+    conflict: Option<ConflictStyle>,
+    stage: Option<u16>,
+    guess: Option<bool>,
+    remote_lookup: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum CheckoutFlags {
+    /// `-b <new-branch>` flag for `git checkout`.
+    /// Creates the branch at the spec and switches to it, fails if it already exists.
+    NewBranch(String),
+
+    /// `-B <new-branch>` flag for `git checkout`.
+    /// Same as `NewBranch` except an existing branch is reset to the spec.
+    ResetBranch(String),
+
+    /// `--track` flag for `git checkout`.
+    /// Sets the start point as upstream of the new branch, it has to be a branch.
+    /// Defaults to `branch.autoSetupMerge`, which tracks remote-tracking start points.
+    Track(bool),
+
+    /// `--no-track` flag for `git checkout`.
+    /// Does not set up an upstream for the new branch.
+    /// Defaults to false.
+    NoTrack(bool),
+
+    /// `--orphan <new-branch>` flag for `git checkout`.
+    /// Switches to a new unborn branch, the index and working tree are the ones of the spec.
+    Orphan(String),
 
-pub enum CheckoutFlags {}
+    /// `--detach` flag for `git checkout`.
+    /// Detaches `HEAD` at the spec even if it is a branch.
+    /// Defaults to false.
+    Detach(bool),
+
+    /// `-f` or `--force` flag for `git checkout`.
+    /// Throws away local changes instead of refusing to switch.
+    /// Defaults to false.
+    Force(bool),
+
+    /// `-m` or `--merge` flag for `git checkout`.
+    /// Carries local changes that conflict with the switch across with a three-way merge,
+    /// leaving conflicted files unmerged.
+    /// Defaults to false.
+    Merge(bool),
+
+    /// `--conflict=<style>` flag for `git checkout`.
+    /// Same as `Merge` and sets how conflicts are shown.
+    /// Defaults to `merge.conflictStyle` or `ConflictStyle::Merge`.
+    Conflict(ConflictStyle),
+
+    /// `--guess` flag for `git checkout`.
+    /// When the spec is not a local branch or tag but exactly one remote has a
+    /// remote-tracking branch of that name, creates a local branch tracking it.
+    /// `checkout.defaultRemote` picks the remote when several have it.
+    /// Defaults to `checkout.guess` or true.
+    Guess(bool),
+
+    /// `--no-guess` flag for `git checkout`.
+    /// Defaults to false.
+    NoGuess(bool),
+
+    /// Not a git flag, looks the spec up as a branch or tag on the remotes and fetches it when
+    /// it can't be resolved locally. Needs network access.
+    /// Defaults to false.
+    RemoteLookup(bool),
+
+    /// `--ours` flag for `git checkout`.
+    /// Checks out our version of unmerged paths from the index, they stay unmerged.
+    /// Defaults to false.
+    Ours(bool),
+
+    /// `--theirs` flag for `git checkout`.
+    /// Checks out their version of unmerged paths from the index, they stay unmerged.
+    /// Defaults to false.
+    Theirs(bool),
+}
+
+/// Ways to show conflicts in files, see `CheckoutFlags::Conflict`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConflictStyle {
+    /// Shows our and their side of a conflict.
+    Merge,
+
+    /// Also shows the common ancestor of a conflict.
+    Diff3,
+}
 
 impl GitRepository {
+    /// Switches to the spec or, when paths are set, checks out the paths, see
+    /// `git_checkout_paths`.
     pub fn git_checkout(&self, config: CheckoutConfig) -> Result<(), Error> {
+        if !config.paths.is_empty() {
+            return self.git_checkout_paths(config).map(|_| ());
+        }
+        let previous = self.head_oid();
+        self.checkout_spec(config)?;
+
+        // post-checkout, its exit status does not affect the checkout
+        if self.run_hooks
+            && let Some(repository) = &self.repository
+        {
+            let previous = previous.to_string();
+            let current = self.head_oid().to_string();
+            let args = [previous.as_str(), current.as_str(), "1"];
+            let _ = hooks::run_hook(repository, "post-checkout", &args, None, &[]);
+        }
+        Ok(())
+    }
+
+    /// Checks out the paths set with `CheckoutConfig::set_paths` and returns the files that were
+    /// checked out. Unmerged paths fail the checkout unless `--ours` or `--theirs` picks a
+    /// version, or are skipped with `--force`.
+    pub fn git_checkout_paths(&self, config: CheckoutConfig) -> Result<Vec<String>, Error> {
+        if let Some(repository) = &self.repository {
+            let written = checkout_paths(repository, &config)?;
+
+            // post-checkout, with the flag telling a file checkout apart from a switch
+            if self.run_hooks {
+                let head = self.head_oid().to_string();
+                let args = [head.as_str(), head.as_str(), "0"];
+                let _ = hooks::run_hook(repository, "post-checkout", &args, None, &[]);
+            }
+            return Ok(written);
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    /// Returns the commit `HEAD` points to, or the zero id if there is none.
+    fn head_oid(&self) -> Oid {
+        let head = match &self.repository {
+            Some(repository) => repository.head().and_then(|head| head.peel_to_commit()),
+            None => return Oid::zero(),
+        };
+        match head {
+            Ok(commit) => commit.id(),
+            Err(_) => Oid::zero(),
+        }
+    }
+
+    fn checkout_spec(&self, config: CheckoutConfig) -> Result<(), Error> {
         if let Some(repository) = &self.repository {
             // skip user verification if configured so
             if self.skip_owner_validation {
@@ -35,46 +218,125 @@ impl GitRepository {
                 };
             }
 
-            // prepare checkout
-            let mut checkout_builder = CheckoutBuilder::new();
+            let flags = &config.flags;
+            let start_point = match config.spec.as_str() {
+                "" => "HEAD",
+                spec => spec,
+            };
 
-            // trying locally present branch
-            match repository.find_branch(&config.spec, git2::BranchType::Local) {
-                Ok(local_branch) => {
-                    let reference = local_branch.get();
-                    let name = match reference.name() {
-                        Some(name) => name,
-                        None => &config.spec,
-                    };
-                    repository.set_head(name)?;
-                    checkout_builder.safe();
-                    repository.checkout_head(Some(&mut checkout_builder))?;
-                    return Ok(());
+            if let Some(name) = &flags.orphan {
+                if repository.find_branch(name, BranchType::Local).is_ok() {
+                    let err_msg = format!("a branch named '{}' already exists", name);
+                    return Err(Error::from_str(&err_msg));
                 }
-                Err(_) => {}
-            };
+                if !config.spec.is_empty() {
+                    let commit = repository.revparse_single(&config.spec)?.peel_to_commit()?;
+                    checkout_target(repository, flags, commit.as_object(), name)?;
+                }
+                repository.set_head(&format!("refs/heads/{}", name))?;
+                return Ok(());
+            }
+
+            if let Some((name, reset)) = &flags.new_branch {
+                if !reset && repository.find_branch(name, BranchType::Local).is_ok() {
+                    let err_msg = format!("a branch named '{}' already exists", name);
+                    return Err(Error::from_str(&err_msg));
+                }
+                let commit = repository.revparse_single(start_point)?.peel_to_commit()?;
+                let track = tracks(repository, flags, start_point)?;
+                // switch the working tree first so a failed checkout leaves no branch behind
+                checkout_target(repository, flags, commit.as_object(), name)?;
+                let full_name = format!("refs/heads/{}", name);
+                let message = format!("branch: Created from {}", start_point);
+                repository.reference(&full_name, commit.id(), true, &message)?;
+                if track {
+                    let mut branch = repository.find_branch(name, BranchType::Local)?;
+                    branch.set_upstream(Some(start_point))?;
+                }
+                repository.set_head(&full_name)?;
+                return Ok(());
+            }
+
+            if flags.detach {
+                let (object, reference) = repository.revparse_ext(start_point)?;
+                let commit = object.peel_to_commit()?;
+                checkout_target(repository, flags, commit.as_object(), start_point)?;
+                // keeps the name in the reflog so `HEAD` is shown as detached at it
+                let annotated = match reference {
+                    Some(reference) => repository.reference_to_annotated_commit(&reference)?,
+                    None => repository.find_annotated_commit(commit.id())?,
+                };
+                repository.set_head_detached_from_annotated(annotated)?;
+                return Ok(());
+            }
+
+            // trying locally present branch
+            if let Ok(local_branch) = repository.find_branch(&config.spec, BranchType::Local) {
+                let reference = local_branch.get();
+                let name = match reference.name() {
+                    Some(name) => name,
+                    None => &config.spec,
+                };
+                let commit = reference.peel_to_commit()?;
+                checkout_target(repository, flags, commit.as_object(), &config.spec)?;
+                repository.set_head(name)?;
+                return Ok(());
+            }
 
             // trying locally present tags
             let tag = format!("refs/tags/{}", &config.spec);
-            match repository.find_reference(&tag) {
-                Ok(tag) => {
-                    let name = match tag.name() {
-                        Some(name) => name,
-                        None => &config.spec,
-                    };
-                    repository.set_head(name)?;
-                    checkout_builder.safe();
-                    repository.checkout_head(Some(&mut checkout_builder))?;
-                    return Ok(());
-                }
-                Err(_) => {}
+            if let Ok(tag) = repository.find_reference(&tag) {
+                let commit = tag.peel_to_commit()?;
+                checkout_target(repository, flags, commit.as_object(), &config.spec)?;
+                let annotated = repository.reference_to_annotated_commit(&tag)?;
+                repository.set_head_detached_from_annotated(annotated)?;
+                return Ok(());
+            }
+
+            // trying remote-tracking branches that are already fetched
+            let guess = match flags.guess {
+                Some(guess) => guess,
+                None => repository
+                    .config()?
+                    .get_bool("checkout.guess")
+                    .unwrap_or(true),
             };
+            if guess {
+                let mut candidates = vec![];
+                for remote in repository.remotes()?.iter().flatten() {
+                    if let Some(tracking) = tracking_ref(repository, remote, &config.spec)? {
+                        candidates.push((remote.to_string(), tracking));
+                    }
+                }
+                if let Some((_, tracking)) = pick_remote(repository, &config.spec, candidates)? {
+                    let reference = repository.find_reference(&tracking)?;
+                    let commit = reference.peel_to_commit()?;
+                    return create_tracking_branch(
+                        repository,
+                        flags,
+                        &config.spec,
+                        &commit,
+                        &tracking,
+                    );
+                }
+            }
+
+            // try commits
+            if let Ok(obj) = repository.revparse_single(&config.spec) {
+                let commit = obj.peel_to_commit()?;
+                checkout_target(repository, flags, commit.as_object(), &config.spec)?;
+                repository.set_head_detached(commit.id())?;
+                return Ok(());
+            }
 
-            // trying remote branches and tags
-            let remotes = repository.remotes()?;
-            for remote in &remotes {
-                if let Some(remote) = remote {
-                    let mut remote = repository.find_remote(remote)?;
+            // trying remote branches and tags, only when looking them up is enabled
+            if flags.remote_lookup {
+                let branch_full = format!("refs/heads/{}", &config.spec);
+                let tag_full = format!("refs/tags/{}", &config.spec);
+                let mut branches = vec![];
+                let mut tag_remote = None;
+                for name in repository.remotes()?.iter().flatten() {
+                    let mut remote = repository.find_remote(name)?;
                     let mut callback = RemoteCallbacks::new();
                     // continue even if cert checks fail, if configured so
                     if self.bypass_certificate_check {
@@ -83,62 +345,42 @@ impl GitRepository {
                     }
                     callback.credentials(move |_a: &str, _b, _c| self.cred.get_cred());
                     remote.connect_auth(git2::Direction::Fetch, Some(callback), None)?;
-                    if let Ok(remote_heads) = remote.list() {
-                        let branch_full = format!("refs/heads/{}", &config.spec);
-                        for remote_head in remote_heads {
-                            if branch_full == remote_head.name() {
-                                let target_commit = remote_head.oid();
-                                let target_commit = repository.find_commit(target_commit)?;
-                                let mut remote = remote.clone();
-                                let refspec = format!(
-                                    "{}:refs/remotes/{}/{}",
-                                    branch_full,
-                                    remote.name().unwrap(),
-                                    &config.spec
-                                );
-                                remote.fetch(&[refspec], None, None)?;
-                                let mut local_branch =
-                                    repository.branch(&config.spec, &target_commit, false)?;
-                                let upstream =
-                                    format!("{}/{}", remote.name().unwrap(), &config.spec);
-                                local_branch.set_upstream(Some(&upstream))?;
-                                repository.set_head(&branch_full)?;
-                                checkout_builder.safe();
-                                repository.checkout_head(Some(&mut checkout_builder))?;
-                                return Ok(());
-                            }
-
-                            let tag_full = format!("refs/tags/{}", &config.spec);
-                            if tag_full == remote_head.name() {
-                                let tag_ref = format!("{}:{}", tag_full, tag_full);
-                                let mut remote = remote.clone();
-                                remote.fetch(&[tag_ref], None, None)?;
-                                let reference = repository.find_reference(&tag_full)?;
-                                let name = match reference.name() {
-                                    Some(name) => name,
-                                    None => &config.spec,
-                                };
-                                repository.set_head(name)?;
-                                checkout_builder.safe();
-                                repository.checkout_head(Some(&mut checkout_builder))?;
-                                return Ok(());
-                            }
+                    for remote_head in remote.list()? {
+                        if remote_head.name() == branch_full {
+                            branches.push((name.to_string(), remote_head.oid()));
+                        } else if remote_head.name() == tag_full && tag_remote.is_none() {
+                            tag_remote = Some(name.to_string());
                         }
                     }
-                    remote.disconnect();
+                    let _ = remote.disconnect();
                 }
-            }
 
-            // checkout to local branch
+                if let Some((name, _)) = pick_remote(repository, &config.spec, branches)? {
+                    let mut remote = repository.find_remote(&name)?;
+                    let tracking = format!("refs/remotes/{}/{}", name, &config.spec);
+                    let refspec = format!("{}:{}", branch_full, tracking);
+                    remote.fetch(&[refspec], None, None)?;
+                    let commit = repository.find_reference(&tracking)?.peel_to_commit()?;
+                    return create_tracking_branch(
+                        repository,
+                        flags,
+                        &config.spec,
+                        &commit,
+                        &tracking,
+                    );
+                }
 
-            // try commits
-            match repository.revparse_single(&config.spec) {
-                Ok(obj) => {
-                    repository.set_head_detached(obj.id())?;
-                    repository.checkout_tree(&obj, Some(&mut checkout_builder))?;
+                if let Some(name) = tag_remote {
+                    let mut remote = repository.find_remote(&name)?;
+                    let tag_ref = format!("{}:{}", tag_full, tag_full);
+                    remote.fetch(&[tag_ref], None, None)?;
+                    let tag = repository.find_reference(&tag_full)?;
+                    let commit = tag.peel_to_commit()?;
+                    checkout_target(repository, flags, commit.as_object(), &config.spec)?;
+                    let annotated = repository.reference_to_annotated_commit(&tag)?;
+                    repository.set_head_detached_from_annotated(annotated)?;
                     return Ok(());
                 }
-                Err(_) => {}
             }
 
             let err_msg = format!("Failed to resolve spec: {}", &config.spec);
@@ -151,6 +393,323 @@ impl GitRepository {
     }
 }
 
+/// Checks out the tree of `target` without moving `HEAD`. Local changes are kept unless they
+/// conflict with the switch, in which case it fails or, with `--merge`, merges them in.
+fn checkout_target(
+    repository: &Repository,
+    flags: &CheckoutFlagsInternal,
+    target: &Object,
+    label: &str,
+) -> Result<(), Error> {
+    let mut checkout_builder = CheckoutBuilder::new();
+    if flags.force {
+        checkout_builder.force();
+    } else {
+        checkout_builder.safe();
+    }
+    match repository.checkout_tree(target, Some(&mut checkout_builder)) {
+        Err(error) if flags.merge && !flags.force && error.code() == ErrorCode::Conflict => {
+            merge_local_changes(repository, flags, target, label)
+        }
+        result => result,
+    }
+}
+
+/// Three-way merges the local changes into `target` with `HEAD` as the base, same as
+/// `git checkout --merge`. Cleanly merged changes stay unstaged, conflicts are left unmerged
+/// in the index with markers in the working tree.
+fn merge_local_changes(
+    repository: &Repository,
+    flags: &CheckoutFlagsInternal,
+    target: &Object,
+    label: &str,
+) -> Result<(), Error> {
+    let head = repository.head()?;
+    let head_tree = head.peel_to_tree()?;
+    let head_label = match head.shorthand() {
+        Some(name) if head.is_branch() => name.to_string(),
+        _ => head.peel_to_commit()?.id().to_string()[..7].to_string(),
+    };
+    let target_tree = target.peel_to_tree()?;
+
+    // the tracked files of the working tree as a tree, without touching the index on disk
+    let mut index = repository.index()?;
+    index.read(false)?;
+    index.update_all(["*"], None)?;
+    let local_tree = repository.find_tree(index.write_tree()?)?;
+    index.read(true)?;
+
+    let mut merged = repository.merge_trees(&head_tree, &target_tree, &local_tree, None)?;
+
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder.force();
+    repository.checkout_tree(target, Some(&mut checkout_builder))?;
+
+    let diff3 = match flags.conflict {
+        Some(style) => style == ConflictStyle::Diff3,
+        None => repository
+            .config()?
+            .get_string("merge.conflictStyle")
+            .is_ok_and(|style| style == "diff3"),
+    };
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder
+        .force()
+        .allow_conflicts(true)
+        .update_index(false)
+        .conflict_style_diff3(diff3)
+        .ancestor_label(&head_label)
+        .our_label(label)
+        .their_label("local");
+    repository.checkout_index(Some(&mut merged), Some(&mut checkout_builder))?;
+
+    let mut index = repository.index()?;
+    index.read_tree(&target_tree)?;
+    for conflict in merged.conflicts()? {
+        let conflict = conflict?;
+        let entries = [conflict.ancestor, conflict.our, conflict.their];
+        for entry in entries.into_iter().flatten() {
+            let path = String::from_utf8_lossy(&entry.path).to_string();
+            let _ = index.remove(Path::new(&path), 0);
+            index.add(&entry)?;
+        }
+    }
+    index.write()
+}
+
+fn checkout_paths(repository: &Repository, config: &CheckoutConfig) -> Result<Vec<String>, Error> {
+    let flags = &config.flags;
+    let pathspec = Pathspec::new(config.paths.iter())?;
+    let mut index = repository.index()?;
+    index.read(false)?;
+
+    let (entries, unmerged) = if config.spec.is_empty() {
+        index_entries(&index, &pathspec, flags.stage)?
+    } else {
+        let tree = repository.revparse_single(&config.spec)?.peel_to_tree()?;
+        (tree_entries(&tree, &pathspec)?, vec![])
+    };
+
+    let mut matched: Vec<String> = entries.iter().map(entry_path).collect();
+    matched.extend(unmerged.iter().cloned());
+    for path in &config.paths {
+        let single = Pathspec::new([path])?;
+        if !matched
+            .iter()
+            .any(|file| single.matches_path(Path::new(file), PathspecFlags::DEFAULT))
+        {
+            let err_msg = format!("pathspec '{}' did not match any file(s) known to git", path);
+            return Err(Error::from_str(&err_msg));
+        }
+    }
+    if !flags.force
+        && let Some(path) = unmerged.first()
+    {
+        let err_msg = match flags.stage {
+            Some(2) => format!("path '{}' does not have our version", path),
+            Some(_) => format!("path '{}' does not have their version", path),
+            None => format!("path '{}' is unmerged", path),
+        };
+        return Err(Error::from_str(&err_msg));
+    }
+
+    write_worktree(repository, &entries)?;
+    // files taken from a tree are staged as well, resolving conflicts on them
+    if !config.spec.is_empty() {
+        for entry in &entries {
+            let path = entry_path(entry);
+            let _ = index.conflict_remove(Path::new(&path));
+            index.add(entry)?;
+        }
+        index.write()?;
+    }
+
+    Ok(entries.iter().map(entry_path).collect())
+}
+
+/// Returns the entries of the index matching the pathspec with the unmerged paths that have no
+/// entry at `stage`, entries picked from a conflict are returned at stage 0.
+pub(crate) fn index_entries(
+    index: &Index,
+    pathspec: &Pathspec,
+    stage: Option<u16>,
+) -> Result<(Vec<IndexEntry>, Vec<String>), Error> {
+    let mut entries: Vec<IndexEntry> = vec![];
+    let mut conflicted: Vec<String> = vec![];
+    for mut entry in index.iter() {
+        let path = entry_path(&entry);
+        if !pathspec.matches_path(Path::new(&path), PathspecFlags::DEFAULT) {
+            continue;
+        }
+        match (entry.flags >> 12) & 3 {
+            0 => entries.push(entry),
+            entry_stage => {
+                if !conflicted.contains(&path) {
+                    conflicted.push(path);
+                }
+                if Some(entry_stage) == stage {
+                    entry.flags &= !0x3000;
+                    entries.push(entry);
+                }
+            }
+        }
+    }
+    let unmerged = conflicted
+        .into_iter()
+        .filter(|path| !entries.iter().any(|entry| entry_path(entry) == *path))
+        .collect();
+    Ok((entries, unmerged))
+}
+
+/// Returns the files of the tree matching the pathspec as stage 0 index entries.
+pub(crate) fn tree_entries(tree: &Tree, pathspec: &Pathspec) -> Result<Vec<IndexEntry>, Error> {
+    let mut entries = vec![];
+    tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        if entry.kind() == Some(ObjectType::Tree) {
+            return TreeWalkResult::Ok;
+        }
+        let path = format!("{}{}", root, entry.name().unwrap_or_default());
+        if pathspec.matches_path(Path::new(&path), PathspecFlags::DEFAULT) {
+            entries.push(IndexEntry {
+                ctime: IndexTime::new(0, 0),
+                mtime: IndexTime::new(0, 0),
+                dev: 0,
+                ino: 0,
+                mode: entry.filemode() as u32,
+                uid: 0,
+                gid: 0,
+                file_size: 0,
+                id: entry.id(),
+                flags: path.len().min(0xfff) as u16,
+                flags_extended: 0,
+                path: path.into_bytes(),
+            });
+        }
+        TreeWalkResult::Ok
+    })?;
+    Ok(entries)
+}
+
+/// Writes the entries to the working tree without touching the index.
+pub(crate) fn write_worktree(repository: &Repository, entries: &[IndexEntry]) -> Result<(), Error> {
+    if entries.is_empty() {
+        return Ok(());
+    }
+    let mut source = Index::new()?;
+    let mut checkout_builder = CheckoutBuilder::new();
+    checkout_builder
+        .force()
+        .update_index(false)
+        .disable_pathspec_match(true);
+    for entry in entries {
+        source.add(entry)?;
+        checkout_builder.path(entry.path.clone());
+    }
+    repository.checkout_index(Some(&mut source), Some(&mut checkout_builder))
+}
+
+pub(crate) fn entry_path(entry: &IndexEntry) -> String {
+    String::from_utf8_lossy(&entry.path).to_string()
+}
+
+/// Returns the remote-tracking ref `name` is fetched into from the remote, if it exists.
+pub(crate) fn tracking_ref(
+    repository: &Repository,
+    remote: &str,
+    name: &str,
+) -> Result<Option<String>, Error> {
+    let branch = format!("refs/heads/{}", name);
+    let remote = repository.find_remote(remote)?;
+    for refspec in remote.refspecs() {
+        if refspec.direction() == git2::Direction::Fetch
+            && refspec.src_matches(&branch)
+            && let Ok(tracking) = refspec.transform(&branch)
+            && let Some(tracking) = tracking.as_str()
+            && repository.find_reference(tracking).is_ok()
+        {
+            return Ok(Some(tracking.to_string()));
+        }
+    }
+    Ok(None)
+}
+
+/// Picks the remote to take `name` from out of the remotes having it, preferring
+/// `checkout.defaultRemote` when more than one does.
+fn pick_remote<T>(
+    repository: &Repository,
+    name: &str,
+    mut candidates: Vec<(String, T)>,
+) -> Result<Option<(String, T)>, Error> {
+    if candidates.len() <= 1 {
+        return Ok(candidates.pop());
+    }
+    if let Ok(default) = repository.config()?.get_string("checkout.defaultRemote")
+        && let Some(index) = candidates.iter().position(|(remote, _)| *remote == default)
+    {
+        return Ok(Some(candidates.swap_remove(index)));
+    }
+    let remotes: Vec<String> = candidates
+        .iter()
+        .map(|(remote, _)| format!("{}/{}", remote, name))
+        .collect();
+    let err_msg = format!(
+        "'{}' matched multiple ({}) remote tracking branches: {}",
+        name,
+        remotes.len(),
+        remotes.join(", ")
+    );
+    Err(Error::from_str(&err_msg))
+}
+
+/// Creates the local branch `name` at the remote-tracking branch, tracking it unless
+/// `--no-track` is set, and switches to it.
+fn create_tracking_branch(
+    repository: &Repository,
+    flags: &CheckoutFlagsInternal,
+    name: &str,
+    commit: &Commit,
+    tracking: &str,
+) -> Result<(), Error> {
+    checkout_target(repository, flags, commit.as_object(), name)?;
+    let upstream = tracking.trim_start_matches("refs/remotes/");
+    let mut branch = repository.branch(name, commit, false)?;
+    if flags.track != Some(false) {
+        branch.set_upstream(Some(upstream))?;
+    }
+    repository.set_head(&format!("refs/heads/{}", name))
+}
+
+/// Returns whether a new branch tracks its start point according to `--track`, `--no-track`
+/// and `branch.autoSetupMerge`.
+fn tracks(
+    repository: &Repository,
+    flags: &CheckoutFlagsInternal,
+    start_point: &str,
+) -> Result<bool, Error> {
+    let remote = repository
+        .find_branch(start_point, BranchType::Remote)
+        .is_ok();
+    let local = repository
+        .find_branch(start_point, BranchType::Local)
+        .is_ok();
+    match flags.track {
+        Some(false) => Ok(false),
+        Some(true) if !remote && !local => {
+            let err_msg = format!(
+                "cannot set up tracking information; starting point '{}' is not a branch",
+                start_point
+            );
+            Err(Error::from_str(&err_msg))
+        }
+        Some(true) => Ok(true),
+        None => match repository.config()?.get_string("branch.autoSetupMerge") {
+            Ok(setting) if setting == "false" => Ok(false),
+            Ok(setting) if setting == "always" => Ok(remote || local),
+            _ => Ok(remote),
+        },
+    }
+}
+
 #[cfg(test)]
 mod checkout_test {
     use std::{env, path::Path, process::Command};
@@ -264,3 +823,257 @@ mod checkout_test {
         assert!(out_4.is_err());
     }
 }
+
+#[cfg(test)]
+mod checkout_flags_test {
+    use std::path::Path;
+
+    use super::{CheckoutConfig, CheckoutFlags, ConflictStyle};
+    use crate::{GitRepository, helpers::test_util::CliComparison};
+
+    #[test]
+    fn checkout_flags_match_cli_test() {
+        let cli = CliComparison::new("checkout_flags");
+        cli.write("base", "a.txt", "a\nb\nc\n");
+        cli.write("base", "b.txt", "b\n");
+        cli.git("base", &["add", "."]);
+        cli.git("base", &["commit", "-m", "root"]);
+        cli.git("base", &["checkout", "-b", "side"]);
+        cli.write("base", "a.txt", "a\nB\nc\n");
+        cli.git("base", &["commit", "-am", "side"]);
+        cli.git("base", &["checkout", "main"]);
+        cli.git("base", &["remote", "add", "origin", "../missing"]);
+        cli.git("base", &["update-ref", "refs/remotes/origin/main", "side"]);
+        cli.fork();
+
+        let repo = GitRepository::open(Path::new(&cli.path("ours"))).unwrap();
+        let checkout = |spec: &str, flags: Vec<CheckoutFlags>| {
+            let mut config = CheckoutConfig::new(spec.to_string());
+            for flag in flags {
+                config.add_flags(flag);
+            }
+            repo.git_checkout(config)
+        };
+        let state = |cli: &CliComparison, dir: &str| {
+            [
+                cli.git(dir, &["status", "--porcelain", "--branch"]),
+                cli.git(dir, &["branch", "-vv"]),
+                cli.git(dir, &["ls-files", "-s"]),
+                cli.git(dir, &["config", "--get-regexp", "^branch\\."]),
+                cli.read(dir, "a.txt"),
+            ]
+            .join("\n")
+        };
+
+        // a new branch carrying a local change, tracking a remote-tracking start point
+        cli.write_both("b.txt", "local\n");
+        checkout(
+            "origin/main",
+            vec![CheckoutFlags::NewBranch("feature".into())],
+        )
+        .unwrap();
+        cli.git("theirs", &["checkout", "-b", "feature", "origin/main"]);
+        cli.record("-b", state);
+
+        // existing branches are refused by -b and reset by -B
+        let exists = checkout("main", vec![CheckoutFlags::NewBranch("side".into())]).is_err();
+        checkout(
+            "main",
+            vec![
+                CheckoutFlags::ResetBranch("side".into()),
+                CheckoutFlags::Track(true),
+            ],
+        )
+        .unwrap();
+        cli.git("theirs", &["checkout", "-B", "side", "--track", "main"]);
+        cli.record("-B --track", state);
+        checkout(
+            "origin/main",
+            vec![
+                CheckoutFlags::NewBranch("untracked".into()),
+                CheckoutFlags::NoTrack(true),
+            ],
+        )
+        .unwrap();
+        cli.git(
+            "theirs",
+            &["checkout", "-b", "untracked", "--no-track", "origin/main"],
+        );
+        cli.record("--no-track", state);
+
+        checkout("main", vec![CheckoutFlags::Detach(true)]).unwrap();
+        cli.git("theirs", &["checkout", "--detach", "main"]);
+        cli.record("--detach", state);
+
+        checkout("side", vec![CheckoutFlags::Orphan("fresh".into())]).unwrap();
+        cli.git("theirs", &["checkout", "--orphan", "fresh", "side"]);
+        cli.record("--orphan", state);
+        cli.git_both(&["commit", "-m", "fresh"]);
+
+        // a local change conflicting with the switch
+        checkout("main", vec![CheckoutFlags::Force(true)]).unwrap();
+        cli.git("theirs", &["checkout", "-f", "main"]);
+        cli.record("-f", state);
+        cli.write_both("a.txt", "a\nX\nc\n");
+        let refused = checkout("feature", vec![]).is_err();
+        cli.record("refused", state);
+        checkout(
+            "feature",
+            vec![CheckoutFlags::Conflict(ConflictStyle::Diff3)],
+        )
+        .unwrap();
+        cli.git("theirs", &["checkout", "--conflict=diff3", "feature"]);
+        cli.record("--conflict=diff3", state);
+
+        cli.assert_matches();
+        assert!(exists);
+        assert!(refused);
+    }
+
+    #[test]
+    fn checkout_paths_match_cli_test() {
+        let cli = CliComparison::new("checkout_paths");
+        cli.write("base", "a.txt", "a\nb\nc\n");
+        cli.write("base", "dir/b.txt", "b\n");
+        cli.write("base", "dir/c.txt", "c\n");
+        cli.git("base", &["add", "."]);
+        cli.git("base", &["commit", "-m", "root"]);
+        cli.git("base", &["checkout", "-b", "side"]);
+        cli.write("base", "a.txt", "a\nB\nc\n");
+        cli.git("base", &["commit", "-am", "side"]);
+        cli.git("base", &["checkout", "main"]);
+        cli.write("base", "a.txt", "a\nX\nc\n");
+        cli.write("base", "dir/b.txt", "main\n");
+        cli.git("base", &["commit", "-am", "main"]);
+        cli.fork();
+
+        let repo = GitRepository::open(Path::new(&cli.path("ours"))).unwrap();
+        let checkout = |spec: &str, paths: &[&str], flags: Vec<CheckoutFlags>| {
+            let mut config = CheckoutConfig::new(spec.to_string());
+            config.set_paths(paths.iter().map(|path| path.to_string()).collect());
+            for flag in flags {
+                config.add_flags(flag);
+            }
+            repo.git_checkout_paths(config)
+        };
+        let state = |cli: &CliComparison, dir: &str| {
+            [
+                cli.git(dir, &["status", "--porcelain", "--branch"]),
+                cli.git(dir, &["ls-files", "-s"]),
+                cli.read(dir, "a.txt"),
+                cli.read(dir, "dir/b.txt"),
+            ]
+            .join("\n")
+        };
+
+        // from the index
+        cli.write_both("a.txt", "local\n");
+        cli.write_both("dir/b.txt", "local\n");
+        let written = checkout("", &["a.txt"], vec![]).unwrap();
+        cli.git("theirs", &["checkout", "--", "a.txt"]);
+        cli.record("index", state);
+
+        // from a tree-ish, HEAD stays on main
+        let from_tree = checkout("HEAD~1", &["dir"], vec![]).unwrap();
+        cli.git("theirs", &["checkout", "HEAD~1", "--", "dir"]);
+        cli.record("tree-ish", state);
+        let missing = checkout("", &["missing.txt"], vec![]).is_err();
+
+        // unmerged paths
+        cli.git_both(&["reset", "--hard"]);
+        cli.git_both(&["merge", "side"]);
+        let unmerged = checkout("", &["a.txt"], vec![]).is_err();
+        checkout("", &["a.txt"], vec![CheckoutFlags::Theirs(true)]).unwrap();
+        cli.git("theirs", &["checkout", "--theirs", "--", "a.txt"]);
+        cli.record("--theirs", state);
+        checkout("", &["a.txt"], vec![CheckoutFlags::Ours(true)]).unwrap();
+        cli.git("theirs", &["checkout", "--ours", "--", "a.txt"]);
+        cli.record("--ours", state);
+        let skipped = checkout("", &["a.txt"], vec![CheckoutFlags::Force(true)]).unwrap();
+        checkout("side", &["a.txt"], vec![]).unwrap();
+        cli.git("theirs", &["checkout", "side", "--", "a.txt"]);
+        cli.record("resolved", state);
+
+        cli.assert_matches();
+        assert_eq!(written, ["a.txt"]);
+        assert_eq!(from_tree, ["dir/b.txt", "dir/c.txt"]);
+        assert!(missing);
+        assert!(unmerged);
+        assert!(skipped.is_empty());
+    }
+
+    #[test]
+    fn checkout_resolution_matches_cli_test() {
+        let cli = CliComparison::new("checkout_resolution");
+        cli.write("base", "a.txt", "a\n");
+        cli.git("base", &["add", "."]);
+        cli.git("base", &["commit", "-m", "root"]);
+        cli.git("base", &["tag", "-a", "v1", "-m", "v1"]);
+        cli.write("base", "a.txt", "b\n");
+        cli.git("base", &["commit", "-am", "second"]);
+        // remotes that can't be reached, everything has to resolve locally
+        for remote in ["origin", "upstream"] {
+            cli.git("base", &["remote", "add", remote, "../missing"]);
+        }
+        for tracking in [
+            "origin/feature",
+            "origin/shared",
+            "upstream/shared",
+            "origin/other",
+        ] {
+            let name = format!("refs/remotes/{}", tracking);
+            cli.git("base", &["update-ref", &name, "HEAD~1"]);
+        }
+        let root_commit = cli.git("base", &["rev-parse", "HEAD~1"]).trim().to_string();
+        cli.fork();
+
+        let repo = GitRepository::open(Path::new(&cli.path("ours"))).unwrap();
+        let checkout = |spec: &str, flags: Vec<CheckoutFlags>| {
+            let mut config = CheckoutConfig::new(spec.to_string());
+            for flag in flags {
+                config.add_flags(flag);
+            }
+            repo.git_checkout(config)
+        };
+        let state = |cli: &CliComparison, dir: &str| {
+            [
+                cli.git(dir, &["status", "--porcelain", "--branch"]),
+                cli.git(dir, &["branch", "-vv"]),
+                cli.git(dir, &["config", "--get-regexp", "^branch\\."]),
+            ]
+            .join("\n")
+        };
+
+        checkout("feature", vec![]).unwrap();
+        cli.git("theirs", &["checkout", "feature"]);
+        cli.record("guess", state);
+
+        let ambiguous = checkout("shared", vec![]);
+        cli.git("theirs", &["checkout", "shared"]);
+        cli.record("ambiguous", state);
+        cli.git_both(&["config", "checkout.defaultRemote", "upstream"]);
+        checkout("shared", vec![]).unwrap();
+        cli.git("theirs", &["checkout", "shared"]);
+        cli.record("checkout.defaultRemote", state);
+
+        let no_guess = checkout("other", vec![CheckoutFlags::NoGuess(true)]).is_err();
+        cli.git("theirs", &["checkout", "--no-guess", "other"]);
+        cli.record("--no-guess", state);
+
+        checkout(&root_commit, vec![]).unwrap();
+        cli.git("theirs", &["checkout", &root_commit]);
+        cli.record("commit", state);
+        checkout("v1", vec![]).unwrap();
+        cli.git("theirs", &["checkout", "v1"]);
+        cli.record("tag", state);
+
+        cli.assert_matches();
+        let ambiguous = ambiguous.unwrap_err();
+        assert!(
+            ambiguous
+                .message()
+                .contains("origin/shared, upstream/shared")
+        );
+        assert!(no_guess);
+    }
+}
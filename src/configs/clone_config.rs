@@ -1,10 +1,12 @@
-use crate::{GitRepository, helpers::channel::ChannelHelper};
+use crate::{GitRepository, GitUrl, GitUrlScheme, guess_dir_name, helpers::channel::ChannelHelper};
 use git2::{
-    AutotagOption, CertificateCheckStatus, Error, FetchOptions, Remote, RemoteCallbacks,
-    build::RepoBuilder,
+    AutotagOption, CertificateCheckStatus, Error, FetchOptions, ObjectType, Oid, Remote,
+    RemoteCallbacks, Repository, SubmoduleUpdateOptions,
+    build::{CheckoutBuilder, CloneLocal, RepoBuilder},
 };
 
 use std::{
+    fs,
     path::{Path, PathBuf},
     time::SystemTime,
 };
@@ -30,17 +32,13 @@ impl CloneConfig {
     ///
     /// A new directory, named after the repository (e.g., "gittwo" from
     /// "https://github.com/psomani16k/gittwo.git"), will be created inside `parent_dir`. The repository will be cloned into this new directory.
+    /// The name is derived the way git does for every url form, see [`guess_dir_name`].
     pub fn new(url: String, parent_dir: &Path) -> Self {
-        let target_dir: String = url;
-        let url = target_dir.clone();
-        let target_dir = target_dir.split("/").last().unwrap();
-        let target_dir = match target_dir.strip_suffix(".git") {
-            Some(t) => t,
-            None => target_dir,
-        };
+        // empty when no name can be guessed, `git_clone` then asks for a custom directory
+        let target_dir = guess_dir_name(&url, false).unwrap_or_default();
 
         CloneConfig {
-            clone_dir_name: target_dir.to_string(),
+            clone_dir_name: target_dir,
             parent_path: parent_dir.to_path_buf(),
             url,
             flags: CloneFlagsInternal::default(),
@@ -91,10 +89,19 @@ impl CloneConfig {
         &self.parent_path
     }
 
+    /// Returns the name of the remote the repository is cloned from, "origin" unless set with
+    /// `CloneFlags::Origin`.
+    pub fn get_origin(&self) -> &str {
+        match &self.flags.origin {
+            Some(origin) => origin,
+            None => "origin",
+        }
+    }
+
     /// Returns the name of the directory for the cloned repository.
     /// The full path will be `parent_path/clone_dir_name/`.
     pub fn get_clone_dir_name(&self) -> String {
-        if self.flags.bare {
+        if self.flags.bare || self.flags.mirror {
             let mut dir = self.clone_dir_name.clone();
             dir += ".git";
             return dir;
@@ -102,6 +109,14 @@ impl CloneConfig {
         self.clone_dir_name.clone()
     }
 
+    /// Returns the path of the source repository when cloning from a local path rather than
+    /// a url, `None` otherwise.
+    pub fn local_source(&self) -> Option<PathBuf> {
+        let url = GitUrl::parse(&self.url).ok()?;
+        let path = PathBuf::from(url.path);
+        (url.scheme == GitUrlScheme::Local && path.is_dir()).then_some(path)
+    }
+
     // setters
 
     /// Sets a custom name for the directory where the repository will be cloned.
@@ -116,8 +131,22 @@ impl CloneConfig {
             CloneFlags::Branch(branch) => self.flags.branch = branch,
             CloneFlags::Depth(depth) => self.flags.depth = depth,
             CloneFlags::SingleBranch(single) => self.flags.single_branch = single,
+            CloneFlags::NoSingleBranch(no_single) => self.flags.single_branch = !no_single,
             CloneFlags::Bare(bare) => self.flags.bare = bare,
             CloneFlags::Recursive(rec) => self.flags.recursive = rec,
+            CloneFlags::Origin(origin) => self.flags.origin = origin,
+            CloneFlags::NoCheckout(no_checkout) => self.flags.no_checkout = no_checkout,
+            CloneFlags::Mirror(mirror) => self.flags.mirror = mirror,
+            CloneFlags::Config(config) => self.flags.config = config,
+            CloneFlags::Template(template) => self.flags.template = template,
+            CloneFlags::SeparateGitDir(git_dir) => self.flags.separate_git_dir = git_dir,
+            CloneFlags::NoTags(no_tags) => self.flags.no_tags = no_tags,
+            CloneFlags::ShallowSubmodules(shallow) => self.flags.shallow_submodules = shallow,
+            CloneFlags::Local(local) => self.flags.no_local = !local,
+            CloneFlags::NoHardlinks(no_hardlinks) => self.flags.no_hardlinks = no_hardlinks,
+            CloneFlags::Reference(references) => self.flags.references = references,
+            CloneFlags::Shared(shared) => self.flags.shared = shared,
+            CloneFlags::Dissociate(dissociate) => self.flags.dissociate = dissociate,
         }
         self
     }
@@ -131,6 +160,19 @@ pub(crate) struct CloneFlagsInternal {
     pub(crate) single_branch: bool,
     pub(crate) bare: bool,
     pub(crate) recursive: Option<Vec<String>>,
+    pub(crate) origin: Option<String>,
+    pub(crate) no_checkout: bool,
+    pub(crate) mirror: bool,
+    pub(crate) config: Vec<(String, String)>,
+    pub(crate) template: Option<PathBuf>,
+    pub(crate) separate_git_dir: Option<PathBuf>,
+    pub(crate) no_tags: bool,
+    pub(crate) shallow_submodules: bool,
+    pub(crate) no_local: bool,
+    pub(crate) no_hardlinks: bool,
+    pub(crate) references: Vec<PathBuf>,
+    pub(crate) shared: bool,
+    pub(crate) dissociate: bool,
 }
 
 /// Represents flags that can be applied to a `git clone` command.
@@ -146,6 +188,8 @@ pub enum CloneFlags {
     /// Corresponds to the [`--depth <depth>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---depthltdepthgt)
     /// flag.
     /// `Some(n)` creates a shallow clone with a history truncated to `n` commits. `None` implies a full clone.
+    /// Unlike git, where `--depth` implies `--single-branch`, every branch is fetched shallowly
+    /// unless combined with `SingleBranch(true)`.
     ///
     /// Defaults to `None`.
     Depth(Option<usize>),
@@ -158,6 +202,15 @@ pub enum CloneFlags {
     /// Defaults to `false`.
     SingleBranch(bool),
 
+    /// Corresponds to the [`--no-single-branch`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---no-single-branch)
+    /// flag.
+    /// `true` clones all branches, undoing a previous `SingleBranch(true)`. Same as
+    /// `SingleBranch(false)`. All branches are cloned by default, also with `Depth`, so this
+    /// only matters after `SingleBranch(true)`.
+    ///
+    /// Defaults to `false`.
+    NoSingleBranch(bool),
+
     /// Corresponds to the [`--bare`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---bare)
     /// flag.
     /// `true` creates a bare Git repository (no working directory). `false` creates a standard repository.
@@ -172,6 +225,107 @@ pub enum CloneFlags {
     ///
     /// Defaults to `None`.
     Recursive(Option<Vec<String>>),
+
+    /// Corresponds to the [`--origin <name>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---originltnamegt)
+    /// or `-o` flag.
+    /// `Some(name)` names the remote `name` instead of "origin".
+    ///
+    /// Defaults to `None`.
+    Origin(Option<String>),
+
+    /// Corresponds to the [`--no-checkout`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---no-checkout)
+    /// or `-n` flag.
+    /// `true` points `HEAD` to the cloned branch without checking out its files.
+    ///
+    /// Defaults to `false`.
+    NoCheckout(bool),
+
+    /// Corresponds to the [`--mirror`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---mirror)
+    /// flag.
+    /// `true` creates a bare repository mapping every reference of the remote (`+refs/*:refs/*`)
+    /// and sets `remote.<name>.mirror`.
+    ///
+    /// Defaults to `false`.
+    Mirror(bool),
+
+    /// Corresponds to the [`--config <key>=<value>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---configltkeygtltvaluegt)
+    /// or `-c` flag.
+    /// Sets each (key, value) pair in the config of the new repository before anything is
+    /// fetched, keys given more than once are added as multiple values.
+    ///
+    /// Defaults to an empty vector.
+    Config(Vec<(String, String)>),
+
+    /// Corresponds to the [`--template <dir>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---templatelttemplate-directorygt)
+    /// flag.
+    /// `Some(dir)` copies the files of `dir` (hooks, `info/exclude`, etc) into the new `.git`
+    /// directory before fetching.
+    ///
+    /// Defaults to `None`.
+    Template(Option<PathBuf>),
+
+    /// Corresponds to the [`--separate-git-dir <dir>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---separate-git-dirltgit-dirgt)
+    /// flag.
+    /// `Some(dir)` places the repository in `dir` and leaves a `.git` file pointing to it in the
+    /// working directory. Can not be combined with `--bare` or `--mirror`.
+    ///
+    /// Defaults to `None`.
+    SeparateGitDir(Option<PathBuf>),
+
+    /// Corresponds to the [`--no-tags`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---no-tags)
+    /// flag.
+    /// `true` does not clone any tags and sets `remote.<name>.tagOpt=--no-tags`, so that later
+    /// fetches do not follow tags either.
+    ///
+    /// Defaults to `false`.
+    NoTags(bool),
+
+    /// Corresponds to the [`--shallow-submodules`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---shallow-submodules)
+    /// flag.
+    /// `true` clones the submodules initialized through `Recursive` with a depth of 1.
+    ///
+    /// Defaults to `false`.
+    ShallowSubmodules(bool),
+
+    /// Corresponds to the [`--local`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---local)
+    /// (`true`) or `--no-local` (`false`) flag, or `-l`.
+    /// `true` bypasses the git transport when cloning from a local path and hardlinks the object
+    /// files where possible. `false` goes through the transport. Urls, including `file://`,
+    /// always use the transport.
+    ///
+    /// Defaults to `true`.
+    Local(bool),
+
+    /// Corresponds to the [`--no-hardlinks`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---no-hardlinks)
+    /// flag.
+    /// `true` copies the object files of a local source instead of hardlinking them.
+    ///
+    /// Defaults to `false`.
+    NoHardlinks(bool),
+
+    /// Corresponds to the [`--reference <repository>`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---referenceltrepositorygt)
+    /// flag, once for each repository.
+    /// Adds the objects of each local repository to `objects/info/alternates`, the history
+    /// found there is not stored in the new repository.
+    ///
+    /// Defaults to an empty vector.
+    Reference(Vec<PathBuf>),
+
+    /// Corresponds to the [`--shared`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---shared)
+    /// or `-s` flag.
+    /// `true` borrows the objects of a local source through `objects/info/alternates` instead
+    /// of keeping a copy of them. Ignored for urls.
+    ///
+    /// Defaults to `false`.
+    Shared(bool),
+
+    /// Corresponds to the [`--dissociate`](https://git-scm.com/docs/git-clone#Documentation/git-clone.txt---dissociate)
+    /// flag.
+    /// `true` copies the objects borrowed through `Reference` or `Shared` into the new
+    /// repository after cloning and removes `objects/info/alternates`.
+    ///
+    /// Defaults to `false`.
+    Dissociate(bool),
 }
 
 impl GitRepository {
@@ -186,6 +340,16 @@ impl GitRepository {
                 "git_clone() called on a pre-existing repository.",
             ));
         }
+        if config.clone_dir_name.is_empty() {
+            return Err(Error::from_str(
+                "No directory name could be guessed. Please specify a directory with custom_clone_directory()",
+            ));
+        }
+        if config.flags.separate_git_dir.is_some() && (config.flags.bare || config.flags.mirror) {
+            return Err(Error::from_str(
+                "--separate-git-dir can not be combined with --bare or --mirror",
+            ));
+        }
 
         let mut remote_update_index = 1;
         let mut transfer_update_index = 100;
@@ -311,6 +475,7 @@ impl GitRepository {
         // +---------------+
 
         // branch
+        let remote_default = def_branch.clone();
         if let Some(branch) = &config.flags.branch {
             def_branch = branch.to_string();
         }
@@ -319,46 +484,139 @@ impl GitRepository {
         if let Some(depth) = config.flags.depth {
             let depth: i32 = depth as i32;
             fetch_options.depth(depth);
-            fetch_options.download_tags(AutotagOption::None);
-            let branch = def_branch.clone();
-            repo_builder.remote_create(move |repo, name, url| {
-                let refspec = format!("+refs/heads/{0:}:refs/remotes/origin/{0:}", branch);
-                repo.remote_with_fetch(name, url, &refspec)
-            });
         }
 
-        // single-branch
-        if config.flags.single_branch {
+        // depth, single-branch and no-tags, otherwise every tag is fetched like libgit2 does
+        if config.flags.depth.is_some() || config.flags.single_branch || config.flags.no_tags {
             fetch_options.download_tags(AutotagOption::None);
-            let branch = def_branch.clone();
-            repo_builder.remote_create(move |repo, name, url| {
-                let refspec = format!("+refs/heads/{0:}:refs/remotes/origin/{0:}", branch);
-                repo.remote_with_fetch(name, url, &refspec)
-            });
+        } else {
+            fetch_options.download_tags(AutotagOption::All);
+        }
+
+        // origin, mirror, config and template, set up in the new repository before fetching
+        let origin = config.get_origin().to_string();
+        let refspec = if config.flags.mirror {
+            "+refs/*:refs/*".to_string()
+        } else if config.flags.single_branch {
+            format!(
+                "+refs/heads/{0:}:refs/remotes/{1:}/{0:}",
+                def_branch, origin
+            )
+        } else {
+            format!("+refs/heads/*:refs/remotes/{}/*", origin)
+        };
+        // reference and shared, the object directories borrowed from and their tips
+        let local_source = config.local_source();
+        let mut alternates = Vec::new();
+        let mut borrowed = Vec::new();
+        let sources = config.flags.references.iter();
+        let shared = local_source.iter().filter(|_| config.flags.shared);
+        for source in sources.chain(shared) {
+            let (objects, tips) = open_alternate(source)?;
+            alternates.push(objects);
+            borrowed.extend(tips);
+        }
+        let borrowing = !alternates.is_empty();
+
+        let setup = CloneSetup {
+            origin,
+            refspec,
+            alternates,
+            flags: config.flags.clone(),
+        };
+        let remote_setup = setup.clone();
+        repo_builder.remote_create(move |repo, _name, url| remote_setup.configure(repo, url));
+
+        // local and no-hardlinks
+        if local_source.is_some() {
+            let clone_local = if config.flags.no_local {
+                CloneLocal::None
+            } else if config.flags.no_hardlinks {
+                CloneLocal::NoLinks
+            } else {
+                CloneLocal::Auto
+            };
+            repo_builder.clone_local(clone_local);
         }
 
         // bare
-        let repo_builder = repo_builder.bare(config.flags.bare);
+        let repo_builder = repo_builder.bare(config.flags.bare || config.flags.mirror);
+
+        // no-checkout
+        if config.flags.no_checkout {
+            let mut checkout = CheckoutBuilder::new();
+            checkout.dry_run();
+            repo_builder.with_checkout(checkout);
+        }
 
         // +--------------+
         // | CLONING REPO |
         // +--------------+
 
-        let repo_builder = repo_builder.branch(&def_branch);
+        // a mirror has no remote-tracking branches to check out, HEAD is set after cloning
+        let branch = (!config.flags.mirror).then_some(def_branch.as_str());
+        if let Some(branch) = branch {
+            repo_builder.branch(branch);
+        }
 
         fetch_options.remote_callbacks(callbacks);
 
-        // setting fetch options and cloning
-        repo_builder.fetch_options(fetch_options);
-        let repository = repo_builder.clone(config.get_url(), &repo_path)?;
+        // setting fetch options and cloning, libgit2 only clones into repositories without
+        // references and fetches every tag on clones that are not shallow, so clones borrowing
+        // objects through alternates and clones without tags are set up separately. Local
+        // clones copy the whole objects directory, tags included, as in git.
+        let copies_objects = local_source.is_some() && !config.flags.no_local;
+        let without_tags = config.flags.no_tags && !copies_objects;
+        let mut repository = if borrowing || without_tags {
+            setup.clone_borrowing(
+                config.get_url(),
+                &repo_path,
+                &borrowed,
+                branch,
+                &remote_default,
+                &mut fetch_options,
+            )?
+        } else {
+            repo_builder.fetch_options(fetch_options);
+            repo_builder.clone(config.get_url(), &repo_path)?
+        };
+
+        // dissociate
+        if borrowing && config.flags.dissociate {
+            repository = dissociate(repository)?;
+        }
+
+        // mirror, without the remote HEAD libgit2 creates as there are no remote-tracking refs
+        if config.flags.mirror {
+            repository.set_head(&format!("refs/heads/{}", def_branch))?;
+            let remote_head = format!("refs/remotes/{}/HEAD", config.get_origin());
+            if let Ok(mut reference) = repository.find_reference(&remote_head) {
+                reference.delete()?;
+            }
+        }
+
+        // no-tags, libgit2 still creates the tag references of local clones copying the objects
+        if config.flags.no_tags && copies_objects && !borrowing {
+            for name in repository.tag_names(None)?.iter().flatten() {
+                repository
+                    .find_reference(&format!("refs/tags/{}", name))?
+                    .delete()?;
+            }
+        }
+
+        // separate-git-dir
+        if let Some(git_dir) = &config.flags.separate_git_dir {
+            repository = separate_git_dir(repository, git_dir)?;
+        }
 
         if let Some(pathspecs) = config.flags.recursive {
+            let shallow = config.flags.shallow_submodules;
             if pathspecs.is_empty() {
                 let submodule = repository.submodules()?;
                 for mut sub in submodule {
                     // git seems to ignore errors in cloning submodule
                     // TODO: investigate this
-                    let _ = sub.clone(None);
+                    let _ = sub.update(true, self.submodule_options(shallow).as_mut());
                 }
             }
             for pathspec in pathspecs {
@@ -366,7 +624,7 @@ impl GitRepository {
                     // git seems to ignore errors in cloning submodule, hence doing the same
                     // here...
                     // TODO: investigate this
-                    let _ = submodule.clone(None);
+                    let _ = submodule.update(true, self.submodule_options(shallow).as_mut());
                 }
             }
         }
@@ -376,6 +634,344 @@ impl GitRepository {
     }
 }
 
+impl GitRepository {
+    /// Returns the options submodules are cloned with, `None` for the defaults.
+    fn submodule_options(&self, shallow: bool) -> Option<SubmoduleUpdateOptions<'_>> {
+        if !shallow {
+            return None;
+        }
+        let mut callbacks = RemoteCallbacks::new();
+
+        // continue even if cert checks fail, if configured so
+        if self.bypass_certificate_check {
+            callbacks.certificate_check(|_, _| Ok(CertificateCheckStatus::CertificateOk));
+        }
+
+        // setting up credentials
+        callbacks.credentials(move |_a: &str, _b, _c| self.cred.get_cred());
+
+        let mut fetch_options = FetchOptions::new();
+        fetch_options.depth(1);
+        fetch_options.remote_callbacks(callbacks);
+        let mut options = SubmoduleUpdateOptions::new();
+        options.fetch(fetch_options);
+        Some(options)
+    }
+}
+
+/// The remote and configuration a new repository is set up with before fetching.
+#[derive(Clone)]
+struct CloneSetup {
+    origin: String,
+    refspec: String,
+    alternates: Vec<PathBuf>,
+    flags: CloneFlagsInternal,
+}
+
+impl CloneSetup {
+    /// Sets up `repo` for `--origin`, `--mirror`, `--config`, `--template`, `--reference` and
+    /// `--shared`, and returns the remote to fetch from.
+    fn configure<'r>(&self, repo: &'r Repository, url: &str) -> Result<Remote<'r>, Error> {
+        // template
+        if let Some(template) = &self.flags.template {
+            copy_template(template, repo.path())?;
+        }
+
+        // reference and shared
+        if !self.alternates.is_empty() {
+            add_alternates(repo, &self.alternates)?;
+        }
+
+        repo.remote_with_fetch(&self.origin, url, &self.refspec)?;
+        let mut git_config = repo.config()?;
+
+        // mirror
+        if self.flags.mirror {
+            git_config.set_bool(&format!("remote.{}.mirror", self.origin), true)?;
+        }
+
+        // no-tags
+        if self.flags.no_tags {
+            git_config.set_str(&format!("remote.{}.tagOpt", self.origin), "--no-tags")?;
+        }
+
+        // config
+        for (key, value) in &self.flags.config {
+            git_config.set_multivar(key, "^$", value)?;
+        }
+
+        // looked up again to pick up the refspecs and urls set through config
+        repo.find_remote(&self.origin)
+    }
+
+    /// Clones `url` into `path` the way `RepoBuilder::clone` does, for repositories borrowing
+    /// objects through alternates and clones without tags. libgit2 leaves out everything
+    /// reachable from the local references when fetching, so the `borrowed` tips get temporary
+    /// references while fetching and none of their history is downloaded or copied. `branch` is checked out and
+    /// set up to track the remote, `remote_default` is the branch the remote `HEAD` points to.
+    fn clone_borrowing(
+        &self,
+        url: &str,
+        path: &Path,
+        borrowed: &[Oid],
+        branch: Option<&str>,
+        remote_default: &str,
+        fetch_options: &mut FetchOptions,
+    ) -> Result<Repository, Error> {
+        if fs::read_dir(path).is_ok_and(|mut entries| entries.next().is_some()) {
+            let err_msg = format!("'{}' exists and is not an empty directory", path.display());
+            return Err(Error::from_str(&err_msg));
+        }
+        let existed = path.exists();
+
+        let cloned =
+            self.fetch_borrowing(url, path, borrowed, branch, remote_default, fetch_options);
+        // like libgit2, nothing of a failed clone is left behind
+        if cloned.is_err() {
+            let _ = fs::remove_dir_all(path);
+            if existed {
+                let _ = fs::create_dir(path);
+            }
+        }
+        cloned
+    }
+
+    fn fetch_borrowing(
+        &self,
+        url: &str,
+        path: &Path,
+        borrowed: &[Oid],
+        branch: Option<&str>,
+        remote_default: &str,
+        fetch_options: &mut FetchOptions,
+    ) -> Result<Repository, Error> {
+        let bare = self.flags.bare || self.flags.mirror;
+        let repository = match bare {
+            true => Repository::init_bare(path)?,
+            false => Repository::init(path)?,
+        };
+        let mut remote = self.configure(&repository, url)?;
+
+        // advertise the borrowed commits as already present
+        let mut tips = Vec::new();
+        for oid in borrowed {
+            let Ok(commit) = repository.find_object(*oid, None)?.peel_to_commit() else {
+                continue;
+            };
+            if !tips.contains(&commit.id()) {
+                tips.push(commit.id());
+            }
+        }
+        let mut temporary = Vec::new();
+        for (index, oid) in tips.iter().enumerate() {
+            let name = format!("refs/gittwo/alternates/{}", index);
+            repository.reference(&name, *oid, true, "clone: borrowed from alternates")?;
+            temporary.push(name);
+        }
+
+        let reflog = format!("clone: from {}", url);
+        let fetched = remote.fetch::<&str>(&[], Some(fetch_options), Some(&reflog));
+        for name in &temporary {
+            repository.find_reference(name)?.delete()?;
+        }
+        let _ = fs::remove_dir_all(repository.path().join("refs/gittwo"));
+        fetched?;
+        drop(remote);
+
+        // a mirror has no remote-tracking branch to check out, HEAD is set after cloning
+        let Some(branch) = branch else {
+            return Ok(repository);
+        };
+
+        // the local branch tracking the remote one, same as libgit2
+        let tracking = format!("refs/remotes/{}/{}", self.origin, branch);
+        let target = repository.find_reference(&tracking)?.peel_to_commit()?.id();
+        let local = format!("refs/heads/{}", branch);
+        repository.reference(&local, target, false, &reflog)?;
+        let mut git_config = repository.config()?;
+        git_config.set_str(&format!("branch.{}.remote", branch), &self.origin)?;
+        git_config.set_str(&format!("branch.{}.merge", branch), &local)?;
+        repository.set_head(&local)?;
+
+        // the remote HEAD, when its branch was fetched
+        let default = format!("refs/remotes/{}/{}", self.origin, remote_default);
+        if repository.find_reference(&default).is_ok() {
+            let remote_head = format!("refs/remotes/{}/HEAD", self.origin);
+            repository.reference_symbolic(&remote_head, &default, true, &reflog)?;
+        }
+
+        // no-checkout, the new work tree is empty so there is nothing to overwrite
+        if !bare && !self.flags.no_checkout {
+            repository.checkout_head(Some(CheckoutBuilder::new().force()))?;
+        }
+        Ok(repository)
+    }
+}
+
+/// Copies the files of a template directory into the `.git` directory of a new repository,
+/// replacing the defaults written by libgit2. The template `config` is not copied.
+fn copy_template(template: &Path, git_dir: &Path) -> Result<(), Error> {
+    let error = |e: std::io::Error| {
+        let err_msg = format!("failed copying template {}: {}", template.display(), e);
+        Error::from_str(&err_msg)
+    };
+    for entry in fs::read_dir(template).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let target = git_dir.join(entry.file_name());
+        if entry.file_type().map_err(error)?.is_dir() {
+            fs::create_dir_all(&target).map_err(error)?;
+            copy_template(&entry.path(), &target)?;
+        } else if entry.file_name() != "config" {
+            fs::copy(entry.path(), &target).map_err(error)?;
+        }
+    }
+    Ok(())
+}
+
+/// Opens a repository whose objects are borrowed through `--reference` or `--shared`, returning
+/// its object directory and the objects its references point to.
+fn open_alternate(path: &Path) -> Result<(PathBuf, Vec<Oid>), Error> {
+    let not_local = || {
+        let err_msg = format!(
+            "reference repository '{}' is not a local repository.",
+            path.display()
+        );
+        Error::from_str(&err_msg)
+    };
+    let repository = Repository::open(path).map_err(|_| not_local())?;
+    if repository.is_shallow() {
+        let err_msg = format!("reference repository '{}' is shallow", path.display());
+        return Err(Error::from_str(&err_msg));
+    }
+    let objects = fs::canonicalize(repository.path().join("objects")).map_err(|_| not_local())?;
+    let mut tips = Vec::new();
+    for reference in repository.references()? {
+        if let Some(oid) = reference?.target() {
+            tips.push(oid);
+        }
+    }
+    Ok((objects, tips))
+}
+
+/// Adds object directories to `objects/info/alternates` of a new repository.
+fn add_alternates(repo: &Repository, alternates: &[PathBuf]) -> Result<(), Error> {
+    let info = repo.path().join("objects").join("info");
+    let error = |e: std::io::Error| {
+        let err_msg = format!(
+            "failed writing {}: {}",
+            info.join("alternates").display(),
+            e
+        );
+        Error::from_str(&err_msg)
+    };
+
+    // the odb is loaded before writing the file, so every alternate is added exactly once
+    let odb = repo.odb()?;
+    let mut lines = String::new();
+    for objects in alternates {
+        odb.add_disk_alternate(&objects.to_string_lossy())?;
+        lines += &format!("{}\n", objects.display());
+    }
+    fs::create_dir_all(&info).map_err(error)?;
+    fs::write(info.join("alternates"), lines).map_err(error)
+}
+
+/// Copies every object borrowed through `objects/info/alternates` into the repository and
+/// removes the file. Like `git repack -a -d`, the objects reachable from the references are
+/// written to a single pack replacing every other pack and loose object, then the repository is
+/// reopened.
+fn dissociate(repository: Repository) -> Result<Repository, Error> {
+    let objects = repository.path().join("objects");
+    let error = |e: std::io::Error| {
+        let err_msg = format!("failed repacking {}: {}", objects.display(), e);
+        Error::from_str(&err_msg)
+    };
+
+    // loose object directories and pack files written while cloning
+    let mut previous = Vec::new();
+    for entry in fs::read_dir(&objects).map_err(error)? {
+        let entry = entry.map_err(error)?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if name.len() == 2 && name.chars().all(|c| c.is_ascii_hexdigit()) {
+            previous.push(entry.path());
+        }
+    }
+    for entry in fs::read_dir(objects.join("pack")).map_err(error)? {
+        previous.push(entry.map_err(error)?.path());
+    }
+
+    let mut walk = repository.revwalk()?;
+    let mut pack = repository.packbuilder()?;
+    for reference in repository.references()? {
+        let reference = reference?;
+        let Some(oid) = reference.target() else {
+            continue;
+        };
+        if let Ok(commit) = reference.peel_to_commit() {
+            walk.push(commit.id())?;
+        }
+        if repository.find_object(oid, None)?.kind() != Some(ObjectType::Commit) {
+            pack.insert_recursive(oid, None)?;
+        }
+    }
+    pack.insert_walk(&mut walk)?;
+    if pack.object_count() > 0 {
+        pack.write(&objects.join("pack"), 0)?;
+    }
+    let written = pack.name().map(|name| format!("pack-{}.", name));
+    drop(pack);
+    drop(walk);
+
+    let path = repository.path().to_path_buf();
+    drop(repository);
+    for previous in previous {
+        let name = previous.file_name().unwrap_or_default().to_string_lossy();
+        if written
+            .as_ref()
+            .is_some_and(|written| name.starts_with(written))
+        {
+            continue;
+        }
+        match previous.is_dir() {
+            true => fs::remove_dir_all(&previous).map_err(error)?,
+            false => fs::remove_file(&previous).map_err(error)?,
+        }
+    }
+
+    let alternates = objects.join("info/alternates");
+    fs::remove_file(&alternates).map_err(|e| {
+        let err_msg = format!("failed removing {}: {}", alternates.display(), e);
+        Error::from_str(&err_msg)
+    })?;
+    Repository::open(path)
+}
+
+/// Moves the `.git` directory of a freshly cloned repository to `git_dir` and replaces it with
+/// a `.git` file pointing there, then reopens the repository.
+fn separate_git_dir(repository: Repository, git_dir: &Path) -> Result<Repository, Error> {
+    let error = |e: std::io::Error| {
+        let err_msg = format!(
+            "failed moving the repository to {}: {}",
+            git_dir.display(),
+            e
+        );
+        Error::from_str(&err_msg)
+    };
+    let Some(workdir) = repository.workdir().map(Path::to_path_buf) else {
+        return Err(Error::from_str(
+            "a bare repository has no separate git directory",
+        ));
+    };
+    let current = repository.path().to_path_buf();
+    drop(repository);
+
+    fs::rename(&current, git_dir).map_err(error)?;
+    let git_dir = fs::canonicalize(git_dir).map_err(error)?;
+    let gitlink = format!("gitdir: {}\n", git_dir.display());
+    fs::write(workdir.join(".git"), gitlink).map_err(error)?;
+    Repository::open(&workdir)
+}
+
 struct ProgressCallbackHelper {
     last_update_time: SystemTime,
     last_throughput_update_time: SystemTime,
@@ -543,8 +1139,19 @@ impl ProgressCallbackHelper {
 #[cfg(test)]
 mod clone_test {
     use super::{CloneConfig, CloneFlags};
-    use crate::GitRepository;
-    use std::{io::BufRead, path::Path, process::Command};
+    use crate::{
+        GitRepository,
+        helpers::test_util::{git, git_output},
+    };
+    use std::{
+        env, fs,
+        io::BufRead,
+        net::{TcpListener, TcpStream},
+        path::Path,
+        process::{Command, Stdio},
+        thread,
+        time::Duration,
+    };
 
     #[test]
     fn git_clone_depth_test() {
@@ -563,24 +1170,26 @@ mod clone_test {
         config.add_flag(CloneFlags::Depth(Some(1)));
         repo.git_clone(config).unwrap();
 
-        // verify that a single commit is cloned in
-        let out = Command::new("git")
-            .args([
-                "-C",
-                "./temp_test/clone_depth/git2-rs/",
-                "rev-list",
-                "--count",
-                "--all",
-            ])
-            .output()
-            .unwrap();
+        // verify that every branch is cloned in with a single commit
+        let work = Path::new("./temp_test/clone_depth/git2-rs/");
+        let branches = git(
+            work,
+            &["for-each-ref", "--format=%(refname)", "refs/remotes"],
+        );
+        let counts: Vec<String> = branches
+            .lines()
+            .map(|branch| git(work, &["rev-list", "--count", branch]))
+            .collect();
+        let heads = git(work, &["ls-remote", "--heads", "origin"]);
 
         Command::new("rm")
             .args(["-rf", "./temp_test/clone_depth/"])
             .output()
             .unwrap();
 
-        assert_eq!(String::from_utf8_lossy(&out.stdout), "1\n");
+        // the remote branches and the remote HEAD
+        assert_eq!(branches.lines().count(), heads.lines().count() + 1);
+        assert!(counts.iter().all(|count| count == "1\n"));
         assert!(repo.is_valid());
     }
 
@@ -691,4 +1300,514 @@ mod clone_test {
         assert_eq!(out, 2);
         assert!(repo.is_valid());
     }
+
+    #[test]
+    fn git_clone_origin_mirror_config_template_test() {
+        let dir_name = "./temp_test/clone_flags/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", &format!("{}src", dir_name)])
+            .output()
+            .unwrap();
+
+        // create a source repository with two branches and a tag, and a template directory
+        let path = env::current_dir().unwrap().join(dir_name);
+        let src = path.join("src");
+        git(&src, &["init", "--initial-branch", "main"]);
+        git(&src, &["config", "user.name", "Cloner"]);
+        git(&src, &["config", "user.email", "cloner@example.com"]);
+        fs::write(src.join("file.txt"), "content\n").unwrap();
+        git(&src, &["add", "file.txt"]);
+        git(&src, &["commit", "-m", "first"]);
+        git(&src, &["branch", "feature"]);
+        git(&src, &["tag", "v1"]);
+        fs::create_dir_all(path.join("template/hooks")).unwrap();
+        fs::write(path.join("template/hooks/pre-commit"), "#!/bin/sh\n").unwrap();
+        fs::write(path.join("template/description"), "from template\n").unwrap();
+
+        // clone with a custom origin, config and template into a separate git directory
+        let mut repo = GitRepository::new();
+        let mut config = CloneConfig::new(src.to_str().unwrap().to_string(), &path);
+        config.custom_clone_directory("work");
+        config.add_flag(CloneFlags::Origin(Some("upstream".to_string())));
+        config.add_flag(CloneFlags::NoCheckout(true));
+        config.add_flag(CloneFlags::NoTags(true));
+        config.add_flag(CloneFlags::Config(vec![
+            ("test.multi".to_string(), "a".to_string()),
+            ("test.multi".to_string(), "b".to_string()),
+        ]));
+        config.add_flag(CloneFlags::Template(Some(path.join("template"))));
+        config.add_flag(CloneFlags::SeparateGitDir(Some(path.join("work.git"))));
+        repo.git_clone(config).unwrap();
+        let work = path.join("work");
+        let remotes = git(&work, &["remote"]);
+        let tracking = git(
+            &work,
+            &["for-each-ref", "--format=%(refname)", "refs/remotes"],
+        );
+        let tags = git(&work, &["tag", "-l"]);
+        let tag_opt = git(&work, &["config", "remote.upstream.tagOpt"]);
+        let multi = git(&work, &["config", "--get-all", "test.multi"]);
+        let head = git(&work, &["symbolic-ref", "HEAD"]);
+        let checked_out = work.join("file.txt").exists();
+        let gitlink = work.join(".git").is_file();
+        let hook = path.join("work.git/hooks/pre-commit").exists();
+        let description = fs::read_to_string(path.join("work.git/description")).unwrap();
+
+        // mirror clone
+        let mut mirror = GitRepository::new();
+        let mut config = CloneConfig::new(src.to_str().unwrap().to_string(), &path);
+        config.add_flag(CloneFlags::Mirror(true));
+        let mirror_dir = config.get_clone_dir_name();
+        mirror.git_clone(config).unwrap();
+        let mirror_path = path.join(&mirror_dir);
+        let mirror_refs = git(&mirror_path, &["for-each-ref", "--format=%(refname)"]);
+        let mirror_config = git(&mirror_path, &["config", "remote.origin.mirror"]);
+        let mirror_bare = git(&mirror_path, &["rev-parse", "--is-bare-repository"]);
+        let invalid = {
+            let mut config = CloneConfig::new(src.to_str().unwrap().to_string(), &path);
+            config.add_flag(CloneFlags::Mirror(true));
+            config.add_flag(CloneFlags::SeparateGitDir(Some(path.join("invalid.git"))));
+            GitRepository::new().git_clone(config)
+        };
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(repo.is_valid());
+        assert_eq!(remotes, "upstream\n");
+        assert_eq!(
+            tracking,
+            "refs/remotes/upstream/HEAD\nrefs/remotes/upstream/feature\nrefs/remotes/upstream/main\n"
+        );
+        assert_eq!(tags, "");
+        assert_eq!(tag_opt, "--no-tags\n");
+        assert_eq!(multi, "a\nb\n");
+        assert_eq!(head, "refs/heads/main\n");
+        assert!(!checked_out);
+        assert!(gitlink);
+        assert!(hook);
+        assert_eq!(description, "from template\n");
+
+        assert_eq!(mirror_dir, "src.git");
+        assert_eq!(
+            mirror_refs,
+            "refs/heads/feature\nrefs/heads/main\nrefs/tags/v1\n"
+        );
+        assert_eq!(mirror_config, "true\n");
+        assert_eq!(mirror_bare, "true\n");
+        assert!(invalid.is_err());
+    }
+
+    #[test]
+    fn git_clone_no_tags_test() {
+        let dir_name = "./temp_test/clone_no_tags/";
+        // reserve a free port for the daemon, it is released again before the daemon binds it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", &format!("{}src", dir_name)])
+            .output()
+            .unwrap();
+
+        // create a source repository with an annotated tag on main and one on a commit that
+        // is only reachable from the tag
+        let path = env::current_dir().unwrap().join(dir_name);
+        let src = path.join("src");
+        git(&src, &["init", "--initial-branch", "main"]);
+        git(&src, &["config", "user.name", "Cloner"]);
+        git(&src, &["config", "user.email", "cloner@example.com"]);
+        git(&src, &["commit", "--allow-empty", "-m", "first"]);
+        git(&src, &["tag", "-a", "-m", "release", "v1"]);
+        let tree = git(&src, &["rev-parse", "HEAD^{tree}"]);
+        let orphan = git(&src, &["commit-tree", tree.trim(), "-m", "orphan"]);
+        let orphan = orphan.trim().to_string();
+        git(&src, &["tag", "-a", "-m", "orphan", "orphan", &orphan]);
+
+        // serve the repository over the git protocol, the local transport sends every tag
+        let mut daemon = Command::new("git")
+            .args([
+                "daemon",
+                "--export-all",
+                "--reuseaddr",
+                "--listen=127.0.0.1",
+            ])
+            .arg(format!("--port={}", port))
+            .arg(format!("--base-path={}", path.display()))
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("git daemon needs to be installed for no-tags clone tests");
+        let listening = (0..50).any(|_| {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(100));
+            false
+        });
+        if !listening {
+            let _ = daemon.kill();
+            let _ = daemon.wait();
+            Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+            panic!("git daemon is not listening on port {} after 5s", port);
+        }
+
+        let url = format!("git://127.0.0.1:{}/src", port);
+        let clone = |dir: &str, flags: Vec<CloneFlags>| {
+            let mut config = CloneConfig::new(url.clone(), &path);
+            config.custom_clone_directory(dir);
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            GitRepository::new().git_clone(config).unwrap();
+            let work = path.join(dir);
+            let tags = git(&work, &["tag", "-l"]);
+            // libgit2 always asks for the tags pointing into the fetched history, the history
+            // only reachable from tags is what must not be downloaded
+            let orphan = git_output(&work, &["cat-file", "-e", &orphan])
+                .status
+                .success();
+            (tags, orphan)
+        };
+
+        // without tags and with every tag, then the same while borrowing objects
+        let no_tags = clone("no_tags", vec![CloneFlags::NoTags(true)]);
+        let tags = clone("tags", vec![]);
+        let reference = CloneFlags::Reference(vec![path.join("no_tags")]);
+        let borrowed = clone("borrowed", vec![reference]);
+        let reference = CloneFlags::Reference(vec![path.join("no_tags")]);
+        let borrowed_no_tags = clone(
+            "borrowed_no_tags",
+            vec![
+                reference,
+                CloneFlags::Dissociate(true),
+                CloneFlags::NoTags(true),
+            ],
+        );
+
+        let _ = daemon.kill();
+        let _ = daemon.wait();
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert_eq!(no_tags, (String::new(), false));
+        assert_eq!(tags, ("orphan\nv1\n".to_string(), true));
+        assert_eq!(borrowed, ("orphan\nv1\n".to_string(), true));
+        assert_eq!(borrowed_no_tags, (String::new(), false));
+    }
+
+    #[test]
+    fn git_clone_shallow_all_branches_test() {
+        let dir_name = "./temp_test/clone_shallow/";
+        // reserve a free port for the daemon, it is released again before the daemon binds it
+        let port = TcpListener::bind("127.0.0.1:0")
+            .unwrap()
+            .local_addr()
+            .unwrap()
+            .port();
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", &format!("{}src", dir_name)])
+            .output()
+            .unwrap();
+
+        // create a source repository with two branches and a submodule with two commits
+        let path = env::current_dir().unwrap().join(dir_name);
+        let src = path.join("src");
+        let sub = path.join("sub");
+        git(&path, &["init", "--initial-branch", "main", "sub"]);
+        git(&sub, &["config", "user.name", "Cloner"]);
+        git(&sub, &["config", "user.email", "cloner@example.com"]);
+        git(&sub, &["commit", "--allow-empty", "-m", "sub first"]);
+        git(&sub, &["commit", "--allow-empty", "-m", "sub second"]);
+        // serve the repositories over the git protocol, shallow fetches are not supported by
+        // the local transport
+        let mut daemon = Command::new("git")
+            .args([
+                "daemon",
+                "--export-all",
+                "--reuseaddr",
+                "--listen=127.0.0.1",
+            ])
+            .arg(format!("--port={}", port))
+            .arg(format!("--base-path={}", path.display()))
+            .arg(&path)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .spawn()
+            .expect("git daemon needs to be installed for shallow clone tests");
+        let listening = (0..50).any(|_| {
+            if TcpStream::connect(("127.0.0.1", port)).is_ok() {
+                return true;
+            }
+            thread::sleep(Duration::from_millis(100));
+            false
+        });
+        if !listening {
+            let _ = daemon.kill();
+            let _ = daemon.wait();
+            Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+            panic!("git daemon is not listening on port {} after 5s", port);
+        }
+
+        git(&src, &["init", "--initial-branch", "main"]);
+        git(&src, &["config", "user.name", "Cloner"]);
+        git(&src, &["config", "user.email", "cloner@example.com"]);
+        let sub_url = format!("git://127.0.0.1:{}/sub", port);
+        git(&src, &["submodule", "add", &sub_url, "sub"]);
+        git(&src, &["commit", "-m", "first"]);
+        git(&src, &["commit", "--allow-empty", "-m", "second"]);
+        git(&src, &["branch", "feature"]);
+        git(&src, &["commit", "--allow-empty", "-m", "third"]);
+        let mut tips = vec![
+            git(&src, &["rev-parse", "main"]).trim().to_string(),
+            git(&src, &["rev-parse", "feature"]).trim().to_string(),
+        ];
+        tips.sort();
+
+        let url = format!("git://127.0.0.1:{}/src", port);
+        let clone = |dir: &str, flags: Vec<CloneFlags>| {
+            let mut repo = GitRepository::new();
+            let mut config = CloneConfig::new(url.clone(), &path);
+            config.custom_clone_directory(dir);
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            repo.git_clone(config).unwrap();
+            let work = path.join(dir);
+            let branches = git(&work, &["branch", "--remotes", "--format=%(refname)"]);
+            let count = git(&work, &["rev-list", "--count", "--all"]);
+            (repo, branches, count)
+        };
+
+        // every branch, only the default branch, and every branch again after overriding
+        let (all, all_branches, all_count) = clone("all", vec![CloneFlags::Depth(Some(1))]);
+        let mut roots: Vec<String> = all
+            .shallow_roots()
+            .unwrap()
+            .iter()
+            .map(|oid| oid.to_string())
+            .collect();
+        roots.sort();
+        let all_shallow = all.is_shallow().unwrap();
+        let (_, single_branches, single_count) = clone(
+            "single",
+            vec![CloneFlags::Depth(Some(1)), CloneFlags::SingleBranch(true)],
+        );
+        let (_, no_single_branches, _) = clone(
+            "no_single",
+            vec![
+                CloneFlags::Depth(Some(1)),
+                CloneFlags::SingleBranch(true),
+                CloneFlags::NoSingleBranch(true),
+            ],
+        );
+
+        // shallow submodules in a full clone
+        let (full, _, full_count) = clone(
+            "full",
+            vec![
+                CloneFlags::Recursive(Some(vec![])),
+                CloneFlags::ShallowSubmodules(true),
+            ],
+        );
+        let full_shallow = full.is_shallow().unwrap();
+        let full_roots = full.shallow_roots().unwrap();
+        let sub_count = git(path.join("full/sub"), &["rev-list", "--count", "HEAD"]);
+
+        let _ = daemon.kill();
+        let _ = daemon.wait();
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert_eq!(
+            all_branches,
+            "refs/remotes/origin/HEAD\nrefs/remotes/origin/feature\nrefs/remotes/origin/main\n"
+        );
+        assert_eq!(all_count, "2\n");
+        assert!(all_shallow);
+        assert_eq!(roots, tips);
+
+        assert_eq!(
+            single_branches,
+            "refs/remotes/origin/HEAD\nrefs/remotes/origin/main\n"
+        );
+        assert_eq!(single_count, "1\n");
+        assert_eq!(no_single_branches, all_branches);
+
+        assert!(!full_shallow);
+        assert!(full_roots.is_empty());
+        assert_eq!(full_count, "3\n");
+        assert_eq!(sub_count, "1\n");
+    }
+
+    #[test]
+    fn git_clone_local_reference_shared_test() {
+        let dir_name = "./temp_test/clone_local/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", &format!("{}src", dir_name)])
+            .output()
+            .unwrap();
+
+        // a packed source repository with a tag
+        let path = env::current_dir().unwrap().join(dir_name);
+        let src = path.join("src");
+        git(&src, &["init", "--initial-branch", "main"]);
+        git(&src, &["config", "user.name", "Cloner"]);
+        git(&src, &["config", "user.email", "cloner@example.com"]);
+        for i in 0..3 {
+            fs::write(src.join("file.txt"), i.to_string()).unwrap();
+            git(&src, &["add", "file.txt"]);
+            git(&src, &["commit", "-m", &format!("commit {}", i)]);
+        }
+        git(&src, &["tag", "-a", "-m", "tag", "v1"]);
+        git(&src, &["gc", "--quiet"]);
+
+        let clone = |dir: &str, url: String, flags: Vec<CloneFlags>| {
+            let mut repo = GitRepository::new();
+            let mut config = CloneConfig::new(url, &path);
+            config.custom_clone_directory(dir);
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            repo.git_clone(config).unwrap();
+            path.join(dir)
+        };
+        let objects = |dir: &Path| {
+            git(dir, &["count-objects", "-v"])
+                .lines()
+                .filter(|line| line.starts_with("count:") || line.starts_with("in-pack:"))
+                .collect::<Vec<&str>>()
+                .join(" ")
+        };
+        let src_url = src.to_string_lossy().to_string();
+        let file_url = format!("file://{}", src_url);
+        let src_objects = fs::canonicalize(src.join(".git/objects")).unwrap();
+
+        // local clones hardlink the object files unless told otherwise
+        let linked = clone("linked", src_url.clone(), vec![]);
+        let copied = clone(
+            "copied",
+            src_url.clone(),
+            vec![CloneFlags::NoHardlinks(true)],
+        );
+        let transport = clone("transport", src_url.clone(), vec![CloneFlags::Local(false)]);
+        #[cfg(unix)]
+        let hardlinked = [&linked, &copied, &transport].map(|dir| shares_pack_file(dir, &src));
+        let copied_count = git(&copied, &["rev-list", "--count", "HEAD"]);
+        let transport_count = git(&transport, &["rev-list", "--count", "HEAD"]);
+
+        // shared borrows the objects of the source
+        let shared = clone("shared", src_url.clone(), vec![CloneFlags::Shared(true)]);
+        let shared_alternates =
+            fs::read_to_string(shared.join(".git/objects/info/alternates")).unwrap();
+        let shared_objects = objects(&shared);
+        let shared_fsck = git_output(&shared, &["fsck"]).status.success();
+        let shared_count = git(&shared, &["rev-list", "--count", "HEAD"]);
+        let shared_refs = git(&shared, &["for-each-ref", "--format=%(refname)"]);
+        let shared_status = git(&shared, &["status", "--porcelain", "--branch"]);
+        let shared_file = fs::read_to_string(shared.join("file.txt")).unwrap();
+
+        // a reference fetched through the transport, only the commit missing from the reference
+        // is downloaded
+        fs::write(src.join("file.txt"), "3").unwrap();
+        git(&src, &["commit", "-am", "commit 3"]);
+        let referenced = clone(
+            "referenced",
+            file_url.clone(),
+            vec![CloneFlags::Reference(vec![linked.clone()])],
+        );
+        let referenced_alternates =
+            fs::read_to_string(referenced.join(".git/objects/info/alternates")).unwrap();
+        let linked_objects = fs::canonicalize(linked.join(".git/objects")).unwrap();
+        let referenced_objects = objects(&referenced);
+        let referenced_fsck = git_output(&referenced, &["fsck"]).status.success();
+
+        // dissociating copies the borrowed objects
+        let dissociated = clone(
+            "dissociated",
+            file_url,
+            vec![
+                CloneFlags::Reference(vec![linked.clone()]),
+                CloneFlags::Dissociate(true),
+            ],
+        );
+        let dissociated_alternates = dissociated.join(".git/objects/info/alternates").exists();
+        let dissociated_objects = objects(&dissociated);
+        let dissociated_fsck = git_output(&dissociated, &["fsck"]).status.success();
+        let dissociated_count = git(&dissociated, &["rev-list", "--count", "HEAD"]);
+
+        // an invalid reference
+        let mut repo = GitRepository::new();
+        let mut config = CloneConfig::new(src_url, &path);
+        config.custom_clone_directory("invalid");
+        config.add_flag(CloneFlags::Reference(vec![path.join("missing")]));
+        let invalid = repo.git_clone(config).unwrap_err();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        #[cfg(unix)]
+        assert_eq!(hardlinked, [true, false, false]);
+        assert_eq!(copied_count, "3\n");
+        assert_eq!(transport_count, "3\n");
+
+        assert_eq!(shared_alternates, format!("{}\n", src_objects.display()));
+        assert_eq!(shared_objects, "count: 0 in-pack: 0");
+        assert!(shared_fsck);
+        assert_eq!(shared_count, "3\n");
+        assert_eq!(
+            shared_refs,
+            "refs/heads/main\nrefs/remotes/origin/HEAD\nrefs/remotes/origin/main\nrefs/tags/v1\n"
+        );
+        assert_eq!(shared_status, "## main...origin/main\n");
+        assert_eq!(shared_file, "2");
+
+        assert_eq!(
+            referenced_alternates,
+            format!("{}\n", linked_objects.display())
+        );
+        // only commit 3 is new, but libgit2's local transport always sends the annotated tag
+        // with the commit, tree and blob it points to, the borrowed history isn't sent
+        assert_eq!(referenced_objects, "count: 0 in-pack: 7");
+        assert!(referenced_fsck);
+
+        assert!(!dissociated_alternates);
+        assert_eq!(dissociated_objects, "count: 0 in-pack: 13");
+        assert!(dissociated_fsck);
+        assert_eq!(dissociated_count, "4\n");
+
+        assert_eq!(
+            invalid.message(),
+            format!(
+                "reference repository '{}' is not a local repository.",
+                path.join("missing").display()
+            )
+        );
+    }
+
+    /// Returns true if a pack file of the repository in `dir` is a hardlink to one in `src`.
+    #[cfg(unix)]
+    fn shares_pack_file(dir: &Path, src: &Path) -> bool {
+        use std::os::unix::fs::MetadataExt;
+
+        let inodes = |dir: &Path| -> Vec<u64> {
+            fs::read_dir(dir.join(".git/objects/pack"))
+                .map(|entries| {
+                    entries
+                        .flatten()
+                        .map(|entry| entry.metadata().unwrap().ino())
+                        .collect()
+                })
+                .unwrap_or_default()
+        };
+        let src_inodes = inodes(src);
+        inodes(dir).iter().any(|inode| src_inodes.contains(inode))
+    }
 }
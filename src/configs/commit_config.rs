@@ -1,12 +1,23 @@
-use std::path::Path;
+use std::{
+    env,
+    path::{Path, PathBuf},
+};
 
-use crate::GitRepository;
-use git2::{Error, Repository, Signature, StatusOptions};
+use crate::{
+    GitRepository,
+    helpers::{
+        date, hooks,
+        signing::{self, Signer},
+    },
+};
+use git2::{Commit, Error, ErrorCode, Oid, Repository, Signature, StatusOptions};
 
 pub struct CommitConfig {
     name: String,
     email: String,
+    author: Option<(String, String)>,
     flags: CommitFlagsInternals,
+    signer: Option<Box<dyn Signer>>,
 }
 
 impl CommitConfig {
@@ -14,7 +25,9 @@ impl CommitConfig {
         CommitConfig {
             name,
             email,
+            author: None,
             flags: CommitFlagsInternals::default(),
+            signer: None,
         }
     }
 
@@ -22,7 +35,9 @@ impl CommitConfig {
         let mut config = CommitConfig {
             name,
             email,
+            author: None,
             flags: CommitFlagsInternals::default(),
+            signer: None,
         };
         config.add_flag(CommitFlags::Message(message));
         config
@@ -32,10 +47,50 @@ impl CommitConfig {
         match flag {
             CommitFlags::Message(msg) => self.flags.message = msg,
             CommitFlags::AllowEmptyMessage(allow) => self.flags.allow_empty_message = allow,
+            CommitFlags::Amend(amend) => self.flags.amend = amend,
+            CommitFlags::All(all) => self.flags.all = all,
+            CommitFlags::AllowEmpty(allow) => self.flags.allow_empty = allow,
+            CommitFlags::Author(author) => self.flags.author = author,
+            CommitFlags::Date(date) => self.flags.date = date,
+            CommitFlags::File(file) => self.flags.file = file,
+            CommitFlags::Cleanup(cleanup) => self.flags.cleanup = cleanup,
+            CommitFlags::Fixup(commit) => self.flags.fixup = commit,
+            CommitFlags::Squash(commit) => self.flags.squash = commit,
+            CommitFlags::GpgSign(key) => {
+                self.flags.gpg_sign = Some(true);
+                self.flags.signing_key = key;
+            }
+            CommitFlags::NoGpgSign(no_sign) => {
+                self.flags.gpg_sign = if no_sign { Some(false) } else { None };
+            }
+            CommitFlags::NoVerify(no_verify) => self.flags.no_verify = no_verify,
         }
         self
     }
 
+    /// Sets a custom `Signer` used whenever the commit is signed, instead of the signer
+    /// configured through `gpg.format` and `user.signingkey`.
+    pub fn set_signer(&mut self, signer: impl Signer + 'static) {
+        self.signer = Some(Box::new(signer));
+    }
+
+    /// Creates a `CommitConfig` with the identity git would use when committing in
+    /// `repository`.
+    ///
+    /// The committer is resolved from `GIT_COMMITTER_NAME`/`GIT_COMMITTER_EMAIL`, then
+    /// `committer.name`/`committer.email`, then `user.name`/`user.email` from the repository,
+    /// global and system config. The author is resolved the same way from the `GIT_AUTHOR_*`
+    /// variables and `author.*` config. Unlike `CommitFlags::Author`, it is only used for new
+    /// commits, amending keeps the author of the replaced commit. Returns an error if no
+    /// identity can be found.
+    pub fn from_git_config(repository: &GitRepository) -> Result<Self, Error> {
+        let repository = repository.get_repository()?;
+        let (name, email) = resolve_identity(repository, "committer")?;
+        let mut config = CommitConfig::new(name, email);
+        config.author = Some(resolve_identity(repository, "author")?);
+        Ok(config)
+    }
+
     pub fn set_name(&mut self, name: String) {
         self.name = name;
     }
@@ -57,20 +112,308 @@ impl CommitConfig {
         self.email.clone()
     }
 
-    fn get_signature(&self) -> Result<Signature, Error> {
+    fn get_signature(&self) -> Result<Signature<'_>, Error> {
         Signature::now(&self.name, &self.email)
     }
+
+    /// Builds the author signature, `amended` is the commit being replaced when `--amend` is
+    /// set, whose author is kept unless `--author` is given and whose author date is kept
+    /// unless `--date` is given.
+    fn get_author(&self, amended: Option<&Commit>) -> Result<Signature<'static>, Error> {
+        let (name, email) = match &self.flags.author {
+            Some(author) => parse_identity(author)?,
+            None => match amended {
+                Some(commit) => {
+                    let author = commit.author();
+                    (
+                        String::from_utf8_lossy(author.name_bytes()).to_string(),
+                        String::from_utf8_lossy(author.email_bytes()).to_string(),
+                    )
+                }
+                None => match &self.author {
+                    Some(author) => author.clone(),
+                    None => (self.name.clone(), self.email.clone()),
+                },
+            },
+        };
+
+        let time = match (&self.flags.date, amended) {
+            (Some(date), _) => date::parse_date(date)?,
+            // git keeps the original author date on amend, even when the author is replaced
+            (None, Some(commit)) => commit.author().when(),
+            (None, None) => Signature::now(&name, &email)?.when(),
+        };
+
+        Signature::new(&name, &email, &time)
+    }
+
+    /// Builds the commit message from `-m`, `-F`, `--fixup`, `--squash` and `--amend`.
+    fn get_message(
+        &self,
+        repository: &Repository,
+        amended: Option<&Commit>,
+    ) -> Result<String, Error> {
+        let mut message = match &self.flags.file {
+            Some(file) => {
+                if !self.flags.message.is_empty() {
+                    return Err(Error::from_str("Option -m cannot be combined with -F."));
+                }
+                std::fs::read_to_string(file).map_err(|e| {
+                    Error::from_str(&format!(
+                        "could not read log file '{}': {}",
+                        file.display(),
+                        e
+                    ))
+                })?
+            }
+            None => self.flags.message.clone(),
+        };
+
+        if let Some(spec) = &self.flags.fixup {
+            let target = repository.revparse_single(spec)?.peel_to_commit()?;
+            message = GitRepository::prefixed_message("fixup!", &target, &message);
+        } else if let Some(spec) = &self.flags.squash {
+            let target = repository.revparse_single(spec)?.peel_to_commit()?;
+            message = GitRepository::prefixed_message("squash!", &target, &message);
+        }
+
+        // reuse the message of the amended commit if no new message is provided
+        if let Some(commit) = amended
+            && message.is_empty()
+            && self.flags.file.is_none()
+        {
+            message = String::from_utf8_lossy(commit.message_bytes()).to_string();
+        }
+
+        Ok(message)
+    }
+
+    /// Cleans up the message according to `--cleanup`.
+    fn clean_message(&self, message: String) -> Result<String, Error> {
+        let message = match self.flags.cleanup {
+            CommitCleanup::Verbatim => message,
+            CommitCleanup::Whitespace | CommitCleanup::Default => {
+                git2::message_prettify(message, None)?
+            }
+            CommitCleanup::Strip => git2::message_prettify(message, Some(b'#'))?,
+            CommitCleanup::Scissors => {
+                let mut kept = String::new();
+                for line in message.split_inclusive('\n') {
+                    if line.starts_with('#') && line.contains(" >8 ") {
+                        break;
+                    }
+                    kept.push_str(line);
+                }
+                git2::message_prettify(kept, None)?
+            }
+        };
+
+        Ok(message)
+    }
+}
+
+/// Resolves the name and email for `role` ("author" or "committer") the way git does.
+fn resolve_identity(repository: &Repository, role: &str) -> Result<(String, String), Error> {
+    let config = repository.config()?;
+    let lookup = |field: &str| -> Option<String> {
+        let var = format!("GIT_{}_{}", role.to_uppercase(), field.to_uppercase());
+        if let Ok(value) = env::var(var)
+            && !value.is_empty()
+        {
+            return Some(value);
+        }
+        if let Ok(value) = config.get_string(&format!("{}.{}", role, field)) {
+            return Some(value);
+        }
+        if let Ok(value) = config.get_string(&format!("user.{}", field)) {
+            return Some(value);
+        }
+        if field == "email" {
+            return env::var("EMAIL").ok().filter(|email| !email.is_empty());
+        }
+        None
+    };
+
+    let name = lookup("name");
+    let email = lookup("email");
+    if let (Some(name), Some(email)) = (name.clone(), email.clone()) {
+        return Ok((name, email));
+    }
+
+    // fall back to the default signature of the repository for whatever is still missing
+    match repository.signature() {
+        Ok(signature) => Ok((
+            name.unwrap_or_else(|| String::from_utf8_lossy(signature.name_bytes()).to_string()),
+            email.unwrap_or_else(|| String::from_utf8_lossy(signature.email_bytes()).to_string()),
+        )),
+        Err(_) => {
+            let role = if role == "author" {
+                "Author"
+            } else {
+                "Committer"
+            };
+            Err(Error::from_str(&format!(
+                "{} identity unknown, please set user.name and user.email in your git config",
+                role
+            )))
+        }
+    }
+}
+
+/// Parses an identity of the form `A U Thor <author@example.com>`.
+fn parse_identity(identity: &str) -> Result<(String, String), Error> {
+    let error = format!("--author '{}' is not in 'Name <email>' form", identity);
+    let (name, rest) = identity
+        .split_once('<')
+        .ok_or_else(|| Error::from_str(&error))?;
+    let email = rest
+        .strip_suffix('>')
+        .ok_or_else(|| Error::from_str(&error))?;
+    Ok((name.trim().to_string(), email.trim().to_string()))
 }
 
 #[derive(Default, Clone)]
 pub(crate) struct CommitFlagsInternals {
     message: String,
     allow_empty_message: bool,
+    amend: bool,
+    all: bool,
+    allow_empty: bool,
+    author: Option<String>,
+    date: Option<String>,
+    file: Option<PathBuf>,
+    cleanup: CommitCleanup,
+    fixup: Option<String>,
+    squash: Option<String>,
+    gpg_sign: Option<bool>,
+    signing_key: Option<String>,
+    no_verify: bool,
 }
 
+/// Represents flags that can be applied to a `git commit` command.
+/// See [git commit documentation](https://git-scm.com/docs/git-commit) for more details on each flag.
 pub enum CommitFlags {
+    /// Corresponds to the `-m <msg>` or `--message <msg>` flag.
     Message(String),
+
+    /// Corresponds to the `--allow-empty-message` flag.
+    ///
+    /// Defaults to `false`.
     AllowEmptyMessage(bool),
+
+    /// Corresponds to the [`--amend`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---amend)
+    /// flag.
+    /// `true` replaces the tip of the current branch. The message and author of the replaced
+    /// commit are kept unless a new message or `--author` is provided, its author date is kept
+    /// unless `--date` is provided.
+    ///
+    /// Defaults to `false`.
+    Amend(bool),
+
+    /// Corresponds to the [`--all`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---all)
+    /// or `-a` flag.
+    /// `true` stages all modified and deleted tracked files before committing.
+    ///
+    /// Defaults to `false`.
+    All(bool),
+
+    /// Corresponds to the [`--allow-empty`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---allow-empty)
+    /// flag.
+    /// `true` allows recording a commit that has the exact same tree as its parent.
+    ///
+    /// Defaults to `false`.
+    AllowEmpty(bool),
+
+    /// Corresponds to the [`--author <author>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---authorltauthorgt)
+    /// flag.
+    /// `Some("A U Thor <author@example.com>")` overrides the author, the committer is still
+    /// the name and email of the `CommitConfig`.
+    ///
+    /// Defaults to `None`.
+    Author(Option<String>),
+
+    /// Corresponds to the [`--date <date>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---dateltdategt)
+    /// flag.
+    /// `Some(date)` overrides the author date. Accepts the git internal format
+    /// (`1112911993 +0200`), ISO 8601 (`2005-04-07T22:13:13+02:00`) and RFC 2822
+    /// (`Thu, 07 Apr 2005 22:13:13 +0200`).
+    ///
+    /// Defaults to `None`.
+    Date(Option<String>),
+
+    /// Corresponds to the [`--file <file>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---fileltfilegt)
+    /// or `-F` flag.
+    /// `Some(path)` takes the commit message from the given file. Cannot be combined with
+    /// `Message`.
+    ///
+    /// Defaults to `None`.
+    File(Option<PathBuf>),
+
+    /// Corresponds to the [`--cleanup <mode>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---cleanupltmodegt)
+    /// flag.
+    ///
+    /// Defaults to `CommitCleanup::Default`.
+    Cleanup(CommitCleanup),
+
+    /// Corresponds to the [`--fixup <commit>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---fixupamendrewordltcommitgt)
+    /// flag.
+    /// `Some(commit)` creates a message of the form `fixup! <subject of commit>`, any message
+    /// passed in is added as the body.
+    ///
+    /// Defaults to `None`.
+    Fixup(Option<String>),
+
+    /// Corresponds to the [`--squash <commit>`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---squashltcommitgt)
+    /// flag.
+    /// `Some(commit)` creates a message of the form `squash! <subject of commit>`, any message
+    /// passed in is added as the body.
+    ///
+    /// Defaults to `None`.
+    Squash(Option<String>),
+
+    /// Corresponds to the [`--gpg-sign[=<keyid>]`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---gpg-signltkeyidgt)
+    /// or `-S` flag.
+    /// Signs the commit using the signer configured through `gpg.format`. `Some(key)` signs
+    /// with the given key, `None` uses `user.signingkey` or the committer identity.
+    ///
+    /// Commits are signed by default when `commit.gpgsign` is set.
+    GpgSign(Option<String>),
+
+    /// Corresponds to the `--no-gpg-sign` flag.
+    /// `true` does not sign the commit, overriding `commit.gpgsign` and any `GpgSign` flag.
+    ///
+    /// Defaults to `false`.
+    NoGpgSign(bool),
+
+    /// Corresponds to the [`--no-verify`](https://git-scm.com/docs/git-commit#Documentation/git-commit.txt---no-verify)
+    /// or `-n` flag.
+    /// `true` bypasses the `pre-commit` and `commit-msg` hooks. Hooks are only run when enabled
+    /// with `GitRepository::run_hooks`.
+    ///
+    /// Defaults to `false`.
+    NoVerify(bool),
+}
+
+/// Modes for cleaning up the commit message, see `CommitFlags::Cleanup`.
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommitCleanup {
+    /// Strip leading and trailing empty lines, trailing whitespace and `#` commentary, and
+    /// collapse consecutive empty lines.
+    Strip,
+
+    /// Same as `Strip` except `#` commentary is not removed.
+    Whitespace,
+
+    /// Do not change the message at all.
+    Verbatim,
+
+    /// Same as `Whitespace` except everything from the scissors line
+    /// (`# ------------------------ >8 ------------------------`) onwards is removed.
+    Scissors,
+
+    /// `Whitespace`, since messages are never edited in an editor.
+    #[default]
+    Default,
 }
 
 impl GitRepository {
@@ -79,40 +422,170 @@ impl GitRepository {
             git2::opts::set_verify_owner_validation(self.skip_owner_validation)?;
         };
         if let Some(repository) = &self.repository {
-            if !config.flags.allow_empty_message && config.flags.message.is_empty() {
+            let head_commit = match repository.head() {
+                Ok(head) => Some(head.peel_to_commit()?),
+                Err(ref e)
+                    if e.code() == ErrorCode::UnbornBranch || e.code() == ErrorCode::NotFound =>
+                {
+                    None
+                }
+                Err(e) => return Err(e),
+            };
+
+            // amend
+            let amended = if config.flags.amend {
+                match &head_commit {
+                    Some(commit) => Some(commit),
+                    None => return Err(Error::from_str("You have nothing to amend.")),
+                }
+            } else {
+                None
+            };
+
+            let hook_env = [
+                ("GIT_INDEX_FILE", repository.path().join("index")),
+                ("GIT_EDITOR", PathBuf::from(":")),
+            ];
+            let hook_env: Vec<(&str, &Path)> = hook_env
+                .iter()
+                .map(|(key, value)| (*key, value.as_path()))
+                .collect();
+
+            // pre-commit
+            if self.run_hooks && !config.flags.no_verify {
+                hooks::run_hook(repository, "pre-commit", &[], None, &hook_env)?;
+            }
+
+            let mut message = config.get_message(repository, amended)?;
+
+            // prepare-commit-msg and commit-msg
+            if self.run_hooks {
+                let message_file = repository.path().join("COMMIT_EDITMSG");
+                if !message.is_empty() && !message.ends_with('\n') {
+                    message.push('\n');
+                }
+                std::fs::write(&message_file, &message).map_err(|e| {
+                    Error::from_str(&format!("could not write COMMIT_EDITMSG: {}", e))
+                })?;
+                let file = message_file.to_string_lossy().to_string();
+                let source = if config.flags.message.is_empty()
+                    && config.flags.file.is_none()
+                    && config.flags.fixup.is_none()
+                    && config.flags.squash.is_none()
+                    && amended.is_some()
+                {
+                    vec![file.as_str(), "commit", "HEAD"]
+                } else {
+                    vec![file.as_str(), "message"]
+                };
+                hooks::run_hook(repository, "prepare-commit-msg", &source, None, &hook_env)?;
+                if !config.flags.no_verify {
+                    hooks::run_hook(repository, "commit-msg", &[&file], None, &hook_env)?;
+                }
+                message = std::fs::read_to_string(&message_file).map_err(|e| {
+                    Error::from_str(&format!("could not read COMMIT_EDITMSG: {}", e))
+                })?;
+            }
+
+            let message = config.clean_message(message)?;
+            if !config.flags.allow_empty_message && message.trim().is_empty() {
                 return Err(Error::from_str(
                     "Aborting commit due to empty commit message.",
                 ));
             }
-            if !GitRepository::has_indexed_files(repository) {
-                return Ok(());
+
+            // all, staged through a separate handle so a refused commit leaves the index untouched
+            let staging = if config.flags.all {
+                match repository.workdir() {
+                    Some(workdir) => Some(Repository::open(workdir)?),
+                    None => {
+                        return Err(Error::from_str("this operation must be run in a work tree"));
+                    }
+                }
+            } else {
+                None
+            };
+            let mut index = match &staging {
+                Some(staging) => staging.index()?,
+                None => repository.index()?,
+            };
+            // hooks may have changed the index on disk
+            index.read(false)?;
+            if staging.is_some() {
+                index.update_all(["*"].iter(), None)?;
             }
+            let tree = index.write_tree()?;
 
-            // if config.flags.message == "" && !config.flags.allow_empty_message {}
+            // allow-empty
+            if amended.is_none() && !config.flags.allow_empty {
+                let unchanged = match &head_commit {
+                    Some(commit) => commit.tree_id() == tree,
+                    None => index.is_empty(),
+                };
+                if unchanged {
+                    return Err(Error::from_str("nothing to commit, working tree clean"));
+                }
+            }
 
-            let mut index = repository.index()?;
-            let tree = index.write_tree()?;
             let tree = repository.find_tree(tree)?;
-            let signature = config.get_signature()?;
-
-            if let Ok(parent_commit) = repository.head() {
-                repository.commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    &config.flags.message,
-                    &tree,
-                    &[&parent_commit.peel_to_commit().unwrap()],
-                )?;
+            let committer = config.get_signature()?;
+            let author = config.get_author(amended)?;
+
+            let parents: Vec<Commit> = match amended {
+                Some(commit) => commit.parents().collect(),
+                None => head_commit.iter().cloned().collect(),
+            };
+            let parents: Vec<&Commit> = parents.iter().collect();
+
+            // gpg-sign
+            let sign = match config.flags.gpg_sign {
+                Some(sign) => sign,
+                None => repository
+                    .config()?
+                    .get_bool("commit.gpgsign")
+                    .unwrap_or(false),
+            };
+
+            let oid = if sign {
+                let buffer = repository
+                    .commit_create_buffer(&author, &committer, &message, &tree, &parents)?;
+                let signature = match &config.signer {
+                    Some(signer) => signer.sign(&buffer)?,
+                    None => {
+                        let identity = format!("{} <{}>", config.name, config.email);
+                        let signer = signing::signer_from_config(
+                            &repository.config()?,
+                            config.flags.signing_key.clone(),
+                            identity,
+                        )?;
+                        signer.sign(&buffer)?
+                    }
+                };
+                let buffer = buffer
+                    .as_str()
+                    .ok_or_else(|| Error::from_str("commit buffer is not valid utf-8"))?;
+                repository.commit_signed(buffer, &signature, Some("gpgsig"))?
             } else {
-                repository.commit(
-                    Some("HEAD"),
-                    &signature,
-                    &signature,
-                    &config.flags.message,
-                    &tree,
-                    &[],
-                )?;
+                repository.commit(None, &author, &committer, &message, &tree, &parents)?
+            };
+
+            let kind = if amended.is_some() {
+                "commit (amend)"
+            } else if parents.is_empty() {
+                "commit (initial)"
+            } else {
+                "commit"
+            };
+            let summary = message.lines().next().unwrap_or_default();
+            GitRepository::update_head(repository, oid, &format!("{}: {}", kind, summary))?;
+            if staging.is_some() {
+                index.write()?;
+                repository.index()?.read(true)?;
+            }
+
+            // post-commit, its exit status does not affect the commit
+            if self.run_hooks {
+                let _ = hooks::run_hook(repository, "post-commit", &[], None, &hook_env);
             }
 
             return Ok(());
@@ -122,26 +595,27 @@ impl GitRepository {
         ))
     }
 
-    fn has_indexed_files(repo: &Repository) -> bool {
-        let mut opts = StatusOptions::new();
-        opts.include_ignored(false);
-        opts.include_untracked(true).recurse_untracked_dirs(true);
-        opts.exclude_submodules(true);
-
-        let statuses = repo.statuses(Some(&mut opts)).unwrap();
-        let statuses = statuses.iter();
-        for i in statuses {
-            let file_status = i.status();
-            if file_status.is_index_renamed()
-                || file_status.is_index_modified()
-                || file_status.is_index_new()
-                || file_status.is_index_deleted()
-                || file_status.is_index_typechange()
-            {
-                return true;
+    /// Points the branch checked out at `HEAD` to `oid`, creating it if the branch is unborn,
+    /// or moves `HEAD` itself when detached.
+    pub(crate) fn update_head(repository: &Repository, oid: Oid, log: &str) -> Result<(), Error> {
+        let head = repository.find_reference("HEAD")?;
+        match head.symbolic_target() {
+            Some(branch) => {
+                repository.reference(branch, oid, true, log)?;
             }
+            None => repository.set_head_detached(oid)?,
+        }
+        Ok(())
+    }
+
+    /// Builds a `fixup!`/`squash!` message for `target`, with `body` as the message body.
+    fn prefixed_message(prefix: &str, target: &Commit, body: &str) -> String {
+        let summary = String::from_utf8_lossy(target.summary_bytes().unwrap_or_default());
+        if body.is_empty() {
+            format!("{} {}", prefix, summary)
+        } else {
+            format!("{} {}\n\n{}", prefix, summary, body)
         }
-        false
     }
 
     pub fn can_commit(repo_dir: &str) -> bool {
@@ -170,3 +644,335 @@ impl GitRepository {
         false
     }
 }
+
+#[cfg(test)]
+mod commit_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{CommitConfig, CommitFlags};
+    use crate::GitRepository;
+
+    #[test]
+    fn git_commit_amend_author_date_test() {
+        let dir_name = "./temp_test/commit_amend/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with a staged file
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        fs::write(format!("{}file.txt", dir_name), "one\n").unwrap();
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "add", "file.txt"])
+            .output()
+            .unwrap();
+
+        // perform an action same as
+        // "git commit -m first --author 'A U Thor <author@example.com>' --date '@1112911993'"
+        let path = env::current_dir().unwrap().join(dir_name);
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let mut config = CommitConfig::with_message(
+            "Committer".to_string(),
+            "committer@example.com".to_string(),
+            "first".to_string(),
+        );
+        config.add_flag(CommitFlags::Author(Some(
+            "A U Thor <author@example.com>".to_string(),
+        )));
+        config.add_flag(CommitFlags::Date(Some("@1112911993".to_string())));
+        repo.git_commit(config).unwrap();
+
+        // nothing staged, the commit should be refused
+        let config = CommitConfig::with_message(
+            "Committer".to_string(),
+            "committer@example.com".to_string(),
+            "second".to_string(),
+        );
+        let empty = repo.git_commit(config);
+
+        // "git commit -a" with an empty message is refused and nothing gets staged
+        fs::write(format!("{}file.txt", dir_name), "two\n").unwrap();
+        let mut config =
+            CommitConfig::new("Committer".to_string(), "committer@example.com".to_string());
+        config.add_flag(CommitFlags::All(true));
+        let no_message = repo.git_commit(config);
+        let refused_status = Command::new("git")
+            .args(["-C", dir_name, "status", "--porcelain"])
+            .output()
+            .unwrap();
+
+        // perform an action same as "git commit -a --amend"
+        let mut config =
+            CommitConfig::new("Committer".to_string(), "committer@example.com".to_string());
+        config.add_flag(CommitFlags::All(true));
+        config.add_flag(CommitFlags::Amend(true));
+        repo.git_commit(config).unwrap();
+        let log = |dir_name: &str| {
+            Command::new("git")
+                .args([
+                    "-C",
+                    dir_name,
+                    "log",
+                    "--format=%an <%ae> %at|%cn|%s",
+                    "--name-status",
+                ])
+                .output()
+                .expect("git cli needs to be installed for comparing test results")
+        };
+        let amended = log(dir_name);
+
+        // perform an action same as "git commit --amend --author 'Other <other@example.com>'"
+        let mut config =
+            CommitConfig::new("Committer".to_string(), "committer@example.com".to_string());
+        config.add_flag(CommitFlags::Amend(true));
+        config.add_flag(CommitFlags::Author(Some(
+            "Other <other@example.com>".to_string(),
+        )));
+        repo.git_commit(config).unwrap();
+
+        // verify the above actions.
+        let out = log(dir_name);
+        let status = Command::new("git")
+            .args(["-C", dir_name, "status", "--porcelain"])
+            .output()
+            .unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(empty.is_err());
+        assert!(no_message.is_err());
+        assert_eq!(
+            String::from_utf8_lossy(&refused_status.stdout),
+            " M file.txt\n"
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&amended.stdout),
+            "A U Thor <author@example.com> 1112911993|Committer|first\n\nA\tfile.txt\n"
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            "Other <other@example.com> 1112911993|Committer|first\n\nA\tfile.txt\n"
+        );
+        assert_eq!(String::from_utf8_lossy(&status.stdout), "");
+    }
+
+    #[test]
+    fn git_commit_identity_from_config_test() {
+        let dir_name = "./temp_test/commit_identity/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with an identity and an author override in its config
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        for (key, value) in [
+            ("user.name", "User"),
+            ("user.email", "user@example.com"),
+            ("author.name", "Author"),
+        ] {
+            let _ = Command::new("git")
+                .args(["-C", dir_name, "config", key, value])
+                .output()
+                .unwrap();
+        }
+
+        // commit using the identity from the config, then amend a commit by someone else
+        let path = env::current_dir().unwrap().join(dir_name);
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let mut config = CommitConfig::from_git_config(&repo).unwrap();
+        config.add_flag(CommitFlags::Message("initial".to_string()));
+        config.add_flag(CommitFlags::AllowEmpty(true));
+        repo.git_commit(config).unwrap();
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "commit", "--allow-empty", "-m", "second"])
+            .args(["--author", "Other <other@example.com>"])
+            .output()
+            .unwrap();
+        let mut config = CommitConfig::from_git_config(&repo).unwrap();
+        config.add_flag(CommitFlags::Amend(true));
+        repo.git_commit(config).unwrap();
+
+        // verify the above actions, against the identities git itself resolves, since
+        // GIT_AUTHOR_* and GIT_COMMITTER_* from the environment take precedence over the config
+        let out = Command::new("git")
+            .args(["-C", dir_name, "log", "--format=%an <%ae>|%cn <%ce>|%s"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        let ident = |var: &str| {
+            let out = Command::new("git")
+                .args(["-C", dir_name, "var", var])
+                .output()
+                .unwrap();
+            let ident = String::from_utf8_lossy(&out.stdout).trim().to_string();
+            // drop the timestamp and timezone
+            ident.rsplitn(3, ' ').nth(2).unwrap_or_default().to_string()
+        };
+        let author = ident("GIT_AUTHOR_IDENT");
+        let committer = ident("GIT_COMMITTER_IDENT");
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            format!("Other <other@example.com>|{committer}|second\n{author}|{committer}|initial\n")
+        );
+        if env::var_os("GIT_AUTHOR_NAME").is_none() && env::var_os("GIT_AUTHOR_EMAIL").is_none() {
+            assert_eq!(author, "Author <user@example.com>");
+        }
+    }
+
+    #[test]
+    fn git_commit_ssh_sign_test() {
+        let dir_name = "./temp_test/commit_ssh_sign/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository and a throwaway ssh key configured for signing
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        let path = env::current_dir().unwrap().join(dir_name);
+        let key = path.join("signing_key");
+        let _ = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key)
+            .output()
+            .expect("ssh-keygen needs to be installed for signing tests");
+        let public_key = fs::read_to_string(path.join("signing_key.pub")).unwrap();
+        fs::write(
+            path.join("allowed_signers"),
+            format!("committer@example.com {}", public_key),
+        )
+        .unwrap();
+        for (key, value) in [
+            ("gpg.format", "ssh"),
+            ("user.signingkey", key.to_str().unwrap()),
+            ("gpg.ssh.allowedSignersFile", "allowed_signers"),
+        ] {
+            let _ = Command::new("git")
+                .args(["-C", dir_name, "config", key, value])
+                .output()
+                .unwrap();
+        }
+
+        // perform an action same as "git commit -S --allow-empty -m signed"
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let mut config = CommitConfig::with_message(
+            "Committer".to_string(),
+            "committer@example.com".to_string(),
+            "signed".to_string(),
+        );
+        config.add_flag(CommitFlags::AllowEmpty(true));
+        config.add_flag(CommitFlags::GpgSign(None));
+        repo.git_commit(config).unwrap();
+
+        // verify the above actions.
+        let out = Command::new("git")
+            .current_dir(&path)
+            .args(["verify-commit", "HEAD"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+    }
+
+    #[test]
+    fn git_commit_hooks_test() {
+        let dir_name = "./temp_test/commit_hooks/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with a rejecting pre-commit hook and a commit-msg hook that adds
+        // a trailer
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        let path = env::current_dir().unwrap().join(dir_name);
+        let hooks = path.join(".git/hooks");
+        fs::create_dir_all(&hooks).unwrap();
+        fs::write(
+            hooks.join("pre-commit"),
+            "#!/bin/sh\necho rejected >&2\nexit 1\n",
+        )
+        .unwrap();
+        fs::write(
+            hooks.join("commit-msg"),
+            "#!/bin/sh\nprintf '\\nChecked-by: hook\\n' >> \"$1\"\n",
+        )
+        .unwrap();
+        let _ = Command::new("chmod")
+            .arg("+x")
+            .arg(hooks.join("pre-commit"))
+            .arg(hooks.join("commit-msg"))
+            .output()
+            .unwrap();
+
+        let mut repo = GitRepository::open(Path::new(&path)).unwrap();
+        repo.run_hooks(true);
+        let new_config = || {
+            let mut config = CommitConfig::with_message(
+                "Committer".to_string(),
+                "committer@example.com".to_string(),
+                "hooked".to_string(),
+            );
+            config.add_flag(CommitFlags::AllowEmpty(true));
+            config
+        };
+
+        // the pre-commit hook rejects the commit
+        let rejected = repo.git_commit(new_config());
+
+        // perform an action same as "git commit --no-verify", only commit-msg is skipped too
+        let mut config = new_config();
+        config.add_flag(CommitFlags::NoVerify(true));
+        repo.git_commit(config).unwrap();
+
+        // allow the pre-commit hook and commit again
+        fs::write(hooks.join("pre-commit"), "#!/bin/sh\nexit 0\n").unwrap();
+        repo.git_commit(new_config()).unwrap();
+
+        // verify the above actions.
+        let out = Command::new("git")
+            .args(["-C", dir_name, "log", "--format=%B|"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        let rejected = rejected.unwrap_err();
+        assert!(crate::is_hook_error(&rejected));
+        assert!(rejected.message().contains("rejected"));
+        assert_eq!(
+            String::from_utf8_lossy(&out.stdout),
+            "hooked\n\nChecked-by: hook\n|\nhooked\n|\n"
+        );
+    }
+}
@@ -0,0 +1,1376 @@
+use std::path::PathBuf;
+
+use crate::{GitRepository, LineHighlight, highlight_line_pair};
+use regex::bytes;
+
+use git2::{
+    Delta, Diff, DiffFindOptions, DiffFormat, DiffOptions, Error, Oid, Patch, Repository,
+    RevparseMode, Tree,
+};
+
+/// A struct used to specify various details about the `git diff` command.
+pub struct DiffConfig {
+    revisions: Vec<String>,
+    paths: Vec<String>,
+    flags: DiffFlagsInternal,
+}
+
+impl DiffConfig {
+    /// Creates a DiffConfig object comparing the passed revisions:
+    /// - no revision compares the index with the working tree, or `HEAD` with the index when
+    ///   `DiffFlags::Cached` is set
+    /// - a single revision compares it with the working tree, or with the index when
+    ///   `DiffFlags::Cached` is set
+    /// - two revisions, or a range `A..B`, compare the first with the second
+    /// - a range `A...B` compares the merge base of both with `B`
+    pub fn new(revisions: Vec<String>) -> Self {
+        DiffConfig {
+            revisions,
+            paths: vec![],
+            flags: DiffFlagsInternal::default(),
+        }
+    }
+
+    pub fn get_revisions(&self) -> &Vec<String> {
+        &self.revisions
+    }
+
+    /// Limits the diff to the paths, same as `git diff -- <path>...`.
+    pub fn set_paths(&mut self, paths: Vec<String>) -> &Self {
+        self.paths = paths;
+        self
+    }
+
+    pub fn add_flag(&mut self, flag: DiffFlags) -> &Self {
+        match flag {
+            DiffFlags::Cached(cached) => self.flags.cached = cached,
+            DiffFlags::FindRenames(threshold) => {
+                self.flags.renames = Some(true);
+                self.flags.rename_threshold = threshold;
+            }
+            DiffFlags::NoRenames(no_renames) => self.flags.renames = Some(!no_renames),
+            DiffFlags::FindCopies(threshold) => {
+                self.flags.renames = Some(true);
+                self.flags.copies = true;
+                self.flags.copy_threshold = threshold;
+            }
+            DiffFlags::IgnoreAllSpace(ignore) => self.flags.ignore_all_space = ignore,
+            DiffFlags::IgnoreSpaceChange(ignore) => self.flags.ignore_space_change = ignore,
+            DiffFlags::IgnoreSpaceAtEol(ignore) => self.flags.ignore_space_at_eol = ignore,
+            DiffFlags::IgnoreBlankLines(ignore) => self.flags.ignore_blank_lines = ignore,
+            DiffFlags::Unified(lines) => self.flags.unified = Some(lines),
+            DiffFlags::InterHunkContext(lines) => self.flags.inter_hunk_context = Some(lines),
+            DiffFlags::Text(text) => self.flags.text = text,
+            DiffFlags::Reverse(reverse) => self.flags.reverse = reverse,
+            DiffFlags::WordDiffRegex(regex) => self.flags.word_regex = regex,
+        };
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct DiffFlagsInternal {
+    cached: bool,
+    renames: Option<bool>,
+    rename_threshold: Option<u16>,
+    copies: bool,
+    copy_threshold: Option<u16>,
+    ignore_all_space: bool,
+    ignore_space_change: bool,
+    ignore_space_at_eol: bool,
+    ignore_blank_lines: bool,
+    unified: Option<u32>,
+    inter_hunk_context: Option<u32>,
+    text: bool,
+    reverse: bool,
+    word_regex: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum DiffFlags {
+    /// `--cached` or `--staged` flag for `git diff`.
+    /// Compares the index instead of the working tree.
+    /// Defaults to false.
+    Cached(bool),
+
+    /// `-M[<n>]` or `--find-renames[=<n>]` flag for `git diff`.
+    /// Detects renames, `Some(n)` sets the similarity threshold in percent (defaults to 50).
+    /// Renames are detected by default unless `diff.renames` is false.
+    FindRenames(Option<u16>),
+
+    /// `--no-renames` flag for `git diff`.
+    /// Turns off rename detection.
+    /// Defaults to false.
+    NoRenames(bool),
+
+    /// `-C[<n>]` or `--find-copies[=<n>]` flag for `git diff`.
+    /// Detects copies from files modified in the same diff as well as renames, `Some(n)` sets
+    /// the similarity threshold in percent (defaults to 50).
+    FindCopies(Option<u16>),
+
+    /// `-w` or `--ignore-all-space` flag for `git diff`.
+    /// Defaults to false.
+    IgnoreAllSpace(bool),
+
+    /// `-b` or `--ignore-space-change` flag for `git diff`.
+    /// Defaults to false.
+    IgnoreSpaceChange(bool),
+
+    /// `--ignore-space-at-eol` flag for `git diff`.
+    /// Defaults to false.
+    IgnoreSpaceAtEol(bool),
+
+    /// `--ignore-blank-lines` flag for `git diff`.
+    /// Defaults to false.
+    IgnoreBlankLines(bool),
+
+    /// `-U<n>` or `--unified=<n>` flag for `git diff`.
+    /// Number of context lines, defaults to `diff.context` or 3.
+    Unified(u32),
+
+    /// `--inter-hunk-context=<n>` flag for `git diff`.
+    /// Shows the context between hunks up to the number of lines, merging the hunks.
+    /// Defaults to `diff.interHunkContext` or 0.
+    InterHunkContext(u32),
+
+    /// `-a` or `--text` flag for `git diff`.
+    /// Treats all files as text.
+    /// Defaults to false.
+    Text(bool),
+
+    /// `-R` flag for `git diff`.
+    /// Swaps both sides of the diff.
+    /// Defaults to false.
+    Reverse(bool),
+
+    /// `--word-diff-regex=<regex>` flag for `git diff`.
+    /// Regex matching a word for `DiffOutput::word_diff`, words are runs of non-whitespace
+    /// characters by default or `diff.wordRegex` when set.
+    WordDiffRegex(Option<String>),
+}
+
+/// Modes of `git diff --word-diff`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WordDiffMode {
+    /// Marks removed words as `[-word-]` and added words as `{+word+}`.
+    Plain,
+    /// Line based format for scripts: removed, added and context runs on their own lines
+    /// starting with `-`, `+` and ` `, a `~` line marks the end of a line of the new file.
+    Porcelain,
+    /// Colors removed words red and added words green, same as `--color-words`.
+    Color,
+}
+
+/// Result of `git_diff`, rendered in the formats of `git diff` or as structured records.
+pub struct DiffOutput<'repo> {
+    repository: &'repo Repository,
+    diff: Diff<'repo>,
+    word_regex: Option<bytes::Regex>,
+}
+
+/// A changed file as returned by `DiffOutput::files`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffFileRecord {
+    pub status: Delta,
+    pub old_path: Option<PathBuf>,
+    pub new_path: Option<PathBuf>,
+    pub old_id: Oid,
+    pub new_id: Oid,
+    /// Similarity index in percent of renamed and copied files.
+    pub similarity: Option<u16>,
+    pub binary: bool,
+    pub insertions: usize,
+    pub deletions: usize,
+    pub hunks: Vec<DiffHunkRecord>,
+}
+
+/// A hunk of a `DiffFileRecord`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffHunkRecord {
+    /// Header line, e.g. `@@ -1,3 +1,4 @@ fn main()`.
+    pub header: String,
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLineRecord>,
+}
+
+/// A line of a `DiffHunkRecord`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLineRecord {
+    /// `+` for added, `-` for removed and ` ` for context lines, `=`, `>` or `<` for changes
+    /// of the newline at the end of the file.
+    pub origin: char,
+    pub old_lineno: Option<u32>,
+    pub new_lineno: Option<u32>,
+    /// Content of the line, including the newline.
+    pub content: String,
+}
+
+/// A removed line paired with the added line replacing it, as returned by
+/// `DiffHunkRecord::highlights`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DiffLinePair {
+    /// Index of the removed line in `DiffHunkRecord::lines`.
+    pub old_index: usize,
+    /// Index of the added line in `DiffHunkRecord::lines`.
+    pub new_index: usize,
+    pub highlight: LineHighlight,
+}
+
+impl DiffHunkRecord {
+    /// Pairs each run of removed lines with the run of added lines following it, line by line,
+    /// and highlights the characters changed within each pair.
+    pub fn highlights(&self) -> Vec<DiffLinePair> {
+        let mut pairs = vec![];
+        let mut index = 0;
+        while index < self.lines.len() {
+            let removed_start = index;
+            while index < self.lines.len() && self.lines[index].origin == '-' {
+                index += 1;
+            }
+            let added_start = index;
+            while index < self.lines.len() && self.lines[index].origin == '+' {
+                index += 1;
+            }
+            let count = (added_start - removed_start).min(index - added_start);
+            for offset in 0..count {
+                let old_index = removed_start + offset;
+                let new_index = added_start + offset;
+                pairs.push(DiffLinePair {
+                    old_index,
+                    new_index,
+                    highlight: highlight_line_pair(
+                        &self.lines[old_index].content,
+                        &self.lines[new_index].content,
+                    ),
+                });
+            }
+            if index == removed_start {
+                index += 1;
+            }
+        }
+        pairs
+    }
+}
+
+impl GitRepository {
+    /// Compares trees, the index and the working tree, same as `git diff`.
+    pub fn git_diff(&self, config: DiffConfig) -> Result<DiffOutput<'_>, Error> {
+        if let Some(repository) = &self.repository {
+            let flags = &config.flags;
+            return diff_output(repository, flags, &config.paths, |options| {
+                let diff = match config.revisions.as_slice() {
+                    [] if flags.cached => {
+                        let head = match repository.head() {
+                            Ok(head) => Some(head.peel_to_tree()?),
+                            Err(_) => None,
+                        };
+                        repository.diff_tree_to_index(head.as_ref(), None, Some(options))?
+                    }
+                    [] => repository.diff_index_to_workdir(None, Some(options))?,
+                    [revision] => {
+                        let revspec = repository.revparse(revision)?;
+                        if revspec.mode().contains(RevparseMode::RANGE) {
+                            let (old, new) = range_trees(repository, revision)?;
+                            repository.diff_tree_to_tree(Some(&old), Some(&new), Some(options))?
+                        } else {
+                            let tree = repository.revparse_single(revision)?.peel_to_tree()?;
+                            if flags.cached {
+                                repository.diff_tree_to_index(Some(&tree), None, Some(options))?
+                            } else {
+                                repository
+                                    .diff_tree_to_workdir_with_index(Some(&tree), Some(options))?
+                            }
+                        }
+                    }
+                    [old, new] => {
+                        let old = repository.revparse_single(old)?.peel_to_tree()?;
+                        let new = repository.revparse_single(new)?.peel_to_tree()?;
+                        repository.diff_tree_to_tree(Some(&old), Some(&new), Some(options))?
+                    }
+                    _ => {
+                        return Err(Error::from_str(
+                            "diff of more than two revisions is not supported",
+                        ));
+                    }
+                };
+                Ok(diff)
+            });
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+}
+
+/// Creates the diff with `build` using the options of `flags`, then detects renames and
+/// copies.
+pub(crate) fn diff_output<'repo, F>(
+    repository: &'repo Repository,
+    flags: &DiffFlagsInternal,
+    paths: &[String],
+    build: F,
+) -> Result<DiffOutput<'repo>, Error>
+where
+    F: FnOnce(&mut DiffOptions) -> Result<Diff<'repo>, Error>,
+{
+    let mut options = DiffOptions::new();
+    for path in paths {
+        options.pathspec(path);
+    }
+    let git_config = repository.config()?;
+    let context = match flags.unified {
+        Some(lines) => lines,
+        None => git_config.get_i32("diff.context").unwrap_or(3).max(0) as u32,
+    };
+    let inter_hunk = match flags.inter_hunk_context {
+        Some(lines) => lines,
+        None => git_config
+            .get_i32("diff.interHunkContext")
+            .unwrap_or(0)
+            .max(0) as u32,
+    };
+    options
+        .context_lines(context)
+        .interhunk_lines(inter_hunk)
+        .ignore_whitespace(flags.ignore_all_space)
+        .ignore_whitespace_change(flags.ignore_space_change)
+        .ignore_whitespace_eol(flags.ignore_space_at_eol)
+        .ignore_blank_lines(flags.ignore_blank_lines)
+        .force_text(flags.text)
+        .reverse(flags.reverse);
+    let mut diff = build(&mut options)?;
+
+    // rename and copy detection, `diff.renames` decides when no flag is given
+    let renames = match flags.renames {
+        Some(renames) => renames,
+        None => match git_config.get_string("diff.renames") {
+            Ok(value) => !matches!(value.as_str(), "false" | "no" | "off" | "0"),
+            Err(_) => true,
+        },
+    };
+    let copies = flags.copies
+        || (flags.renames.is_none()
+            && matches!(
+                git_config.get_string("diff.renames").as_deref(),
+                Ok("copies") | Ok("copy")
+            ));
+    if renames {
+        let mut find = DiffFindOptions::new();
+        find.renames(true).copies(copies);
+        if let Some(threshold) = flags.rename_threshold {
+            find.rename_threshold(threshold);
+        }
+        if let Some(threshold) = flags.copy_threshold {
+            find.copy_threshold(threshold);
+        }
+        diff.find_similar(Some(&mut find))?;
+    }
+
+    let word_regex = match &flags.word_regex {
+        Some(regex) => Some(regex.clone()),
+        None => git_config.get_string("diff.wordRegex").ok(),
+    };
+    let word_regex = match word_regex {
+        Some(regex) => Some(
+            bytes::Regex::new(&regex)
+                .map_err(|e| Error::from_str(&format!("invalid word regex: {}", e)))?,
+        ),
+        None => None,
+    };
+
+    Ok(DiffOutput {
+        repository,
+        diff,
+        word_regex,
+    })
+}
+
+/// Resolves `A..B` to the trees of `A` and `B`, and `A...B` to the trees of their merge base
+/// and `B`.
+fn range_trees<'repo>(
+    repository: &'repo Repository,
+    range: &str,
+) -> Result<(Tree<'repo>, Tree<'repo>), Error> {
+    let revspec = repository.revparse(range)?;
+    let head = || -> Result<Oid, Error> { Ok(repository.head()?.peel_to_commit()?.id()) };
+    let from = match revspec.from() {
+        Some(from) => from.peel_to_commit()?.id(),
+        None => head()?,
+    };
+    let to = match revspec.to() {
+        Some(to) => to.peel_to_commit()?.id(),
+        None => head()?,
+    };
+    let from = if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+        repository.merge_base(from, to)?
+    } else {
+        from
+    };
+    Ok((
+        repository.find_commit(from)?.tree()?,
+        repository.find_commit(to)?.tree()?,
+    ))
+}
+
+impl<'repo> DiffOutput<'repo> {
+    /// Returns the underlying `git2::Diff`.
+    pub fn get_diff(&self) -> &Diff<'repo> {
+        &self.diff
+    }
+
+    /// Renders the diff as a patch, same as `git diff`.
+    pub fn patch(&self) -> Result<String, Error> {
+        let mut out = Vec::new();
+        self.diff.print(DiffFormat::Patch, |_, _, line| {
+            if matches!(line.origin(), '+' | '-' | ' ') {
+                out.push(line.origin() as u8);
+            }
+            out.extend_from_slice(line.content());
+            true
+        })?;
+        Ok(String::from_utf8_lossy(&out).to_string())
+    }
+
+    /// Renders the diff with changes highlighted per word, same as `git diff --word-diff=<mode>`.
+    pub fn word_diff(&self, mode: WordDiffMode) -> Result<String, Error> {
+        let mut words = WordDiff {
+            style: match mode {
+                WordDiffMode::Plain => &PLAIN_STYLE,
+                WordDiffMode::Porcelain => &PORCELAIN_STYLE,
+                WordDiffMode::Color => &COLOR_STYLE,
+            },
+            regex: self.word_regex.as_ref(),
+            minus: vec![],
+            plus: vec![],
+            out: vec![],
+        };
+        let color = mode == WordDiffMode::Color;
+        let mut result = Ok(());
+        self.diff.print(DiffFormat::Patch, |_, _, line| {
+            let content = line.content();
+            match line.origin() {
+                '-' | '+' => {
+                    let buffer = if line.origin() == '-' {
+                        &mut words.minus
+                    } else {
+                        &mut words.plus
+                    };
+                    buffer.extend_from_slice(content);
+                    if !content.ends_with(b"\n") {
+                        buffer.push(b'\n');
+                    }
+                }
+                // "\ No newline at end of file" is dropped
+                '=' | '>' | '<' => {}
+                origin => {
+                    if let Err(e) = words.flush() {
+                        result = Err(e);
+                        return false;
+                    }
+                    let content = content.strip_suffix(b"\n").unwrap_or(content);
+                    let out = &mut words.out;
+                    match origin {
+                        ' ' if mode == WordDiffMode::Porcelain => {
+                            out.push(b' ');
+                            out.extend_from_slice(content);
+                            out.extend_from_slice(b"\n~\n");
+                        }
+                        ' ' => {
+                            out.extend_from_slice(content);
+                            if color && !content.is_empty() {
+                                out.extend_from_slice(RESET.as_bytes());
+                            }
+                            out.push(b'\n');
+                        }
+                        'H' if color => {
+                            let end = content
+                                .windows(2)
+                                .skip(2)
+                                .position(|w| w == b"@@")
+                                .map_or(content.len(), |i| i + 4);
+                            out.extend_from_slice(FRAG.as_bytes());
+                            out.extend_from_slice(&content[..end]);
+                            out.extend_from_slice(RESET.as_bytes());
+                            if let Some(func) = content[end..].strip_prefix(b" ") {
+                                out.push(b' ');
+                                out.extend_from_slice(RESET.as_bytes());
+                                out.extend_from_slice(func);
+                                out.extend_from_slice(RESET.as_bytes());
+                            }
+                            out.push(b'\n');
+                        }
+                        'F' if color => {
+                            for header in content.split(|b| *b == b'\n') {
+                                out.extend_from_slice(META.as_bytes());
+                                out.extend_from_slice(header);
+                                out.extend_from_slice(RESET.as_bytes());
+                                out.push(b'\n');
+                            }
+                        }
+                        _ => {
+                            out.extend_from_slice(content);
+                            out.push(b'\n');
+                        }
+                    }
+                }
+            }
+            true
+        })?;
+        result?;
+        words.flush()?;
+        Ok(String::from_utf8_lossy(&words.out).to_string())
+    }
+
+    /// Renders the changed paths, same as `git diff --name-only`.
+    pub fn name_only(&self) -> Result<String, Error> {
+        let mut out = String::new();
+        for file in self.files()? {
+            let path = file.new_path.or(file.old_path).unwrap_or_default();
+            out.push_str(&format!("{}\n", path.display()));
+        }
+        Ok(out)
+    }
+
+    /// Renders the changed paths with their status, same as `git diff --name-status`.
+    pub fn name_status(&self) -> Result<String, Error> {
+        let mut out = String::new();
+        for file in self.files()? {
+            let status = status_char(file.status);
+            let old = file.old_path.unwrap_or_default();
+            let new = file.new_path.unwrap_or_default();
+            match file.similarity {
+                Some(score) => out.push_str(&format!(
+                    "{}{:03}\t{}\t{}\n",
+                    status,
+                    score,
+                    old.display(),
+                    new.display()
+                )),
+                None if file.status == Delta::Deleted => {
+                    out.push_str(&format!("{}\t{}\n", status, old.display()))
+                }
+                None => out.push_str(&format!("{}\t{}\n", status, new.display())),
+            }
+        }
+        Ok(out)
+    }
+
+    /// Renders the number of added and deleted lines per file, same as `git diff --numstat`.
+    pub fn numstat(&self) -> Result<String, Error> {
+        let mut out = String::new();
+        for file in self.files()? {
+            if file.binary {
+                out.push_str("-\t-\t");
+            } else {
+                out.push_str(&format!("{}\t{}\t", file.insertions, file.deletions));
+            }
+            out.push_str(&format!("{}\n", print_name(&file)));
+        }
+        Ok(out)
+    }
+
+    /// Renders the summary line of `--stat`, same as `git diff --shortstat`.
+    pub fn shortstat(&self) -> Result<String, Error> {
+        let files = self.files()?;
+        if files.is_empty() {
+            return Ok(String::new());
+        }
+        Ok(summary(&files))
+    }
+
+    /// Renders a diffstat `width` columns wide, same as `git diff --stat=<width>`.
+    /// `git diff --stat` uses 80 columns when not writing to a terminal.
+    pub fn stat(&self, width: usize) -> Result<String, Error> {
+        let files = self.files()?;
+        if files.is_empty() {
+            return Ok(String::new());
+        }
+
+        // binary files are shown as "Bin <old> -> <new> bytes"
+        let sizes: Vec<(u64, u64)> = files
+            .iter()
+            .map(|file| self.sizes(file))
+            .collect::<Result<_, _>>()?;
+
+        let mut max_len = 0;
+        let mut max_change = 0;
+        let mut bin_width = 0;
+        let mut number_width = 0;
+        for (file, (old_size, new_size)) in files.iter().zip(&sizes) {
+            max_len = max_len.max(print_name(file).chars().count());
+            if file.binary {
+                let w = 14 + decimal_width(*new_size as usize) + decimal_width(*old_size as usize);
+                bin_width = bin_width.max(w);
+                number_width = 3;
+                continue;
+            }
+            max_change = max_change.max(file.insertions + file.deletions);
+        }
+
+        let number_width = number_width.max(decimal_width(max_change));
+        let width = width.max(16 + 6 + number_width);
+        let mut graph_width = if max_change + 4 > bin_width {
+            max_change
+        } else {
+            bin_width - 4
+        };
+        let mut name_width = max_len;
+        if name_width + number_width + 6 + graph_width > width {
+            let wanted = (width * 3 / 8).saturating_sub(number_width + 6);
+            if graph_width > wanted {
+                graph_width = wanted.max(6);
+            }
+            if name_width > width.saturating_sub(number_width + 6 + graph_width) {
+                name_width = width.saturating_sub(number_width + 6 + graph_width);
+            } else {
+                graph_width = width - number_width - 6 - name_width;
+            }
+        }
+
+        let mut out = String::new();
+        for (file, (old_size, new_size)) in files.iter().zip(&sizes) {
+            // shorten long names from the left, at a directory boundary if possible
+            let name = print_name(file);
+            let mut prefix = "";
+            let mut name: &str = &name;
+            if name_width < name.chars().count() {
+                prefix = "...";
+                let len = name_width.saturating_sub(3);
+                let skip = name.chars().count() - len;
+                let start = name.char_indices().nth(skip).map_or(name.len(), |(i, _)| i);
+                name = &name[start..];
+                if let Some(slash) = name.find('/') {
+                    name = &name[slash..];
+                }
+            }
+            let len = if prefix.is_empty() {
+                name_width
+            } else {
+                name_width.saturating_sub(3)
+            };
+            let padding = len.saturating_sub(name.chars().count());
+
+            if file.binary {
+                out.push_str(&format!(
+                    " {}{}{} | {:>w$}",
+                    prefix,
+                    name,
+                    " ".repeat(padding),
+                    "Bin",
+                    w = number_width
+                ));
+                if *old_size == 0 && *new_size == 0 {
+                    out.push('\n');
+                } else {
+                    out.push_str(&format!(" {} -> {} bytes\n", old_size, new_size));
+                }
+                continue;
+            }
+
+            let (mut add, mut del) = (file.insertions, file.deletions);
+            if graph_width <= max_change {
+                let mut total = scale_linear(add + del, graph_width, max_change);
+                if total < 2 && add > 0 && del > 0 {
+                    total = 2;
+                }
+                if add < del {
+                    add = scale_linear(add, graph_width, max_change);
+                    del = total - add;
+                } else {
+                    del = scale_linear(del, graph_width, max_change);
+                    add = total - del;
+                }
+            }
+            let changes = file.insertions + file.deletions;
+            out.push_str(&format!(
+                " {}{}{} | {:>w$}{}{}{}\n",
+                prefix,
+                name,
+                " ".repeat(padding),
+                changes,
+                if changes > 0 { " " } else { "" },
+                "+".repeat(add),
+                "-".repeat(del),
+                w = number_width
+            ));
+        }
+        out.push_str(&summary(&files));
+        Ok(out)
+    }
+
+    /// Returns the changed files with their hunks and lines.
+    pub fn files(&self) -> Result<Vec<DiffFileRecord>, Error> {
+        let similarities = self.similarities()?;
+        let mut files = vec![];
+        for (index, delta) in self.diff.deltas().enumerate() {
+            let mut record = DiffFileRecord {
+                status: delta.status(),
+                old_path: delta.old_file().path().map(|p| p.to_path_buf()),
+                new_path: delta.new_file().path().map(|p| p.to_path_buf()),
+                old_id: delta.old_file().id(),
+                new_id: delta.new_file().id(),
+                similarity: similarities.get(index).copied().flatten(),
+                binary: delta.flags().is_binary(),
+                insertions: 0,
+                deletions: 0,
+                hunks: vec![],
+            };
+            match record.status {
+                Delta::Added | Delta::Untracked => record.old_path = None,
+                Delta::Deleted => record.new_path = None,
+                _ => {}
+            }
+
+            if let Some(patch) = Patch::from_diff(&self.diff, index)? {
+                record.binary = patch.delta().flags().is_binary();
+                let (_, insertions, deletions) = patch.line_stats()?;
+                record.insertions = insertions;
+                record.deletions = deletions;
+                for hunk_index in 0..patch.num_hunks() {
+                    let (hunk, count) = patch.hunk(hunk_index)?;
+                    let mut lines = vec![];
+                    for line_index in 0..count {
+                        let line = patch.line_in_hunk(hunk_index, line_index)?;
+                        lines.push(DiffLineRecord {
+                            origin: line.origin(),
+                            old_lineno: line.old_lineno(),
+                            new_lineno: line.new_lineno(),
+                            content: String::from_utf8_lossy(line.content()).to_string(),
+                        });
+                    }
+                    record.hunks.push(DiffHunkRecord {
+                        header: String::from_utf8_lossy(hunk.header())
+                            .trim_end()
+                            .to_string(),
+                        old_start: hunk.old_start(),
+                        old_lines: hunk.old_lines(),
+                        new_start: hunk.new_start(),
+                        new_lines: hunk.new_lines(),
+                        lines,
+                    });
+                }
+            }
+            if record.binary {
+                record.insertions = 0;
+                record.deletions = 0;
+            }
+            files.push(record);
+        }
+        Ok(files)
+    }
+
+    /// Similarity scores of renamed and copied files, these are only exposed through the raw
+    /// format (`R086`).
+    fn similarities(&self) -> Result<Vec<Option<u16>>, Error> {
+        let mut scores = vec![];
+        self.diff.print(DiffFormat::Raw, |delta, _, line| {
+            let score = match delta.status() {
+                Delta::Renamed | Delta::Copied => {
+                    let content = String::from_utf8_lossy(line.content()).to_string();
+                    content
+                        .split('\t')
+                        .next()
+                        .and_then(|fields| fields.split(' ').next_back())
+                        .and_then(|status| status.get(1..))
+                        .and_then(|score| score.parse::<u16>().ok())
+                }
+                _ => None,
+            };
+            scores.push(score);
+            true
+        })?;
+        Ok(scores)
+    }
+
+    /// Sizes in bytes of both sides of a binary file.
+    fn sizes(&self, file: &DiffFileRecord) -> Result<(u64, u64), Error> {
+        if !file.binary {
+            return Ok((0, 0));
+        }
+        let size = |id: Oid, path: &Option<PathBuf>| -> u64 {
+            if let Ok(blob) = self.repository.find_blob(id) {
+                return blob.size() as u64;
+            }
+            match (self.repository.workdir(), path) {
+                (Some(workdir), Some(path)) => match workdir.join(path).metadata() {
+                    Ok(metadata) => metadata.len(),
+                    Err(_) => 0,
+                },
+                _ => 0,
+            }
+        };
+        let old = if file.old_path.is_some() {
+            size(file.old_id, &None)
+        } else {
+            0
+        };
+        let new = if file.new_path.is_some() {
+            size(file.new_id, &file.new_path)
+        } else {
+            0
+        };
+        Ok((old, new))
+    }
+}
+
+/// How `--word-diff` marks a run of text: color, prefix and suffix.
+type WordStyleElement = (&'static str, &'static str, &'static str);
+
+struct WordStyle {
+    new: WordStyleElement,
+    old: WordStyleElement,
+    context: WordStyleElement,
+    newline: &'static str,
+}
+
+const RESET: &str = "\x1b[m";
+const META: &str = "\x1b[1m";
+const FRAG: &str = "\x1b[36m";
+
+const PLAIN_STYLE: WordStyle = WordStyle {
+    new: ("", "{+", "+}"),
+    old: ("", "[-", "-]"),
+    context: ("", "", ""),
+    newline: "\n",
+};
+
+const PORCELAIN_STYLE: WordStyle = WordStyle {
+    new: ("", "+", "\n"),
+    old: ("", "-", "\n"),
+    context: ("", " ", "\n"),
+    newline: "~\n",
+};
+
+const COLOR_STYLE: WordStyle = WordStyle {
+    new: ("\x1b[32m", "", ""),
+    old: ("\x1b[31m", "", ""),
+    context: ("", "", ""),
+    newline: "\n",
+};
+
+/// Collects the removed and added lines between context lines and renders them word by word,
+/// a port of git's `diff_words_show`.
+struct WordDiff<'a> {
+    style: &'a WordStyle,
+    regex: Option<&'a bytes::Regex>,
+    minus: Vec<u8>,
+    plus: Vec<u8>,
+    out: Vec<u8>,
+}
+
+impl WordDiff<'_> {
+    fn flush(&mut self) -> Result<(), Error> {
+        if self.minus.is_empty() && self.plus.is_empty() {
+            return Ok(());
+        }
+        let minus = std::mem::take(&mut self.minus);
+        let plus = std::mem::take(&mut self.plus);
+        if plus.is_empty() {
+            self.write(self.style.old, &minus);
+            return Ok(());
+        }
+
+        // words are compared as lines, the first entry is a sentinel at the start of the text
+        let minus_words = self.words(&minus);
+        let plus_words = self.words(&plus);
+        let join = |text: &[u8], words: &[(usize, usize)]| -> Vec<u8> {
+            let mut joined = vec![];
+            for (begin, end) in &words[1..] {
+                joined.extend_from_slice(&text[*begin..*end]);
+                joined.push(b'\n');
+            }
+            joined
+        };
+        let (old, new) = (join(&minus, &minus_words), join(&plus, &plus_words));
+        let mut options = DiffOptions::new();
+        options.context_lines(0).force_text(true);
+        let patch = Patch::from_buffers(&old, None, &new, None, Some(&mut options))?;
+
+        let mut current_plus = 0;
+        for index in 0..patch.num_hunks() {
+            let (hunk, _) = patch.hunk(index)?;
+            let span = |words: &[(usize, usize)], first: u32, len: u32| {
+                let (first, len) = (first as usize, len as usize);
+                if len > 0 {
+                    (words[first].0, words[first + len - 1].1)
+                } else {
+                    (words[first].1, words[first].1)
+                }
+            };
+            let (minus_begin, minus_end) = span(&minus_words, hunk.old_start(), hunk.old_lines());
+            let (plus_begin, plus_end) = span(&plus_words, hunk.new_start(), hunk.new_lines());
+            if current_plus != plus_begin {
+                self.write(self.style.context, &plus[current_plus..plus_begin]);
+            }
+            if minus_begin != minus_end {
+                self.write(self.style.old, &minus[minus_begin..minus_end]);
+            }
+            if plus_begin != plus_end {
+                self.write(self.style.new, &plus[plus_begin..plus_end]);
+            }
+            current_plus = plus_end;
+        }
+        if current_plus != plus.len() {
+            self.write(self.style.context, &plus[current_plus..]);
+        }
+        Ok(())
+    }
+
+    /// Splits `text` into words, runs of non-whitespace characters or matches of the word
+    /// regex, which never span lines.
+    fn words(&self, text: &[u8]) -> Vec<(usize, usize)> {
+        let mut words = vec![(0, 0)];
+        let mut begin = 0;
+        loop {
+            let end;
+            if let Some(regex) = self.regex {
+                let Some(found) = regex.find(&text[begin..]) else {
+                    break;
+                };
+                end = match text[begin + found.start()..begin + found.end()]
+                    .iter()
+                    .position(|b| *b == b'\n')
+                {
+                    Some(newline) => begin + found.start() + newline,
+                    None => begin + found.end(),
+                };
+                begin += found.start();
+                if begin >= end {
+                    break;
+                }
+            } else {
+                while begin < text.len() && text[begin].is_ascii_whitespace() {
+                    begin += 1;
+                }
+                if begin >= text.len() {
+                    break;
+                }
+                let mut next = begin + 1;
+                while next < text.len() && !text[next].is_ascii_whitespace() {
+                    next += 1;
+                }
+                end = next;
+            }
+            words.push((begin, end));
+            begin = end;
+        }
+        words
+    }
+
+    /// Writes `text` marked with `element`, each line separately.
+    fn write(&mut self, element: WordStyleElement, text: &[u8]) {
+        let (color, prefix, suffix) = element;
+        let mut rest = text;
+        while !rest.is_empty() {
+            let newline = rest.iter().position(|b| *b == b'\n');
+            let end = newline.unwrap_or(rest.len());
+            if end != 0 {
+                self.out.extend_from_slice(color.as_bytes());
+                self.out.extend_from_slice(prefix.as_bytes());
+                self.out.extend_from_slice(&rest[..end]);
+                self.out.extend_from_slice(suffix.as_bytes());
+                if !color.is_empty() {
+                    self.out.extend_from_slice(RESET.as_bytes());
+                }
+            }
+            match newline {
+                Some(newline) => {
+                    self.out.extend_from_slice(self.style.newline.as_bytes());
+                    rest = &rest[newline + 1..];
+                }
+                None => break,
+            }
+        }
+    }
+}
+
+/// Name of a file in `--stat` and `--numstat`, renames are shown as `dir/{old => new}.txt`.
+fn print_name(file: &DiffFileRecord) -> String {
+    let old = file
+        .old_path
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    let new = file
+        .new_path
+        .as_ref()
+        .map(|p| p.display().to_string())
+        .unwrap_or_default();
+    if file.similarity.is_none() || old == new {
+        return if new.is_empty() { old } else { new };
+    }
+
+    let (a, b) = (old.as_bytes(), new.as_bytes());
+    // common prefix up to a slash
+    let mut pfx_length = 0;
+    let mut i = 0;
+    while i < a.len() && i < b.len() && a[i] == b[i] {
+        if a[i] == b'/' {
+            pfx_length = i + 1;
+        }
+        i += 1;
+    }
+
+    // common suffix from a slash, not overlapping the prefix
+    let mut sfx_length = 0;
+    let adjust = if pfx_length > 0 { 1 } else { 0 };
+    let (mut x, mut y) = (a.len() as isize, b.len() as isize);
+    let floor = pfx_length as isize - adjust;
+    while x >= floor && y >= floor {
+        let ca = if (x as usize) < a.len() {
+            a[x as usize]
+        } else {
+            0
+        };
+        let cb = if (y as usize) < b.len() {
+            b[y as usize]
+        } else {
+            0
+        };
+        if ca != cb {
+            break;
+        }
+        if ca == b'/' {
+            sfx_length = a.len() - x as usize;
+        }
+        x -= 1;
+        y -= 1;
+    }
+
+    let a_mid = a.len().saturating_sub(pfx_length + sfx_length);
+    let b_mid = b.len().saturating_sub(pfx_length + sfx_length);
+    let mut name = String::new();
+    if pfx_length + sfx_length > 0 {
+        name.push_str(&old[..pfx_length]);
+        name.push('{');
+    }
+    name.push_str(&old[pfx_length..pfx_length + a_mid]);
+    name.push_str(" => ");
+    name.push_str(&new[pfx_length..pfx_length + b_mid]);
+    if pfx_length + sfx_length > 0 {
+        name.push('}');
+        name.push_str(&old[old.len() - sfx_length..]);
+    }
+    name
+}
+
+fn summary(files: &[DiffFileRecord]) -> String {
+    let insertions: usize = files.iter().map(|file| file.insertions).sum();
+    let deletions: usize = files.iter().map(|file| file.deletions).sum();
+    let mut out = if files.len() == 1 {
+        String::from(" 1 file changed")
+    } else {
+        format!(" {} files changed", files.len())
+    };
+    if insertions > 0 || deletions == 0 {
+        let unit = if insertions == 1 {
+            "insertion"
+        } else {
+            "insertions"
+        };
+        out.push_str(&format!(", {} {}(+)", insertions, unit));
+    }
+    if deletions > 0 || insertions == 0 {
+        let unit = if deletions == 1 {
+            "deletion"
+        } else {
+            "deletions"
+        };
+        out.push_str(&format!(", {} {}(-)", deletions, unit));
+    }
+    out.push('\n');
+    out
+}
+
+fn status_char(status: Delta) -> char {
+    match status {
+        Delta::Added => 'A',
+        Delta::Deleted => 'D',
+        Delta::Modified => 'M',
+        Delta::Renamed => 'R',
+        Delta::Copied => 'C',
+        Delta::Typechange => 'T',
+        Delta::Untracked => '?',
+        Delta::Ignored => '!',
+        Delta::Conflicted => 'U',
+        _ => 'X',
+    }
+}
+
+fn scale_linear(it: usize, width: usize, max_change: usize) -> usize {
+    if it == 0 {
+        return 0;
+    }
+    1 + it * (width.saturating_sub(1)) / max_change
+}
+
+fn decimal_width(number: usize) -> usize {
+    number.to_string().len()
+}
+
+#[cfg(test)]
+mod diff_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{DiffConfig, DiffFlags, WordDiffMode};
+    use crate::{GitRepository, helpers::test_util::git_output};
+    use git2::Delta;
+
+    type Render = fn(&super::DiffOutput) -> String;
+
+    #[test]
+    fn git_diff_matches_cli_test() {
+        let dir_name = "./temp_test/diff/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        let path = env::current_dir().unwrap().join(dir_name);
+        let lines = |count: usize, prefix: &str| -> String {
+            (1..=count)
+                .map(|i| format!("{} line {}\n", prefix, i))
+                .collect()
+        };
+        git_output(&path, &["init", "-b", "main"]);
+        git_output(&path, &["config", "user.name", "Committer"]);
+        git_output(&path, &["config", "user.email", "committer@example.com"]);
+        fs::create_dir_all(path.join("some/deeply/nested/directory")).unwrap();
+        fs::write(path.join("a.txt"), lines(20, "a")).unwrap();
+        fs::write(path.join("b.txt"), lines(5, "b")).unwrap();
+        fs::write(
+            path.join("some/deeply/nested/directory/long.txt"),
+            lines(30, "l"),
+        )
+        .unwrap();
+        fs::write(path.join("bin.dat"), [0u8, 1, 2, 3, 0, 5]).unwrap();
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "first"]);
+
+        let mut a = lines(20, "a").replace("a line 3\n", "a line three\n");
+        a.push_str("appended\n");
+        fs::write(path.join("a.txt"), &a).unwrap();
+        fs::remove_file(path.join("b.txt")).unwrap();
+        fs::rename(
+            path.join("some/deeply/nested/directory/long.txt"),
+            path.join("some/deeply/nested/directory/moved.txt"),
+        )
+        .unwrap();
+        fs::write(
+            path.join("some/deeply/nested/directory/moved.txt"),
+            lines(30, "l").replace("l line 30\n", "last\n"),
+        )
+        .unwrap();
+        fs::write(path.join("bin.dat"), [0u8, 9, 8, 7, 6, 5, 4, 3]).unwrap();
+        fs::write(path.join("new.txt"), "new\n").unwrap();
+        git_output(&path, &["add", "-A"]);
+        git_output(&path, &["commit", "-m", "second"]);
+
+        // staged and unstaged changes, including whitespace only ones
+        fs::write(path.join("new.txt"), "new\nstaged\n").unwrap();
+        git_output(&path, &["add", "new.txt"]);
+        fs::write(
+            path.join("a.txt"),
+            a.replace("a line 10\n", "a  line 10 \n")
+                .replace("a line 15\n", "a line fifteen\n"),
+        )
+        .unwrap();
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let cases: Vec<(Vec<&str>, Vec<&str>, Vec<DiffFlags>)> = vec![
+            (vec![], vec![], vec![]),
+            (vec![], vec!["--cached"], vec![DiffFlags::Cached(true)]),
+            (vec!["HEAD~1"], vec![], vec![]),
+            (
+                vec!["HEAD~1"],
+                vec!["--cached"],
+                vec![DiffFlags::Cached(true)],
+            ),
+            (vec!["HEAD~1", "HEAD"], vec![], vec![]),
+            (vec!["HEAD~1..HEAD"], vec![], vec![]),
+            (vec!["HEAD...HEAD~1"], vec![], vec![]),
+            (
+                vec!["HEAD~1", "HEAD"],
+                vec!["--no-renames"],
+                vec![DiffFlags::NoRenames(true)],
+            ),
+            (
+                vec!["HEAD", "HEAD~1"],
+                vec!["-R", "-U1"],
+                vec![DiffFlags::Reverse(true), DiffFlags::Unified(1)],
+            ),
+            (vec![], vec!["-w"], vec![DiffFlags::IgnoreAllSpace(true)]),
+            (vec![], vec!["-b"], vec![DiffFlags::IgnoreSpaceChange(true)]),
+            (
+                vec![],
+                vec!["-U2", "--inter-hunk-context=5"],
+                vec![DiffFlags::Unified(2), DiffFlags::InterHunkContext(5)],
+            ),
+        ];
+
+        let mut results = vec![];
+        for (revisions, args, flags) in cases {
+            let formats: [(&str, Render); 7] = [
+                ("--patch", |output| output.patch().unwrap()),
+                ("--stat=80", |output| output.stat(80).unwrap()),
+                ("--stat=40", |output| output.stat(40).unwrap()),
+                ("--numstat", |output| output.numstat().unwrap()),
+                ("--shortstat", |output| output.shortstat().unwrap()),
+                ("--name-only", |output| output.name_only().unwrap()),
+                ("--name-status", |output| output.name_status().unwrap()),
+            ];
+            for (format, render) in formats {
+                // `-U` implies `--patch` for git
+                if format != "--patch" && args.iter().any(|arg| arg.starts_with("-U")) {
+                    continue;
+                }
+                let mut config = DiffConfig::new(revisions.iter().map(|r| r.to_string()).collect());
+                for flag in flags.clone() {
+                    config.add_flag(flag);
+                }
+                let ours = render(&repo.git_diff(config).unwrap());
+                let output = git_output(
+                    &path,
+                    &[&["diff", format], &args[..], &revisions[..]].concat(),
+                );
+                let theirs = String::from_utf8_lossy(&output.stdout).to_string();
+                results.push((format, args.clone(), revisions.clone(), ours, theirs));
+            }
+        }
+
+        // path limiting
+        let mut config = DiffConfig::new(vec!["HEAD~1".to_string(), "HEAD".to_string()]);
+        config.set_paths(vec!["a.txt".to_string(), "new.txt".to_string()]);
+        let ours = repo.git_diff(config).unwrap().name_status().unwrap();
+        let output = git_output(
+            &path,
+            &[
+                "diff",
+                "--name-status",
+                "HEAD~1",
+                "HEAD",
+                "--",
+                "a.txt",
+                "new.txt",
+            ],
+        );
+        let theirs = String::from_utf8_lossy(&output.stdout).to_string();
+        results.push(("--name-status", vec!["--"], vec![], ours, theirs));
+
+        let config = DiffConfig::new(vec!["HEAD~1".to_string(), "HEAD".to_string()]);
+        let files = repo.git_diff(config).unwrap().files().unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (format, args, revisions, ours, theirs) in results {
+            assert_eq!(ours, theirs, "{} {:?} {:?}", format, args, revisions);
+        }
+
+        let renamed = files.iter().find(|f| f.status == Delta::Renamed).unwrap();
+        assert_eq!(renamed.similarity, Some(96));
+        assert_eq!((renamed.insertions, renamed.deletions), (1, 1));
+        let modified = files
+            .iter()
+            .find(|f| f.status == Delta::Modified && !f.binary);
+        let hunk = &modified.unwrap().hunks[0];
+        assert_eq!((hunk.old_start, hunk.new_start), (1, 1));
+        assert!(hunk.header.starts_with("@@ -1,6 +1,6 @@"));
+        let removed = hunk.lines.iter().find(|line| line.origin == '-').unwrap();
+        assert_eq!(removed.content, "a line 3\n");
+        assert_eq!((removed.old_lineno, removed.new_lineno), (Some(3), None));
+        assert!(files.iter().any(|f| f.binary && f.insertions == 0));
+    }
+
+    #[test]
+    fn git_word_diff_matches_cli_test() {
+        let dir_name = "./temp_test/word_diff/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        let path = env::current_dir().unwrap().join(dir_name);
+        git_output(&path, &["init", "-b", "main"]);
+        git_output(&path, &["config", "user.name", "Committer"]);
+        git_output(&path, &["config", "user.email", "committer@example.com"]);
+        let mut text: String = (1..=20).map(|i| format!("fn line_{}()\n", i)).collect();
+        text.push_str("foo bar baz\n\nremoved line\nline two here\nsame\nlast");
+        fs::write(path.join("a.txt"), &text).unwrap();
+        fs::write(path.join("b.txt"), "a(b, c);\n").unwrap();
+        fs::write(path.join("bin.dat"), [0u8, 1, 2]).unwrap();
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "first"]);
+
+        let text = text
+            .replace("fn line_3()", "fn line_three( )")
+            .replace("foo bar baz", "foo  qux baz\nnew line")
+            .replace("removed line\n", "")
+            .replace("line two here", "line 2 here  extra")
+            .replace("last", "last\n");
+        fs::write(path.join("a.txt"), text).unwrap();
+        fs::write(path.join("b.txt"), "a(d, c);\n").unwrap();
+        fs::write(path.join("bin.dat"), [0u8, 1, 3]).unwrap();
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let cases = [
+            (WordDiffMode::Plain, vec!["--word-diff=plain"], None),
+            (WordDiffMode::Porcelain, vec!["--word-diff=porcelain"], None),
+            (
+                WordDiffMode::Color,
+                vec!["--word-diff=color", "--color=always"],
+                None,
+            ),
+            (
+                WordDiffMode::Plain,
+                vec!["--word-diff=plain", "--word-diff-regex=[a-z]+|[^[:space:]]"],
+                Some("[a-z]+|[^[:space:]]"),
+            ),
+        ];
+        let mut results = vec![];
+        for (mode, args, regex) in cases {
+            let mut config = DiffConfig::new(vec![]);
+            config.add_flag(DiffFlags::WordDiffRegex(regex.map(String::from)));
+            let ours = repo.git_diff(config).unwrap().word_diff(mode).unwrap();
+            let output = git_output(&path, &[&["diff"], &args[..]].concat());
+            let theirs = String::from_utf8_lossy(&output.stdout).to_string();
+            results.push((args, ours, theirs));
+        }
+
+        let files = repo
+            .git_diff(DiffConfig::new(vec![]))
+            .unwrap()
+            .files()
+            .unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (args, ours, theirs) in results {
+            assert_eq!(ours, theirs, "{:?}", args);
+        }
+
+        // character level highlighting of the modified line pairs
+        let hunk = &files[0].hunks[0];
+        let pairs = hunk.highlights();
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(hunk.lines[pairs[0].old_index].content, "fn line_3()\n");
+        assert_eq!(hunk.lines[pairs[0].new_index].content, "fn line_three( )\n");
+        assert_eq!(pairs[0].highlight.old, vec![8..9]);
+        assert_eq!(pairs[0].highlight.new, vec![8..13, 14..15]);
+        let hunk = &files[1].hunks[0];
+        let pairs = hunk.highlights();
+        assert_eq!(pairs[0].highlight.old, vec![2..3]);
+        assert_eq!(pairs[0].highlight.new, vec![2..3]);
+
+        let highlight = crate::highlight_line_pair("héllo wörld", "héllo world!");
+        assert_eq!(highlight.old, vec![8..10]);
+        assert_eq!(highlight.new, vec![8..9, 12..13]);
+    }
+}
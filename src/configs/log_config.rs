@@ -0,0 +1,653 @@
+use std::collections::HashSet;
+
+use crate::{GitRepository, helpers::date};
+use git2::{
+    Commit, DiffOptions, Error, Oid, Repository, RevparseMode, Revwalk, Signature, Sort, Tree,
+};
+use regex::{Regex, RegexBuilder};
+
+/// A struct used to specify various details about the `git log` command.
+pub struct LogConfig {
+    revisions: Vec<String>,
+    paths: Vec<String>,
+    flags: LogFlagsInternal,
+}
+
+impl LogConfig {
+    /// Creates a LogConfig object for the passed revisions, `HEAD` is used if empty.
+    ///
+    /// Revisions can be single commits, ranges like `A..B` and `A...B`, or excluded commits
+    /// like `^X`.
+    pub fn new(revisions: Vec<String>) -> Self {
+        LogConfig {
+            revisions,
+            paths: vec![],
+            flags: LogFlagsInternal::default(),
+        }
+    }
+
+    pub fn get_revisions(&self) -> &Vec<String> {
+        &self.revisions
+    }
+
+    /// Limits the history to commits touching the paths, same as `git log -- <path>...`.
+    ///
+    /// History is simplified like git's default mode, following only a parent that is the same
+    /// as the merge for the given paths.
+    pub fn set_paths(&mut self, paths: Vec<String>) -> &Self {
+        self.paths = paths;
+        self
+    }
+
+    pub fn add_flag(&mut self, flag: LogFlags) -> &Self {
+        match flag {
+            LogFlags::MaxCount(count) => self.flags.max_count = count,
+            LogFlags::Skip(skip) => self.flags.skip = skip,
+            LogFlags::Author(author) => self.flags.author = author,
+            LogFlags::Committer(committer) => self.flags.committer = committer,
+            LogFlags::Grep(grep) => self.flags.grep = grep,
+            LogFlags::RegexpIgnoreCase(ignore) => self.flags.ignore_case = ignore,
+            LogFlags::Since(since) => self.flags.since = since,
+            LogFlags::Until(until) => self.flags.until = until,
+            LogFlags::FirstParent(first) => self.flags.first_parent = first,
+            LogFlags::NoMerges(no_merges) => self.flags.no_merges = no_merges,
+            LogFlags::Merges(merges) => self.flags.merges = merges,
+            LogFlags::Order(order) => self.flags.order = order,
+            LogFlags::Reverse(reverse) => self.flags.reverse = reverse,
+        };
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct LogFlagsInternal {
+    max_count: Option<usize>,
+    skip: usize,
+    author: Option<String>,
+    committer: Option<String>,
+    grep: Option<String>,
+    ignore_case: bool,
+    since: Option<String>,
+    until: Option<String>,
+    first_parent: bool,
+    no_merges: bool,
+    merges: bool,
+    order: LogOrder,
+    reverse: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum LogFlags {
+    /// `-n <number>` or `--max-count=<number>` flag for `git log`.
+    /// Limits the number of commits to output. Passing `None` unsets the flag.
+    MaxCount(Option<usize>),
+
+    /// `--skip=<number>` flag for `git log`.
+    /// Skips the number of commits before starting to output.
+    /// Defaults to 0.
+    Skip(usize),
+
+    /// `--author=<pattern>` flag for `git log`.
+    /// Only outputs commits whose author ("Name <email>") matches the regular expression.
+    /// Passing `None` unsets the flag.
+    Author(Option<String>),
+
+    /// `--committer=<pattern>` flag for `git log`.
+    /// Only outputs commits whose committer ("Name <email>") matches the regular expression.
+    /// Passing `None` unsets the flag.
+    Committer(Option<String>),
+
+    /// `--grep=<pattern>` flag for `git log`.
+    /// Only outputs commits whose message matches the regular expression.
+    /// Passing `None` unsets the flag.
+    Grep(Option<String>),
+
+    /// `-i` or `--regexp-ignore-case` flag for `git log`.
+    /// Matches the `Author`, `Committer` and `Grep` patterns case insensitively.
+    /// Defaults to false.
+    RegexpIgnoreCase(bool),
+
+    /// `--since=<date>` flag for `git log`.
+    /// Only outputs commits committed at or after the date, see `CommitFlags::Date` for the
+    /// supported formats, relative dates like "2 weeks ago" are supported as well.
+    /// Passing `None` unsets the flag.
+    Since(Option<String>),
+
+    /// `--until=<date>` flag for `git log`.
+    /// Only outputs commits committed at or before the date. Passing `None` unsets the flag.
+    Until(Option<String>),
+
+    /// `--first-parent` flag for `git log`.
+    /// Follows only the first parent of merge commits.
+    /// Defaults to false.
+    FirstParent(bool),
+
+    /// `--no-merges` flag for `git log`.
+    /// Skips commits with more than one parent.
+    /// Defaults to false.
+    NoMerges(bool),
+
+    /// `--merges` flag for `git log`.
+    /// Only outputs commits with more than one parent.
+    /// Defaults to false.
+    Merges(bool),
+
+    /// `--topo-order` or `--date-order` flag for `git log`.
+    /// Defaults to `LogOrder::Default`, reverse chronological order.
+    Order(LogOrder),
+
+    /// `--reverse` flag for `git log`.
+    /// Outputs the selected commits in reverse order, `MaxCount` and `Skip` are applied
+    /// before reversing like git does. This needs to walk the whole selection first.
+    /// Defaults to false.
+    Reverse(bool),
+}
+
+/// Order in which commits are output by `git log`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LogOrder {
+    /// Reverse chronological order.
+    #[default]
+    Default,
+
+    /// `--topo-order`, parents are never shown before all of their children and lines of
+    /// history are not intermixed.
+    Topo,
+
+    /// `--date-order`, parents are never shown before all of their children, otherwise
+    /// commits are shown in commit timestamp order.
+    Date,
+}
+
+/// A commit as returned by `git_log`.
+#[derive(Clone)]
+pub struct CommitRecord {
+    pub id: Oid,
+    pub tree_id: Oid,
+    pub parent_ids: Vec<Oid>,
+    pub author: Signature<'static>,
+    pub committer: Signature<'static>,
+    /// Raw commit message.
+    pub message: String,
+}
+
+impl CommitRecord {
+    pub(crate) fn from_commit(commit: &Commit) -> Self {
+        CommitRecord {
+            id: commit.id(),
+            tree_id: commit.tree_id(),
+            parent_ids: commit.parent_ids().collect(),
+            author: commit.author().to_owned(),
+            committer: commit.committer().to_owned(),
+            message: String::from_utf8_lossy(commit.message_bytes()).to_string(),
+        }
+    }
+
+    /// Returns the subject of the commit, the first paragraph of the message joined into a
+    /// single line.
+    pub fn subject(&self) -> String {
+        let message = self.message.trim_start_matches('\n');
+        let paragraph = match message.find("\n\n") {
+            Some(index) => &message[..index],
+            None => message,
+        };
+        let lines: Vec<&str> = paragraph.lines().map(|line| line.trim()).collect();
+        lines.join(" ")
+    }
+
+    /// Returns the body of the commit, the message after the subject paragraph.
+    pub fn body(&self) -> &str {
+        let message = self.message.trim_start_matches('\n');
+        match message.find("\n\n") {
+            Some(index) => message[index..].trim_start_matches('\n'),
+            None => "",
+        }
+    }
+
+    /// Returns true for commits with more than one parent.
+    pub fn is_merge(&self) -> bool {
+        self.parent_ids.len() > 1
+    }
+}
+
+/// Lazy iterator over the commits selected by a `LogConfig`, returned by `git_log`.
+pub struct LogIter<'repo> {
+    repository: &'repo Repository,
+    revwalk: Revwalk<'repo>,
+    paths: Vec<String>,
+    // commits reached through the simplified history when limiting by paths
+    wanted: HashSet<Oid>,
+    author: Option<Regex>,
+    committer: Option<Regex>,
+    grep: Option<Regex>,
+    since: Option<i64>,
+    until: Option<i64>,
+    first_parent: bool,
+    no_merges: bool,
+    merges: bool,
+    skip: usize,
+    remaining: Option<usize>,
+    reverse: bool,
+    reversed: Option<Vec<CommitRecord>>,
+}
+
+impl GitRepository {
+    /// Walks the history selected by the config, same as `git log` or `git rev-list`.
+    ///
+    /// Commits are read lazily while iterating, except with `LogFlags::Reverse`.
+    pub fn git_log(&self, config: LogConfig) -> Result<LogIter<'_>, Error> {
+        if let Some(repository) = &self.repository {
+            let flags = config.flags;
+            let mut revwalk = repository.revwalk()?;
+
+            let sort = match flags.order {
+                // the simplified history is computed from children to parents
+                LogOrder::Default if config.paths.is_empty() => Sort::NONE,
+                LogOrder::Default | LogOrder::Date => Sort::TOPOLOGICAL | Sort::TIME,
+                LogOrder::Topo => Sort::TOPOLOGICAL,
+            };
+            revwalk.set_sorting(sort)?;
+            if flags.first_parent {
+                revwalk.simplify_first_parent()?;
+            }
+
+            let mut wanted = HashSet::new();
+            if config.revisions.is_empty() {
+                revwalk.push_head()?;
+                wanted.insert(repository.head()?.peel_to_commit()?.id());
+            }
+            for revision in &config.revisions {
+                push_revision(repository, &mut revwalk, revision, &mut wanted)?;
+            }
+
+            let regex = |pattern: &Option<String>| -> Result<Option<Regex>, Error> {
+                match pattern {
+                    Some(pattern) => RegexBuilder::new(pattern)
+                        .case_insensitive(flags.ignore_case)
+                        .multi_line(true)
+                        .build()
+                        .map(Some)
+                        .map_err(|e| Error::from_str(&e.to_string())),
+                    None => Ok(None),
+                }
+            };
+            let date = |date: &Option<String>| -> Result<Option<i64>, Error> {
+                match date {
+                    Some(date) => Ok(Some(date::parse_date(date)?.seconds())),
+                    None => Ok(None),
+                }
+            };
+
+            return Ok(LogIter {
+                repository,
+                revwalk,
+                paths: config.paths,
+                wanted,
+                author: regex(&flags.author)?,
+                committer: regex(&flags.committer)?,
+                grep: regex(&flags.grep)?,
+                since: date(&flags.since)?,
+                until: date(&flags.until)?,
+                first_parent: flags.first_parent,
+                no_merges: flags.no_merges,
+                merges: flags.merges,
+                skip: flags.skip,
+                remaining: flags.max_count,
+                reverse: flags.reverse,
+                reversed: None,
+            });
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+}
+
+/// Pushes or hides the commits of a single revision argument, `A`, `^A`, `A..B` or `A...B`.
+fn push_revision(
+    repository: &Repository,
+    revwalk: &mut Revwalk,
+    revision: &str,
+    wanted: &mut HashSet<Oid>,
+) -> Result<(), Error> {
+    if let Some(revision) = revision.strip_prefix('^') {
+        let commit = repository.revparse_single(revision)?.peel_to_commit()?;
+        return revwalk.hide(commit.id());
+    }
+
+    let revspec = repository.revparse(revision)?;
+    let from = match revspec.from() {
+        Some(from) => from.peel_to_commit()?.id(),
+        None => repository.head()?.peel_to_commit()?.id(),
+    };
+    if !revspec.mode().contains(RevparseMode::RANGE) {
+        wanted.insert(from);
+        return revwalk.push(from);
+    }
+
+    let to = match revspec.to() {
+        Some(to) => to.peel_to_commit()?.id(),
+        None => repository.head()?.peel_to_commit()?.id(),
+    };
+    if revspec.mode().contains(RevparseMode::MERGE_BASE) {
+        // symmetric difference, commits reachable from either side but not from both
+        for base in repository.merge_bases(from, to)?.iter() {
+            revwalk.hide(*base)?;
+        }
+        wanted.insert(from);
+        revwalk.push(from)?;
+    } else {
+        revwalk.hide(from)?;
+    }
+    wanted.insert(to);
+    revwalk.push(to)
+}
+
+impl LogIter<'_> {
+    fn next_commit(&mut self) -> Option<Result<CommitRecord, Error>> {
+        loop {
+            let oid = match self.revwalk.next()? {
+                Ok(oid) => oid,
+                Err(e) => return Some(Err(e)),
+            };
+            let commit = match self.repository.find_commit(oid) {
+                Ok(commit) => commit,
+                Err(e) => return Some(Err(e)),
+            };
+
+            if !self.paths.is_empty() {
+                match self.simplify(&commit) {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            if !self.matches(&commit) {
+                continue;
+            }
+            if self.skip > 0 {
+                self.skip -= 1;
+                continue;
+            }
+            match self.remaining {
+                Some(0) => return None,
+                Some(remaining) => self.remaining = Some(remaining - 1),
+                None => {}
+            }
+            return Some(Ok(CommitRecord::from_commit(&commit)));
+        }
+    }
+
+    /// Decides whether the commit is part of the history simplified for the paths and marks
+    /// the parents to follow. Returns true if the commit changes the paths.
+    fn simplify(&mut self, commit: &Commit) -> Result<bool, Error> {
+        if !self.wanted.remove(&commit.id()) {
+            return Ok(false);
+        }
+
+        let tree = commit.tree()?;
+        let mut parents: Vec<Commit> = commit.parents().collect();
+        if self.first_parent {
+            parents.truncate(1);
+        }
+        if parents.is_empty() {
+            return Ok(!self.same_for_paths(None, &tree)?);
+        }
+
+        let mut same = vec![];
+        for parent in &parents {
+            same.push(self.same_for_paths(Some(&parent.tree()?), &tree)?);
+        }
+
+        // a merge taking the paths from one parent only follows that parent
+        if parents.len() > 1
+            && let Some(index) = same.iter().position(|same| *same)
+        {
+            self.wanted.insert(parents[index].id());
+            return Ok(false);
+        }
+        for parent in &parents {
+            self.wanted.insert(parent.id());
+        }
+        Ok(!same.iter().any(|same| *same))
+    }
+
+    fn same_for_paths(&self, old: Option<&Tree>, new: &Tree) -> Result<bool, Error> {
+        let mut options = DiffOptions::new();
+        for path in &self.paths {
+            options.pathspec(path);
+        }
+        let diff = self
+            .repository
+            .diff_tree_to_tree(old, Some(new), Some(&mut options))?;
+        Ok(diff.deltas().len() == 0)
+    }
+
+    fn matches(&self, commit: &Commit) -> bool {
+        let merge = commit.parent_count() > 1;
+        if (self.no_merges && merge) || (self.merges && !merge) {
+            return false;
+        }
+
+        let time = commit.committer().when().seconds();
+        if self.since.is_some_and(|since| time < since)
+            || self.until.is_some_and(|until| time > until)
+        {
+            return false;
+        }
+
+        let identity = |signature: Signature| {
+            format!(
+                "{} <{}>",
+                String::from_utf8_lossy(signature.name_bytes()),
+                String::from_utf8_lossy(signature.email_bytes())
+            )
+        };
+        if let Some(author) = &self.author
+            && !author.is_match(&identity(commit.author()))
+        {
+            return false;
+        }
+        if let Some(committer) = &self.committer
+            && !committer.is_match(&identity(commit.committer()))
+        {
+            return false;
+        }
+        if let Some(grep) = &self.grep
+            && !grep.is_match(&String::from_utf8_lossy(commit.message_bytes()))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+impl Iterator for LogIter<'_> {
+    type Item = Result<CommitRecord, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if !self.reverse {
+            return self.next_commit();
+        }
+
+        if self.reversed.is_none() {
+            let mut commits = vec![];
+            while let Some(commit) = self.next_commit() {
+                match commit {
+                    Ok(commit) => commits.push(commit),
+                    Err(e) => return Some(Err(e)),
+                }
+            }
+            self.reversed = Some(commits);
+        }
+        self.reversed.as_mut()?.pop().map(Ok)
+    }
+}
+
+#[cfg(test)]
+mod log_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{LogConfig, LogFlags, LogOrder};
+    use crate::{
+        GitRepository,
+        helpers::test_util::{git_command, git_output},
+    };
+
+    #[test]
+    fn git_log_matches_cli_test() {
+        let dir_name = "./temp_test/log/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // a history with a feature branch merged into main, one commit per minute
+        let path = env::current_dir().unwrap().join(dir_name);
+        let mut time = 1_700_000_000;
+        let mut commit = |author: &str, file: &str, message: &str| {
+            time += 60;
+            if !file.is_empty() {
+                fs::write(path.join(file), time.to_string()).unwrap();
+                git_output(&path, &["add", file]);
+            }
+            let date = format!("@{} +0000", time);
+            git_command(&path)
+                .args(["commit", "--allow-empty", "-m", message])
+                .args(["--author", author])
+                .env("GIT_AUTHOR_DATE", &date)
+                .env("GIT_COMMITTER_DATE", &date)
+                .output()
+                .unwrap();
+        };
+        git_output(&path, &["init", "-b", "main"]);
+        git_output(&path, &["config", "user.name", "Committer"]);
+        git_output(&path, &["config", "user.email", "committer@example.com"]);
+        commit("Bob <bob@example.com>", "a.txt", "first");
+        commit("Bob <bob@example.com>", "b.txt", "second");
+        git_output(&path, &["checkout", "-b", "feature"]);
+        commit("Alice <alice@example.com>", "a.txt", "change a");
+        commit("Alice <alice@example.com>", "c.txt", "add c");
+        git_output(&path, &["checkout", "main"]);
+        commit("Bob <bob@example.com>", "b.txt", "fix bug\n\nin b");
+        git_command(&path)
+            .args(["merge", "--no-ff", "-m", "merge feature", "feature"])
+            .env("GIT_AUTHOR_DATE", "@1700000400 +0000")
+            .env("GIT_COMMITTER_DATE", "@1700000400 +0000")
+            .output()
+            .unwrap();
+        commit("Bob <bob@example.com>", "b.txt", "after merge");
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let cases: Vec<(Vec<&str>, Vec<&str>, Vec<LogFlags>)> = vec![
+            (vec![], vec![], vec![]),
+            (
+                vec![],
+                vec!["--max-count=2", "--skip=1"],
+                vec![LogFlags::MaxCount(Some(2)), LogFlags::Skip(1)],
+            ),
+            (vec!["feature..main"], vec![], vec![]),
+            (vec!["main...feature~1"], vec![], vec![]),
+            (vec!["main", "^feature"], vec![], vec![]),
+            (
+                vec![],
+                vec!["-i", "--author=ALICE"],
+                vec![
+                    LogFlags::RegexpIgnoreCase(true),
+                    LogFlags::Author(Some("ALICE".into())),
+                ],
+            ),
+            (
+                vec![],
+                vec!["--committer=committer@"],
+                vec![LogFlags::Committer(Some("committer@".into()))],
+            ),
+            (
+                vec![],
+                vec!["--grep=^fix"],
+                vec![LogFlags::Grep(Some("^fix".into()))],
+            ),
+            (
+                vec![],
+                vec!["--since=@1700000180", "--until=@1700000300"],
+                vec![
+                    LogFlags::Since(Some("@1700000180".into())),
+                    LogFlags::Until(Some("@1700000300".into())),
+                ],
+            ),
+            (
+                vec![],
+                vec!["--first-parent"],
+                vec![LogFlags::FirstParent(true)],
+            ),
+            (vec![], vec!["--no-merges"], vec![LogFlags::NoMerges(true)]),
+            (vec![], vec!["--merges"], vec![LogFlags::Merges(true)]),
+            (
+                vec![],
+                vec!["--date-order"],
+                vec![LogFlags::Order(LogOrder::Date)],
+            ),
+            (
+                vec![],
+                vec!["--reverse", "-n", "3"],
+                vec![LogFlags::Reverse(true), LogFlags::MaxCount(Some(3))],
+            ),
+        ];
+
+        let mut results = vec![];
+        for (revisions, args, flags) in cases {
+            let mut config = LogConfig::new(revisions.iter().map(|r| r.to_string()).collect());
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            let ours: Vec<String> = repo
+                .git_log(config)
+                .unwrap()
+                .map(|commit| commit.unwrap().id.to_string())
+                .collect();
+            let output = git_output(
+                &path,
+                &[&["log", "--format=%H"], &args[..], &revisions[..]].concat(),
+            );
+            let theirs: Vec<String> = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect();
+            results.push((args, revisions, ours, theirs));
+        }
+
+        // path limiting with history simplification
+        for paths in [vec!["a.txt"], vec!["b.txt"], vec!["c.txt", "a.txt"]] {
+            let mut config = LogConfig::new(vec![]);
+            config.set_paths(paths.iter().map(|p| p.to_string()).collect());
+            let ours: Vec<String> = repo
+                .git_log(config)
+                .unwrap()
+                .map(|commit| commit.unwrap().id.to_string())
+                .collect();
+            let output = git_output(&path, &[&["log", "--format=%H", "--"], &paths[..]].concat());
+            let theirs: Vec<String> = String::from_utf8_lossy(&output.stdout)
+                .lines()
+                .map(String::from)
+                .collect();
+            results.push((paths, vec![], ours, theirs));
+        }
+
+        let mut config = LogConfig::new(vec!["HEAD~1".to_string()]);
+        config.add_flag(LogFlags::Grep(Some("fix".into())));
+        let fix = repo.git_log(config).unwrap().next().unwrap().unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (args, revisions, ours, theirs) in results {
+            assert!(!theirs.is_empty(), "{:?} {:?}", args, revisions);
+            assert_eq!(ours, theirs, "{:?} {:?}", args, revisions);
+        }
+        assert_eq!(fix.subject(), "fix bug");
+        assert_eq!(fix.body(), "in b\n");
+        assert!(!fix.is_merge());
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{GitRepository, helpers::pattern::wildmatch};
+use git2::{CertificateCheckStatus, Direction, Error, Oid, Remote, RemoteCallbacks};
+
+/// A struct used to specify various details about the `git ls-remote` command.
+#[derive(Default, Clone)]
+pub struct LsRemoteConfig {
+    patterns: Vec<String>,
+    flags: LsRemoteFlagsInternal,
+}
+
+impl LsRemoteConfig {
+    pub fn new() -> Self {
+        LsRemoteConfig {
+            patterns: vec![],
+            flags: LsRemoteFlagsInternal::default(),
+        }
+    }
+
+    /// Only lists the references matching at least one of the glob patterns, same as
+    /// `git ls-remote <repository> <patterns>...`.
+    ///
+    /// A pattern is matched against the tail of the reference name starting after a `/`, so
+    /// "main" matches "refs/heads/main" but not "refs/heads/domain".
+    pub fn set_patterns(&mut self, patterns: Vec<String>) -> &Self {
+        self.patterns = patterns;
+        self
+    }
+
+    pub fn add_flag(&mut self, flag: LsRemoteFlags) -> &Self {
+        match flag {
+            LsRemoteFlags::Heads(heads) => self.flags.heads = heads,
+            LsRemoteFlags::Tags(tags) => self.flags.tags = tags,
+            LsRemoteFlags::Refs(refs) => self.flags.refs = refs,
+            LsRemoteFlags::Symref(symref) => self.flags.symref = symref,
+        };
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct LsRemoteFlagsInternal {
+    heads: bool,
+    tags: bool,
+    refs: bool,
+    symref: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum LsRemoteFlags {
+    /// `--heads` or `--branches` flag, `true` lists only the branches (`refs/heads/`).
+    /// Combined with `Tags` both branches and tags are listed.
+    /// Defaults to false.
+    Heads(bool),
+
+    /// `--tags` flag, `true` lists only the tags (`refs/tags/`), including their peeled
+    /// `^{}` entries. Combined with `Heads` both branches and tags are listed.
+    /// Defaults to false.
+    Tags(bool),
+
+    /// `--refs` flag, `true` leaves out the peeled tags and pseudo refs like `HEAD`.
+    /// Defaults to false.
+    Refs(bool),
+
+    /// `--symref` flag, `true` fills in `RemoteRef::symref_target` for symbolic refs.
+    /// Defaults to false.
+    Symref(bool),
+}
+
+/// A reference advertised by a remote, as returned by `ls_remote`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteRef {
+    /// Full name of the reference, e.g. "refs/heads/main", "HEAD" or "refs/tags/v1.0^{}".
+    pub name: String,
+
+    /// Id the reference points to.
+    pub oid: Oid,
+
+    /// Reference a symbolic reference points to, e.g. "refs/heads/main" for `HEAD`.
+    pub symref_target: Option<String>,
+}
+
+impl GitRepository {
+    /// Lists the references of a remote repository, same as `git ls-remote`.
+    ///
+    /// `url_or_name` is the name of a remote of the opened repository or a url. Works without
+    /// an opened repository as well, in which case it must be a url. The configured credentials
+    /// and certificate check settings are used to connect.
+    ///
+    /// Example of listing the tags of a repository without opening or cloning it.
+    /// ```ignore
+    /// let mut config = LsRemoteConfig::new();
+    /// config.add_flag(LsRemoteFlags::Tags(true));
+    /// let tags = GitRepository::new().ls_remote("https://github.com/git/git.git", config)?;
+    /// ```
+    pub fn ls_remote(
+        &self,
+        url_or_name: &str,
+        config: LsRemoteConfig,
+    ) -> Result<Vec<RemoteRef>, Error> {
+        let mut remote = match &self.repository {
+            Some(repository) => match repository.find_remote(url_or_name) {
+                Ok(remote) => remote,
+                Err(_) => repository.remote_anonymous(url_or_name)?,
+            },
+            None => Remote::create_detached(url_or_name)?,
+        };
+
+        let flags = &config.flags;
+        let mut refs = vec![];
+        for mut remote_ref in self.remote_heads(&mut remote)? {
+            let name = remote_ref.name.as_str();
+
+            // --heads and --tags
+            if (flags.heads || flags.tags)
+                && !(flags.heads && name.starts_with("refs/heads/")
+                    || flags.tags && name.starts_with("refs/tags/"))
+            {
+                continue;
+            }
+
+            // --refs
+            if flags.refs && (!name.starts_with("refs/") || name.ends_with("^{}")) {
+                continue;
+            }
+
+            // patterns, matched against the tail of the name
+            let tail = format!("/{}", name);
+            if !config.patterns.is_empty()
+                && !config
+                    .patterns
+                    .iter()
+                    .any(|pattern| wildmatch(&format!("*/{}", pattern), &tail))
+            {
+                continue;
+            }
+
+            // --symref
+            if !flags.symref {
+                remote_ref.symref_target = None;
+            }
+            refs.push(remote_ref);
+        }
+        Ok(refs)
+    }
+
+    /// Connects to `remote` with the configured credentials and returns the references it
+    /// advertises. The symref target of `HEAD` is the default branch of the remote.
+    pub(crate) fn remote_heads(&self, remote: &mut Remote) -> Result<Vec<RemoteRef>, Error> {
+        let mut callbacks = RemoteCallbacks::new();
+
+        // continue even if cert checks fail, if configured so
+        if self.bypass_certificate_check {
+            callbacks.certificate_check(|_, _| Ok(CertificateCheckStatus::CertificateOk));
+        }
+
+        // setup credentials
+        let cred = self.cred.clone();
+        callbacks.credentials(move |_a: &str, _b, _c| cred.get_cred());
+
+        let connection = remote.connect_auth(Direction::Fetch, Some(callbacks), None)?;
+        let heads = connection
+            .list()?
+            .iter()
+            .map(|head| RemoteRef {
+                name: head.name().to_string(),
+                oid: head.oid(),
+                symref_target: head.symref_target().map(|target| target.to_string()),
+            })
+            .collect();
+        Ok(heads)
+    }
+}
+
+#[cfg(test)]
+mod ls_remote_test {
+    use std::{env, path::Path, process::Command};
+
+    use super::{LsRemoteConfig, LsRemoteFlags, RemoteRef};
+    use crate::{GitRepository, helpers::test_util::git_output};
+
+    #[test]
+    fn ls_remote_matches_cli_test() {
+        let dir_name = "./temp_test/ls_remote/";
+        let remote_name = "./temp_test/ls_remote_remote.git";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a bare remote with two branches, a lightweight and an annotated tag
+        let path = env::current_dir().unwrap().join(dir_name);
+        let remote = env::current_dir().unwrap().join(remote_name);
+        let url = remote.to_str().unwrap();
+        git_output(&path, &["init", "--initial-branch", "main"]);
+        git_output(&path, &["init", "--bare", "--initial-branch", "main", url]);
+        git_output(&path, &["remote", "add", "origin", url]);
+        git_output(&path, &["config", "user.name", "Remote"]);
+        git_output(&path, &["config", "user.email", "remote@example.com"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "first"]);
+        git_output(&path, &["tag", "light"]);
+        git_output(&path, &["tag", "-a", "v1.0", "-m", "annotated"]);
+        git_output(&path, &["push", "origin", "main", "main:domain", "--tags"]);
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let ls_remote = |repo: &GitRepository, patterns: Vec<&str>, flags: Vec<LsRemoteFlags>| {
+            let mut config = LsRemoteConfig::new();
+            config.set_patterns(patterns.into_iter().map(String::from).collect());
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            repo.ls_remote("origin", config).unwrap()
+        };
+        let cli = |args: &[&str]| {
+            let out = git_output(&path, &[&["ls-remote"], args, &["origin"]].concat());
+            String::from_utf8_lossy(&out.stdout).to_string()
+        };
+        let format = |refs: Vec<RemoteRef>| {
+            refs.iter()
+                .map(|remote_ref| format!("{}\t{}\n", remote_ref.oid, remote_ref.name))
+                .collect::<String>()
+        };
+
+        let all = format(ls_remote(&repo, vec![], vec![]));
+        let git_all = cli(&[]);
+        let heads = format(ls_remote(&repo, vec![], vec![LsRemoteFlags::Heads(true)]));
+        let git_heads = cli(&["--heads"]);
+        let tags = format(ls_remote(&repo, vec![], vec![LsRemoteFlags::Tags(true)]));
+        let git_tags = cli(&["--tags"]);
+        let refs = format(ls_remote(&repo, vec![], vec![LsRemoteFlags::Refs(true)]));
+        let git_refs = cli(&["--refs"]);
+        let pattern = ls_remote(&repo, vec!["main", "v*"], vec![]);
+        let git_pattern = git_output(&path, &["ls-remote", "origin", "main", "v*"]);
+        let symref = ls_remote(&repo, vec!["HEAD"], vec![LsRemoteFlags::Symref(true)]);
+        let no_symref = ls_remote(&repo, vec!["HEAD"], vec![]);
+
+        // without an opened repository
+        let detached = GitRepository::new()
+            .ls_remote(url, LsRemoteConfig::new())
+            .unwrap();
+        let missing = GitRepository::new().ls_remote("origin", LsRemoteConfig::new());
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+        Command::new("rm")
+            .args(["-rf", remote_name])
+            .output()
+            .unwrap();
+
+        assert_eq!(all, git_all);
+        assert_eq!(heads, git_heads);
+        assert_eq!(tags, git_tags);
+        assert_eq!(refs, git_refs);
+        assert!(!refs.contains("^{}") && !refs.contains("HEAD"));
+        assert_eq!(
+            format(pattern.clone()),
+            String::from_utf8_lossy(&git_pattern.stdout)
+        );
+        let names: Vec<&str> = pattern.iter().map(|r| r.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["refs/heads/main", "refs/tags/v1.0", "refs/tags/v1.0^{}"]
+        );
+        assert_eq!(symref[0].name, "HEAD");
+        assert_eq!(symref[0].symref_target.as_deref(), Some("refs/heads/main"));
+        assert_eq!(no_symref[0].symref_target, None);
+        assert_eq!(format(detached), all);
+        assert!(missing.is_err());
+    }
+}
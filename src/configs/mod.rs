@@ -1,9 +1,16 @@
 pub mod add_config;
+pub mod blame_config;
 pub mod checkout_config;
 pub mod clone_config;
 pub mod commit_config;
+pub mod diff_config;
 pub mod fetch_config;
 pub mod init_config;
+pub mod log_config;
+pub mod ls_remote_config;
 pub mod push_config;
 pub mod remote_config;
 pub mod restore_config;
+pub mod switch_config;
+pub mod tag_config;
+pub mod verify_config;
@@ -1,5 +1,8 @@
-use crate::GitRepository;
-use git2::{BranchType, CertificateCheckStatus, Error, PushOptions, RemoteCallbacks};
+use crate::{GitRepository, helpers::hooks};
+use git2::{
+    BranchType, CertificateCheckStatus, Error, PushOptions, Remote, RemoteCallbacks, Repository,
+};
+use std::cell::RefCell;
 
 #[derive(Default, Clone)]
 pub struct PushConfig {
@@ -34,6 +37,7 @@ impl PushConfig {
         match flag {
             PushFlags::SetUpstream(set) => self.flags.set_upstream = set,
             PushFlags::All(all) => self.flags.all = all,
+            PushFlags::NoVerify(no_verify) => self.flags.no_verify = no_verify,
         };
         self
     }
@@ -43,11 +47,15 @@ impl PushConfig {
 pub(crate) struct PushFlagsInternal {
     set_upstream: bool,
     all: bool,
+    no_verify: bool,
 }
 
 pub enum PushFlags {
     SetUpstream(bool),
     All(bool),
+    /// `--no-verify` flag, `true` bypasses the `pre-push` hook. Hooks are only run when enabled
+    /// with `GitRepository::run_hooks`.
+    NoVerify(bool),
 }
 
 impl GitRepository {
@@ -80,6 +88,11 @@ impl GitRepository {
             let cred = self.cred.clone();
             callbacks.credentials(move |_a: &str, _b, _c| cred.get_cred());
 
+            // pre-push
+            if !config.flags.no_verify {
+                self.pre_push(repository, &remote, &mut callbacks);
+            }
+
             options.remote_callbacks(callbacks);
 
             let branch = repository.head()?;
@@ -153,3 +166,97 @@ impl GitRepository {
         ))
     }
 }
+
+impl GitRepository {
+    /// Pushes `refspecs` to the remote named `remote` with the configured credentials, returns
+    /// an error if the remote rejects any of the references. The `pre-push` hook is run unless
+    /// `no_verify` is set.
+    pub(crate) fn push_refspecs(
+        &self,
+        repository: &Repository,
+        remote: &str,
+        refspecs: &[String],
+        no_verify: bool,
+    ) -> Result<(), Error> {
+        let rejected = RefCell::new(vec![]);
+        let mut remote = repository.find_remote(remote)?;
+        let mut callbacks = RemoteCallbacks::new();
+        let mut options = PushOptions::new();
+
+        // continue even if cert checks fail, if configured so
+        if self.bypass_certificate_check {
+            callbacks.certificate_check(|_, _| Ok(CertificateCheckStatus::CertificateOk));
+        }
+
+        // setup credentials
+        let cred = self.cred.clone();
+        callbacks.credentials(move |_a: &str, _b, _c| cred.get_cred());
+
+        // pre-push
+        if !no_verify {
+            self.pre_push(repository, &remote, &mut callbacks);
+        }
+
+        // collect references rejected by the remote
+        callbacks.push_update_reference(|name, status| {
+            if let Some(status) = status {
+                rejected.borrow_mut().push(format!("{} ({})", name, status));
+            }
+            Ok(())
+        });
+
+        options.remote_callbacks(callbacks);
+        remote.push(refspecs, Some(&mut options))?;
+        drop(options);
+
+        let rejected = rejected.into_inner();
+        if !rejected.is_empty() {
+            let err_msg = format!("failed to push some refs: {}", rejected.join(", "));
+            return Err(Error::from_str(&err_msg));
+        }
+        Ok(())
+    }
+
+    /// Runs the `pre-push` hook once the updates are negotiated with `remote`, before anything
+    /// is uploaded, if hooks are enabled. Each line of its input is
+    /// `<local ref> <local oid> <remote ref> <remote oid>`, with a local ref of `(delete)` for
+    /// deletions. The push is cancelled if the hook fails.
+    pub(crate) fn pre_push<'a>(
+        &self,
+        repository: &'a Repository,
+        remote: &Remote,
+        callbacks: &mut RemoteCallbacks<'a>,
+    ) {
+        if !self.run_hooks {
+            return;
+        }
+        let url = match remote.pushurl().or(remote.url()) {
+            Some(url) => url.to_string(),
+            None => String::new(),
+        };
+        let name = match remote.name() {
+            Some(name) => name.to_string(),
+            None => url.clone(),
+        };
+
+        callbacks.push_negotiation(move |updates| {
+            // <local ref> SP <local sha1> SP <remote ref> SP <remote sha1> LF
+            let mut input = String::new();
+            for update in updates {
+                let src = match update.src_refname() {
+                    Some("") | None => "(delete)",
+                    Some(src) => src,
+                };
+                let dst = update.dst_refname().unwrap_or_default();
+                input += &format!("{} {} {} {}\n", src, update.dst(), dst, update.src());
+            }
+            hooks::run_hook(
+                repository,
+                "pre-push",
+                &[&name, &url],
+                Some(input.as_bytes()),
+                &[],
+            )
+        });
+    }
+}
@@ -1,7 +1,8 @@
 use std::fmt::Display;
 
-use crate::GitRepository;
-use git2::Error;
+use crate::{FetchConfig, GitRepository, RemoteRef};
+use git2::{BranchType, Config, Direction, Error, Remote, Repository};
+use regex::Regex;
 
 /// A struct used to specify various details about the `git remote` command.
 pub struct RemoteConfig {
@@ -54,12 +55,43 @@ impl RemoteConfig {
                     RemoteFlags::Delete(delete) => {
                         self.flags.delete = delete;
                     }
+                    RemoteFlags::Auto(auto) => self.flags.auto = auto,
                     _ => return Err(error),
                 },
-                RemoteSubCommand::Remove(_) => return Err(error),
+                RemoteSubCommand::SetBranches(_, _) => match flag {
+                    RemoteFlags::Add(add) => self.flags.add = add,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::Prune(_) => match flag {
+                    RemoteFlags::DryRun(dry_run) => self.flags.dry_run = dry_run,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::Update(_) => match flag {
+                    RemoteFlags::Prune(prune) => self.flags.prune = prune,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::SetUrl(_, _, _) => match flag {
+                    RemoteFlags::Push(push) => self.flags.push = push,
+                    RemoteFlags::Add(add) => self.flags.add = add,
+                    RemoteFlags::Delete(delete) => self.flags.delete = delete,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::GetUrl(_) => match flag {
+                    RemoteFlags::Push(push) => self.flags.push = push,
+                    RemoteFlags::All(all) => self.flags.all = all,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::Show(_) => match flag {
+                    RemoteFlags::NoQuery(no_query) => self.flags.no_query = no_query,
+                    _ => return Err(error),
+                },
+                RemoteSubCommand::Remove(_) | RemoteSubCommand::Rename(_, _) => {
+                    return Err(error);
+                }
             };
         } else {
             match flag {
+                RemoteFlags::Verbose(verbose) => self.flags.verbose = verbose,
                 _ => return Err(error),
             };
         }
@@ -80,15 +112,58 @@ pub enum RemoteSubCommand {
 
     /// Set the `set-head` subcommand to the RemoteConfig
     /// Takes two inputs of remote name and an optional branch in this order.
-    /// The optional branch field can only be empty only if delete flag is set, else it will throw
-    /// an error.
+    /// The optional branch field can only be empty only if the delete or the auto flag is set,
+    /// else it will throw an error.
     SetHead(String, Option<String>),
+
+    /// Set the `set-branches` subcommand to the RemoteConfig.
+    /// Takes the remote name and the branches to be tracked in this order. The fetch refspecs of
+    /// the remote are replaced, or appended to with the add flag.
+    SetBranches(String, Vec<String>),
+
+    /// Set the `prune` subcommand to the RemoteConfig.
+    /// Takes the names of the remotes whose stale remote-tracking refs are to be deleted.
+    Prune(Vec<String>),
+
+    /// Set the `update` subcommand to the RemoteConfig.
+    /// Takes the names of the remotes or remote groups (`remotes.<group>`) to be fetched, pass an
+    /// empty vector to fetch the `default` group, or all the remotes without
+    /// `remote.<name>.skipDefaultUpdate` if that group is not configured.
+    Update(Vec<String>),
+
+    /// Set the `rename` subcommand to the RemoteConfig.
+    /// Takes the old and the new name of the remote in this order. The remote-tracking refs and
+    /// the `branch.<name>.remote` settings of the remote are renamed as well.
+    Rename(String, String),
+
+    /// Set the `get-url` subcommand to the RemoteConfig.
+    /// Takes the name of the remote as the input.
+    GetUrl(String),
+
+    /// Set the `set-url` subcommand to the RemoteConfig.
+    /// Takes the remote name, the new url and an optional regex matching the old urls to be
+    /// replaced in this order.
+    /// With the add flag the new url is added instead, with the delete flag the second field is
+    /// the regex of the urls to be deleted. The old url must be empty with either of them.
+    SetUrl(String, String, Option<String>),
+
+    /// Set the `show` subcommand to the RemoteConfig.
+    /// Takes the name of the remote as the input.
+    Show(String),
 }
 
 #[derive(Default)]
 pub(crate) struct RemoteFlagsInternal {
     track: Option<Vec<String>>,
     delete: bool,
+    verbose: bool,
+    push: bool,
+    all: bool,
+    add: bool,
+    no_query: bool,
+    auto: bool,
+    dry_run: bool,
+    prune: bool,
 }
 
 #[derive(Clone, Debug)]
@@ -98,10 +173,50 @@ pub enum RemoteFlags {
     /// Passing an empty vector unsets the flag.
     Track(Vec<String>),
 
-    /// `-d` or `--delete` flag for `git remote set-head`.
+    /// `-d` or `--delete` flag for `git remote set-head` and `git remote set-url`.
     /// Pass in true to set the flag and false to unset it.
     /// Defaults to false.
     Delete(bool),
+
+    /// `-v` or `--verbose` flag for `git remote`.
+    /// `true` fills in the fetch and push urls of the listed remotes.
+    /// Defaults to false.
+    Verbose(bool),
+
+    /// `--push` flag for `git remote get-url` and `git remote set-url`.
+    /// `true` works on the push urls instead of the fetch urls.
+    /// Defaults to false.
+    Push(bool),
+
+    /// `--all` flag for `git remote get-url`.
+    /// `true` returns all the urls instead of only the first one.
+    /// Defaults to false.
+    All(bool),
+
+    /// `--add` flag for `git remote set-url` and `git remote set-branches`.
+    /// `true` adds the new url or branches instead of replacing the existing ones.
+    /// Defaults to false.
+    Add(bool),
+
+    /// `-n` flag for `git remote show`.
+    /// `true` does not contact the remote, only the local remote-tracking refs and config are
+    /// used. Defaults to false.
+    NoQuery(bool),
+
+    /// `-a` or `--auto` flag for `git remote set-head`.
+    /// `true` asks the remote for its default branch and points the remote HEAD to it.
+    /// Defaults to false.
+    Auto(bool),
+
+    /// `-n` or `--dry-run` flag for `git remote prune`.
+    /// `true` only reports the stale remote-tracking refs without deleting them.
+    /// Defaults to false.
+    DryRun(bool),
+
+    /// `-p` or `--prune` flag for `git remote update`.
+    /// `true` prunes the stale remote-tracking refs of every fetched remote.
+    /// Defaults to false.
+    Prune(bool),
 }
 
 impl Display for RemoteFlags {
@@ -109,12 +224,156 @@ impl Display for RemoteFlags {
         match self {
             RemoteFlags::Track(items) => write!(f, "--track {:?}", items),
             RemoteFlags::Delete(delete) => write!(f, "--delete {}", delete),
+            RemoteFlags::Verbose(verbose) => write!(f, "--verbose {}", verbose),
+            RemoteFlags::Push(push) => write!(f, "--push {}", push),
+            RemoteFlags::All(all) => write!(f, "--all {}", all),
+            RemoteFlags::Add(add) => write!(f, "--add {}", add),
+            RemoteFlags::NoQuery(no_query) => write!(f, "-n {}", no_query),
+            RemoteFlags::Auto(auto) => write!(f, "--auto {}", auto),
+            RemoteFlags::DryRun(dry_run) => write!(f, "--dry-run {}", dry_run),
+            RemoteFlags::Prune(prune) => write!(f, "--prune {}", prune),
         }
     }
 }
 
+/// The result of `git_remote`, depending on the subcommand that was run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteOutput {
+    /// Returned by the subcommands which only modify the repository.
+    Done,
+
+    /// The configured remotes sorted by name, returned when no subcommand is set.
+    List(Vec<RemoteInfo>),
+
+    /// The urls returned by `get-url`.
+    Urls(Vec<String>),
+
+    /// The details returned by `show`.
+    Show(RemoteShow),
+
+    /// The stale remote-tracking refs deleted by `prune` and `update`, or the ones that would be
+    /// deleted with the dry run flag.
+    Pruned(Vec<String>),
+}
+
+/// A remote as listed by `git remote`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteInfo {
+    /// Name of the remote.
+    pub name: String,
+
+    /// Url the remote is fetched from, only filled with `RemoteFlags::Verbose`.
+    pub fetch_url: Option<String>,
+
+    /// Urls the remote is pushed to, only filled with `RemoteFlags::Verbose`.
+    pub push_urls: Vec<String>,
+}
+
+/// The details of a remote as returned by `git remote show`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteShow {
+    /// Name of the remote.
+    pub name: String,
+
+    /// Url the remote is fetched from.
+    pub fetch_url: Option<String>,
+
+    /// Urls the remote is pushed to.
+    pub push_urls: Vec<String>,
+
+    /// Default branch of the remote, `None` if it is not known or the remote was not queried.
+    pub head_branch: Option<String>,
+
+    /// Branches of the remote sorted by name.
+    pub branches: Vec<RemoteBranchInfo>,
+
+    /// Local branches configured for `git pull` from this remote.
+    pub pull: Vec<RemotePullInfo>,
+
+    /// Local branches configured for `git push` to this remote.
+    pub push: Vec<RemotePushInfo>,
+}
+
+/// A branch of a remote as returned by `git remote show`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemoteBranchInfo {
+    /// Name of the branch on the remote, e.g. "main".
+    pub name: String,
+
+    pub state: RemoteBranchState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemoteBranchState {
+    /// The branch is fetched into a remote-tracking ref.
+    Tracked,
+
+    /// The branch is not fetched yet, the next fetch will create its remote-tracking ref.
+    New,
+
+    /// The remote-tracking ref exists but the branch was removed from the remote.
+    Stale,
+
+    /// The branch is not matched by any fetch refspec of the remote.
+    Ignored,
+
+    /// The remote was not queried, the branch is known from its remote-tracking ref.
+    NotQueried,
+}
+
+/// A local branch which merges with (or rebases onto) a branch of the remote on `git pull`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemotePullInfo {
+    /// Name of the local branch.
+    pub local: String,
+
+    /// Name of the branch on the remote.
+    pub remote: String,
+
+    /// `true` if the branch rebases instead of merging.
+    pub rebase: bool,
+}
+
+/// A local branch which is pushed to a branch of the remote on `git push`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RemotePushInfo {
+    /// Name of the local branch.
+    pub local: String,
+
+    /// Name of the branch on the remote.
+    pub remote: String,
+
+    /// `true` if the push is forced.
+    pub forced: bool,
+
+    pub state: RemotePushState,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RemotePushState {
+    /// The branch does not exist on the remote yet.
+    Create,
+
+    /// The branch on the remote points to the same commit.
+    UpToDate,
+
+    /// The branch on the remote can be fast-forwarded to the local branch.
+    FastForwardable,
+
+    /// The branch on the remote has commits that are not in the local branch.
+    LocalOutOfDate,
+
+    /// The remote was not queried.
+    NotQueried,
+}
+
 impl GitRepository {
-    pub fn git_remote(&self, config: RemoteConfig) -> Result<(), Error> {
+    /// Runs `git remote` with the given config.
+    ///
+    /// Returns `RemoteOutput::List` when no subcommand is set, `RemoteOutput::Urls` for `GetUrl`,
+    /// `RemoteOutput::Show` for `Show`, `RemoteOutput::Pruned` for `Prune` and `Update`, and
+    /// `RemoteOutput::Done` for the other subcommands.
+    pub fn git_remote(&self, config: RemoteConfig) -> Result<RemoteOutput, Error> {
         if let Some(repository) = &self.repository {
             if let Some(subcommand) = &config.subcommand {
                 match subcommand {
@@ -142,18 +401,8 @@ impl GitRepository {
                     }
                     RemoteSubCommand::SetHead(remote, branch) => {
                         // git remote set-head
-                        if !config.flags.delete && branch.is_some() {
-                            let name = format!("refs/remote/{}/HEAD", remote);
-                            let branch = branch.clone().unwrap();
-                            let target = format!("refs/remote/{}/{}", remote, branch);
-                            repository.reference_symbolic(
-                                &name,
-                                &target,
-                                true,
-                                "set remote HEAD",
-                            )?;
-                        } else if config.flags.delete {
-                            let name = format!("refs/remote/{}/HEAD", remote);
+                        let name = format!("refs/remotes/{}/HEAD", remote);
+                        if config.flags.delete {
                             match repository.find_reference(&name) {
                                 Ok(mut reference) => {
                                     reference.delete()?;
@@ -161,15 +410,127 @@ impl GitRepository {
                                 Err(ref e) if e.code() == git2::ErrorCode::NotFound => {}
                                 Err(e) => return Err(e),
                             };
+                            return Ok(RemoteOutput::Done);
+                        }
+
+                        let branch = match branch {
+                            Some(branch) => branch.clone(),
+                            // --auto
+                            None if config.flags.auto => {
+                                let mut git_remote = repository.find_remote(remote)?;
+                                let heads = self.remote_heads(&mut git_remote)?;
+                                match head_branch(&heads) {
+                                    Some(branch) => branch,
+                                    None => {
+                                        return Err(Error::from_str(
+                                            "Cannot determine remote HEAD",
+                                        ));
+                                    }
+                                }
+                            }
+                            None => {
+                                return Err(Error::from_str(
+                                    "a branch is required unless the auto or delete flag is set",
+                                ));
+                            }
+                        };
+                        let target = format!("refs/remotes/{}/{}", remote, branch);
+                        if repository.find_reference(&target).is_err() {
+                            let err_msg = format!("Not a valid ref: {}", target);
+                            return Err(Error::from_str(&err_msg));
                         }
+                        repository.reference_symbolic(&name, &target, true, "set remote HEAD")?;
+                    }
+                    RemoteSubCommand::SetBranches(name, branches) => {
+                        // git remote set-branches
+                        repository.find_remote(name)?;
+                        if !config.flags.add {
+                            let mut git_config = repository.config()?;
+                            let key = format!("remote.{}.fetch", name);
+                            match git_config.remove_multivar(&key, ".*") {
+                                Ok(()) => {}
+                                Err(ref e) if e.code() == git2::ErrorCode::NotFound => {}
+                                Err(e) => return Err(e),
+                            };
+                        }
+                        for branch in branches {
+                            let spec =
+                                format!("+refs/heads/{}:refs/remotes/{}/{}", branch, name, branch);
+                            repository.remote_add_fetch(name, &spec)?;
+                        }
+                    }
+                    RemoteSubCommand::Prune(names) => {
+                        // git remote prune
+                        let mut pruned = vec![];
+                        for name in names {
+                            let dry_run = config.flags.dry_run;
+                            pruned.extend(self.prune_remote(repository, name, dry_run)?);
+                        }
+                        return Ok(RemoteOutput::Pruned(pruned));
+                    }
+                    RemoteSubCommand::Update(groups) => {
+                        // git remote update
+                        let mut pruned = vec![];
+                        for name in update_remotes(repository, groups)? {
+                            self.git_fetch(FetchConfig::new(Some(name.clone())))?;
+                            // -p
+                            if config.flags.prune {
+                                pruned.extend(self.prune_remote(repository, &name, false)?);
+                            }
+                        }
+                        return Ok(RemoteOutput::Pruned(pruned));
+                    }
+                    RemoteSubCommand::Rename(old, new) => {
+                        // git remote rename
+                        repository.remote_rename(old, new)?;
+                        let mut git_config = repository.config()?;
+                        if git_config
+                            .get_string("remote.pushDefault")
+                            .is_ok_and(|push_default| push_default == *old)
+                        {
+                            git_config.set_str("remote.pushDefault", new)?;
+                        }
+                    }
+                    RemoteSubCommand::GetUrl(name) => {
+                        // git remote get-url
+                        repository.find_remote(name)?;
+                        let mut urls = remote_urls(repository, name, config.flags.push)?;
+                        if !config.flags.all {
+                            urls.truncate(1);
+                        }
+                        return Ok(RemoteOutput::Urls(urls));
+                    }
+                    RemoteSubCommand::SetUrl(name, url, old_url) => {
+                        // git remote set-url
+                        repository.find_remote(name)?;
+                        set_url(repository, &config.flags, name, url, old_url.as_deref())?;
+                    }
+                    RemoteSubCommand::Show(name) => {
+                        // git remote show
+                        let show = self.show_remote(repository, name, config.flags.no_query)?;
+                        return Ok(RemoteOutput::Show(show));
                     }
                 }
             } else {
                 // git remote
-                todo!();
+                let mut remotes = vec![];
+                for name in repository.remotes()?.iter().flatten() {
+                    let mut info = RemoteInfo {
+                        name: name.to_string(),
+                        fetch_url: None,
+                        push_urls: vec![],
+                    };
+                    // -v
+                    if config.flags.verbose {
+                        info.fetch_url = remote_urls(repository, name, false)?.into_iter().next();
+                        info.push_urls = remote_urls(repository, name, true)?;
+                    }
+                    remotes.push(info);
+                }
+                return Ok(RemoteOutput::List(remotes));
             }
 
-            return Ok(());
+            return Ok(RemoteOutput::Done);
         }
 
         Err(Error::from_str(
@@ -178,13 +539,372 @@ impl GitRepository {
     }
 }
 
+impl GitRepository {
+    fn show_remote(
+        &self,
+        repository: &Repository,
+        name: &str,
+        no_query: bool,
+    ) -> Result<RemoteShow, Error> {
+        let mut remote = repository.find_remote(name)?;
+        let heads = match no_query {
+            true => None,
+            false => Some(self.remote_heads(&mut remote)?),
+        };
+        let head_branch = heads.as_deref().and_then(head_branch);
+        let remote_oid = |name: &str| match &heads {
+            Some(heads) => heads
+                .iter()
+                .find(|head| head.name == name)
+                .map(|head| head.oid),
+            None => None,
+        };
+        let fetch_refspecs: Vec<_> = remote
+            .refspecs()
+            .filter(|refspec| refspec.direction() == Direction::Fetch)
+            .collect();
+        let push_refspecs: Vec<_> = remote
+            .refspecs()
+            .filter(|refspec| refspec.direction() == Direction::Push)
+            .collect();
+
+        // remote branches
+        let mut branches = vec![];
+        if let Some(heads) = &heads {
+            for RemoteRef { name: head, .. } in heads {
+                let Some(branch) = head.strip_prefix("refs/heads/") else {
+                    continue;
+                };
+                let tracking = fetch_refspecs
+                    .iter()
+                    .filter(|refspec| refspec.src_matches(head))
+                    .find_map(|refspec| refspec.transform(head).ok());
+                let state = match tracking.as_ref().and_then(|tracking| tracking.as_str()) {
+                    Some(tracking) if repository.find_reference(tracking).is_ok() => {
+                        RemoteBranchState::Tracked
+                    }
+                    Some(_) => RemoteBranchState::New,
+                    None => RemoteBranchState::Ignored,
+                };
+                branches.push(RemoteBranchInfo {
+                    name: branch.to_string(),
+                    state,
+                });
+            }
+        }
+        for (_, head) in tracking_refs(repository, &remote)? {
+            let Some(branch) = head.strip_prefix("refs/heads/") else {
+                continue;
+            };
+            let state = match &heads {
+                Some(_) if remote_oid(&head).is_some() => continue,
+                Some(_) => RemoteBranchState::Stale,
+                None => RemoteBranchState::NotQueried,
+            };
+            branches.push(RemoteBranchInfo {
+                name: branch.to_string(),
+                state,
+            });
+        }
+        branches.sort_by(|a, b| a.name.cmp(&b.name));
+
+        // local branches configured for pull and push
+        let git_config = repository.config()?;
+        let mut pull = vec![];
+        let mut push = vec![];
+        for branch in repository.branches(Some(BranchType::Local))? {
+            let (branch, _) = branch?;
+            let Some(local) = branch.name()? else {
+                continue;
+            };
+            let full = format!("refs/heads/{}", local);
+
+            // pull
+            let branch_remote = git_config.get_string(&format!("branch.{}.remote", local));
+            let merge = git_config.get_string(&format!("branch.{}.merge", local));
+            if branch_remote.is_ok_and(|branch_remote| branch_remote == name)
+                && let Ok(merge) = merge
+            {
+                let rebase = git_config
+                    .get_string(&format!("branch.{}.rebase", local))
+                    .is_ok_and(|rebase| !matches!(rebase.as_str(), "false" | "no" | "off" | "0"));
+                pull.push(RemotePullInfo {
+                    local: local.to_string(),
+                    remote: merge.trim_start_matches("refs/heads/").to_string(),
+                    rebase,
+                });
+            }
+
+            // push, the matching branches unless push refspecs are configured
+            let destination = if push_refspecs.is_empty() {
+                let exists = match &heads {
+                    Some(_) => remote_oid(&full).is_some(),
+                    None => fetch_refspecs
+                        .iter()
+                        .filter(|refspec| refspec.src_matches(&full))
+                        .filter_map(|refspec| refspec.transform(&full).ok())
+                        .any(|tracking| {
+                            tracking
+                                .as_str()
+                                .is_some_and(|tracking| repository.find_reference(tracking).is_ok())
+                        }),
+                };
+                if exists {
+                    Some((full.clone(), false))
+                } else {
+                    None
+                }
+            } else {
+                push_refspecs.iter().find_map(|refspec| {
+                    let matches = refspec.src_matches(&full) || refspec.src() == Some(local);
+                    if !matches {
+                        return None;
+                    }
+                    let wildcard = refspec.src().is_some_and(|src| src.contains('*'));
+                    let destination = match wildcard {
+                        true => refspec.transform(&full).ok()?.as_str()?.to_string(),
+                        false => refspec.dst().unwrap_or(local).to_string(),
+                    };
+                    let destination = match destination.starts_with("refs/") {
+                        true => destination,
+                        false => format!("refs/heads/{}", destination),
+                    };
+                    Some((destination, refspec.is_force()))
+                })
+            };
+            let Some((destination, forced)) = destination else {
+                continue;
+            };
+            let state = match &heads {
+                None => RemotePushState::NotQueried,
+                Some(_) => match (remote_oid(&destination), branch.get().target()) {
+                    (None, _) => RemotePushState::Create,
+                    (Some(remote), Some(local)) if remote == local => RemotePushState::UpToDate,
+                    (Some(remote), Some(local))
+                        if repository.find_commit(remote).is_ok()
+                            && repository.graph_descendant_of(local, remote)? =>
+                    {
+                        RemotePushState::FastForwardable
+                    }
+                    _ => RemotePushState::LocalOutOfDate,
+                },
+            };
+            push.push(RemotePushInfo {
+                local: local.to_string(),
+                remote: destination.trim_start_matches("refs/heads/").to_string(),
+                forced,
+                state,
+            });
+        }
+
+        Ok(RemoteShow {
+            name: name.to_string(),
+            fetch_url: remote_urls(repository, name, false)?.into_iter().next(),
+            push_urls: remote_urls(repository, name, true)?,
+            head_branch,
+            branches,
+            pull,
+            push,
+        })
+    }
+}
+
+impl GitRepository {
+    /// Deletes the remote-tracking refs of `name` whose branches were removed from the remote,
+    /// returns the deleted refs.
+    fn prune_remote(
+        &self,
+        repository: &Repository,
+        name: &str,
+        dry_run: bool,
+    ) -> Result<Vec<String>, Error> {
+        let mut remote = repository.find_remote(name)?;
+        let heads = self.remote_heads(&mut remote)?;
+        let mut pruned = vec![];
+        for (tracking, head) in tracking_refs(repository, &remote)? {
+            if heads.iter().any(|remote_head| remote_head.name == head) {
+                continue;
+            }
+            if !dry_run {
+                repository.find_reference(&tracking)?.delete()?;
+            }
+            pruned.push(tracking);
+        }
+        Ok(pruned)
+    }
+}
+
+/// Returns the branch the `HEAD` of a remote points to out of its advertised references.
+fn head_branch(heads: &[RemoteRef]) -> Option<String> {
+    heads
+        .iter()
+        .find(|head| head.name == "HEAD")
+        .and_then(|head| head.symref_target.as_deref())
+        .map(|branch| branch.trim_start_matches("refs/heads/").to_string())
+}
+
+/// Returns the remote-tracking refs of `remote` as (remote-tracking ref, ref on the remote),
+/// symbolic refs like `refs/remotes/<name>/HEAD` are skipped.
+fn tracking_refs(repository: &Repository, remote: &Remote) -> Result<Vec<(String, String)>, Error> {
+    let mut refs = vec![];
+    for reference in repository.references()?.flatten() {
+        let Some(tracking) = reference.name() else {
+            continue;
+        };
+        if reference.symbolic_target().is_some() {
+            continue;
+        }
+        let head = remote
+            .refspecs()
+            .filter(|refspec| refspec.direction() == Direction::Fetch)
+            .filter(|refspec| refspec.dst_matches(tracking))
+            .find_map(|refspec| refspec.rtransform(tracking).ok());
+        if let Some(head) = head.as_ref().and_then(|head| head.as_str()) {
+            refs.push((tracking.to_string(), head.to_string()));
+        }
+    }
+    Ok(refs)
+}
+
+/// Resolves the remotes and remote groups passed to `git remote update` to remote names.
+fn update_remotes(repository: &Repository, groups: &[String]) -> Result<Vec<String>, Error> {
+    let config = repository.config()?;
+    let default = ["default".to_string()];
+    let groups = match groups.is_empty() {
+        true => &default[..],
+        false => groups,
+    };
+    let mut names: Vec<String> = vec![];
+    for group in groups {
+        let members = config_values(&config, &format!("remotes.{}", group))?;
+        let members: Vec<String> = if !members.is_empty() {
+            members
+                .iter()
+                .flat_map(|members| members.split_whitespace())
+                .map(|member| member.to_string())
+                .collect()
+        } else if group == "default" {
+            let mut members = vec![];
+            for name in repository.remotes()?.iter().flatten() {
+                let key = format!("remote.{}.skipDefaultUpdate", name);
+                if !config.get_bool(&key).unwrap_or(false) {
+                    members.push(name.to_string());
+                }
+            }
+            members
+        } else if repository.find_remote(group).is_ok() {
+            vec![group.clone()]
+        } else {
+            let err_msg = format!("No such remote or remote group: {}", group);
+            return Err(Error::from_str(&err_msg));
+        };
+        for member in members {
+            if !names.contains(&member) {
+                names.push(member);
+            }
+        }
+    }
+    Ok(names)
+}
+
+/// Returns all the values of a multivar config key, in the order they are set.
+fn config_values(config: &Config, key: &str) -> Result<Vec<String>, Error> {
+    let mut values = vec![];
+    let mut entries = match config.multivar(key, None) {
+        Ok(entries) => entries,
+        Err(ref e) if e.code() == git2::ErrorCode::NotFound => return Ok(values),
+        Err(e) => return Err(e),
+    };
+    while let Some(entry) = entries.next() {
+        if let Some(value) = entry?.value() {
+            values.push(value.to_string());
+        }
+    }
+    Ok(values)
+}
+
+/// Returns the fetch urls of a remote, or its push urls (falling back to the fetch urls when
+/// none are set) if `push` is true.
+fn remote_urls(repository: &Repository, name: &str, push: bool) -> Result<Vec<String>, Error> {
+    let config = repository.config()?;
+    let urls = config_values(&config, &format!("remote.{}.url", name))?;
+    if push {
+        let push_urls = config_values(&config, &format!("remote.{}.pushurl", name))?;
+        if !push_urls.is_empty() {
+            return Ok(push_urls);
+        }
+    }
+    Ok(urls)
+}
+
+/// Sets, adds or deletes the urls of a remote the same way `git remote set-url` does.
+fn set_url(
+    repository: &Repository,
+    flags: &RemoteFlagsInternal,
+    name: &str,
+    url: &str,
+    old_url: Option<&str>,
+) -> Result<(), Error> {
+    if (flags.add || flags.delete) && old_url.is_some() {
+        return Err(Error::from_str(
+            "an old url can not be given with the add or delete flags",
+        ));
+    }
+    let mut config = repository.config()?;
+    let key = match flags.push {
+        true => format!("remote.{}.pushurl", name),
+        false => format!("remote.{}.url", name),
+    };
+
+    // --add
+    if flags.add {
+        return config.set_multivar(&key, "^$", url);
+    }
+
+    // without an old url the only url is replaced
+    let current = config_values(&config, &key)?;
+    let Some(pattern) = old_url.or(flags.delete.then_some(url)) else {
+        if current.len() > 1 {
+            let err_msg = format!("{} has multiple values, pass the old url to replace", key);
+            return Err(Error::from_str(&err_msg));
+        }
+        return config.set_str(&key, url);
+    };
+
+    let regex = match Regex::new(pattern) {
+        Ok(regex) => regex,
+        Err(_) => {
+            let err_msg = format!("Invalid old URL pattern: {}", pattern);
+            return Err(Error::from_str(&err_msg));
+        }
+    };
+    let matches = current.iter().filter(|value| regex.is_match(value)).count();
+    if matches == 0 {
+        let err_msg = format!("No such URL found: {}", pattern);
+        return Err(Error::from_str(&err_msg));
+    }
+
+    // --delete
+    if flags.delete {
+        if !flags.push && matches == current.len() {
+            return Err(Error::from_str("Will not delete all non-push URLs"));
+        }
+        return config.remove_multivar(&key, pattern);
+    }
+    config.set_multivar(&key, pattern, url)
+}
+
 #[cfg(test)]
 mod remote_test {
     use std::{env, path::Path, process::Command};
 
     use crate::{
         GitRepository,
-        configs::remote_config::{RemoteConfig, RemoteFlags, RemoteSubCommand},
+        configs::remote_config::{
+            RemoteBranchState, RemoteConfig, RemoteFlags, RemoteOutput, RemotePushState,
+            RemoteSubCommand,
+        },
+        helpers::test_util::git_output,
     };
 
     #[test]
@@ -237,4 +957,464 @@ mod remote_test {
 "
         );
     }
+
+    #[test]
+    fn git_remote_list_rename_urls_test() {
+        let dir_name = "./temp_test/remote_list_rename_urls/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with two remotes and a branch tracking one of them
+        let path = env::current_dir().unwrap().join(dir_name);
+        git_output(&path, &["init", "--initial-branch", "main"]);
+        git_output(
+            &path,
+            &[
+                "remote",
+                "add",
+                "upstream",
+                "https://example.com/upstream.git",
+            ],
+        );
+        git_output(
+            &path,
+            &["remote", "add", "origin", "https://example.com/origin.git"],
+        );
+        git_output(&path, &["config", "user.name", "Remote"]);
+        git_output(&path, &["config", "user.email", "remote@example.com"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "first"]);
+        git_output(&path, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git_output(
+            &path,
+            &[
+                "symbolic-ref",
+                "refs/remotes/origin/HEAD",
+                "refs/remotes/origin/main",
+            ],
+        );
+        git_output(&path, &["branch", "--set-upstream-to", "origin/main"]);
+        git_output(&path, &["config", "remote.pushDefault", "origin"]);
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let run = |subcommand: Option<RemoteSubCommand>, flags: Vec<RemoteFlags>| {
+            let mut config = RemoteConfig::new(subcommand);
+            for flag in flags {
+                config.add_flag(flag).unwrap();
+            }
+            repo.git_remote(config)
+        };
+        let get_url = |flags: Vec<RemoteFlags>| match run(
+            Some(RemoteSubCommand::GetUrl("origin".into())),
+            flags,
+        ) {
+            Ok(RemoteOutput::Urls(urls)) => urls,
+            _ => panic!("get-url did not return urls"),
+        };
+        let set_url = |url: &str, old: Option<&str>, flags: Vec<RemoteFlags>| {
+            let old = old.map(String::from);
+            let subcommand = RemoteSubCommand::SetUrl("origin".into(), url.into(), old);
+            run(Some(subcommand), flags)
+        };
+
+        // listing
+        let names = run(None, vec![]).unwrap();
+        let verbose = run(None, vec![RemoteFlags::Verbose(true)]).unwrap();
+
+        // set-url and get-url
+        set_url("https://example.com/moved.git", None, vec![]).unwrap();
+        set_url(
+            "https://example.com/mirror.git",
+            None,
+            vec![RemoteFlags::Add(true)],
+        )
+        .unwrap();
+        let multiple = set_url("https://example.com/other.git", None, vec![]);
+        set_url(
+            "https://example.com/push.git",
+            None,
+            vec![RemoteFlags::Push(true)],
+        )
+        .unwrap();
+        set_url("ssh://example.com/mirror.git", Some("mirror"), vec![]).unwrap();
+        let no_match = set_url("https://example.com/x.git", Some("missing"), vec![]);
+        let first = get_url(vec![]);
+        let all = get_url(vec![RemoteFlags::All(true)]);
+        let push = get_url(vec![RemoteFlags::Push(true)]);
+        let git_all = git_output(&path, &["remote", "get-url", "--all", "origin"]);
+        set_url("moved", None, vec![RemoteFlags::Delete(true)]).unwrap();
+        let delete_all = set_url("example", None, vec![RemoteFlags::Delete(true)]);
+        let after_delete = get_url(vec![RemoteFlags::All(true)]);
+        let invalid_flag = RemoteConfig::new(Some(RemoteSubCommand::GetUrl("origin".into())))
+            .add_flag(RemoteFlags::Add(true));
+
+        // rename
+        let rename = RemoteSubCommand::Rename("origin".into(), "fork".into());
+        run(Some(rename), vec![]).unwrap();
+        let refs = git_output(
+            &path,
+            &[
+                "for-each-ref",
+                "--format=%(refname) %(symref)",
+                "refs/remotes",
+            ],
+        );
+        let branch_remote = git_output(&path, &["config", "branch.main.remote"]);
+        let push_default = git_output(&path, &["config", "remote.pushDefault"]);
+        let remotes = git_output(&path, &["remote"]);
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        let RemoteOutput::List(names) = names else {
+            panic!("git remote did not return a list");
+        };
+        let RemoteOutput::List(verbose) = verbose else {
+            panic!("git remote -v did not return a list");
+        };
+        assert_eq!(names[0].name, "origin");
+        assert_eq!(names[1].name, "upstream");
+        assert_eq!(names[0].fetch_url, None);
+        assert_eq!(
+            verbose[1].fetch_url.as_deref(),
+            Some("https://example.com/upstream.git")
+        );
+        assert_eq!(
+            verbose[1].push_urls,
+            vec!["https://example.com/upstream.git"]
+        );
+
+        assert!(multiple.is_err());
+        assert_eq!(
+            no_match.unwrap_err().message(),
+            "No such URL found: missing"
+        );
+        assert_eq!(first, vec!["https://example.com/moved.git"]);
+        assert_eq!(
+            all,
+            vec![
+                "https://example.com/moved.git",
+                "ssh://example.com/mirror.git"
+            ]
+        );
+        assert_eq!(push, vec!["https://example.com/push.git"]);
+        assert_eq!(
+            String::from_utf8_lossy(&git_all.stdout),
+            all.join("\n") + "\n"
+        );
+        assert_eq!(
+            delete_all.unwrap_err().message(),
+            "Will not delete all non-push URLs"
+        );
+        assert_eq!(after_delete, vec!["ssh://example.com/mirror.git"]);
+        assert!(invalid_flag.is_err());
+
+        assert_eq!(
+            String::from_utf8_lossy(&refs.stdout),
+            "refs/remotes/fork/HEAD refs/remotes/fork/main\nrefs/remotes/fork/main \n"
+        );
+        assert_eq!(String::from_utf8_lossy(&branch_remote.stdout), "fork\n");
+        assert_eq!(String::from_utf8_lossy(&push_default.stdout), "fork\n");
+        assert_eq!(String::from_utf8_lossy(&remotes.stdout), "fork\nupstream\n");
+    }
+
+    #[test]
+    fn git_remote_show_test() {
+        let dir_name = "./temp_test/remote_show/";
+        let remote_name = "./temp_test/remote_show_remote.git";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a bare remote with three branches, fetch two of them and remove one of those
+        let path = env::current_dir().unwrap().join(dir_name);
+        let remote = env::current_dir().unwrap().join(remote_name);
+        git_output(&path, &["init", "--initial-branch", "main"]);
+        git_output(
+            &path,
+            &[
+                "init",
+                "--bare",
+                "--initial-branch",
+                "main",
+                remote.to_str().unwrap(),
+            ],
+        );
+        git_output(
+            &path,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        git_output(&path, &["config", "user.name", "Remote"]);
+        git_output(&path, &["config", "user.email", "remote@example.com"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "first"]);
+        git_output(
+            &path,
+            &["push", "origin", "main", "main:gone", "main:later"],
+        );
+        git_output(&path, &["update-ref", "-d", "refs/remotes/origin/later"]);
+        let remote_git = ["--git-dir", remote.to_str().unwrap()];
+        git_output(
+            &path,
+            &[&remote_git[..], &["update-ref", "-d", "refs/heads/gone"]].concat(),
+        );
+        git_output(&path, &["branch", "--set-upstream-to", "origin/main"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "second"]);
+        git_output(&path, &["branch", "later"]);
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let show = |flags: Vec<RemoteFlags>| {
+            let mut config = RemoteConfig::new(Some(RemoteSubCommand::Show("origin".into())));
+            for flag in flags {
+                config.add_flag(flag).unwrap();
+            }
+            match repo.git_remote(config) {
+                Ok(RemoteOutput::Show(show)) => show,
+                _ => panic!("show did not return the remote details"),
+            }
+        };
+        let queried = show(vec![]);
+        let not_queried = show(vec![RemoteFlags::NoQuery(true)]);
+        let git_show = git_output(&path, &["remote", "show", "origin"]);
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+        Command::new("rm")
+            .args(["-rf", remote_name])
+            .output()
+            .unwrap();
+
+        // collapse the column alignment of the cli output
+        let git_show = String::from_utf8_lossy(&git_show.stdout);
+        let git_show = git_show.split_whitespace().collect::<Vec<&str>>().join(" ");
+        assert_eq!(
+            queried.fetch_url,
+            Some(remote.to_str().unwrap().to_string())
+        );
+        assert_eq!(queried.head_branch.as_deref(), Some("main"));
+        assert!(git_show.contains("HEAD branch: main"));
+
+        let branches: Vec<(&str, RemoteBranchState)> = queried
+            .branches
+            .iter()
+            .map(|branch| (branch.name.as_str(), branch.state))
+            .collect();
+        assert_eq!(
+            branches,
+            vec![
+                ("gone", RemoteBranchState::Stale),
+                ("later", RemoteBranchState::New),
+                ("main", RemoteBranchState::Tracked),
+            ]
+        );
+        assert!(git_show.contains("refs/remotes/origin/gone stale"));
+        assert!(git_show.contains("later new"));
+        assert!(git_show.contains("main tracked"));
+
+        assert_eq!(queried.pull.len(), 1);
+        assert_eq!(queried.pull[0].local, "main");
+        assert_eq!(queried.pull[0].remote, "main");
+        assert!(git_show.contains("main merges with remote main"));
+
+        let push: Vec<(&str, RemotePushState)> = queried
+            .push
+            .iter()
+            .map(|push| (push.local.as_str(), push.state))
+            .collect();
+        assert_eq!(
+            push,
+            vec![
+                ("later", RemotePushState::FastForwardable),
+                ("main", RemotePushState::FastForwardable),
+            ]
+        );
+        assert!(git_show.contains("later pushes to later (fast-forwardable)"));
+        assert!(git_show.contains("main pushes to main (fast-forwardable)"));
+
+        assert_eq!(not_queried.head_branch, None);
+        assert_eq!(not_queried.branches.len(), 2);
+        assert!(
+            not_queried
+                .branches
+                .iter()
+                .all(|branch| branch.state == RemoteBranchState::NotQueried)
+        );
+        assert_eq!(not_queried.push.len(), 1);
+        assert_eq!(not_queried.push[0].state, RemotePushState::NotQueried);
+    }
+
+    #[test]
+    fn git_remote_prune_set_head_update_set_branches_test() {
+        let dir_name = "./temp_test/remote_prune_update/";
+        let remote_name = "./temp_test/remote_prune_update_remote.git";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a bare remote with three branches, fetch them and remove one on the remote
+        let path = env::current_dir().unwrap().join(dir_name);
+        let remote = env::current_dir().unwrap().join(remote_name);
+        let remote_git = ["--git-dir", remote.to_str().unwrap()];
+        git_output(&path, &["init", "--initial-branch", "main"]);
+        git_output(
+            &path,
+            &[
+                "init",
+                "--bare",
+                "--initial-branch",
+                "main",
+                remote.to_str().unwrap(),
+            ],
+        );
+        git_output(
+            &path,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        git_output(
+            &path,
+            &["remote", "add", "mirror", remote.to_str().unwrap()],
+        );
+        git_output(&path, &["config", "remotes.mirrors", "mirror"]);
+        git_output(&path, &["config", "user.name", "Remote"]);
+        git_output(&path, &["config", "user.email", "remote@example.com"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "first"]);
+        git_output(
+            &path,
+            &["push", "origin", "main", "main:feature", "main:old"],
+        );
+        git_output(
+            &path,
+            &[&remote_git[..], &["update-ref", "-d", "refs/heads/old"]].concat(),
+        );
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let run = |subcommand: RemoteSubCommand, flags: Vec<RemoteFlags>| {
+            let mut config = RemoteConfig::new(Some(subcommand));
+            for flag in flags {
+                config.add_flag(flag).unwrap();
+            }
+            repo.git_remote(config)
+        };
+        let ref_exists = |name: &str| {
+            git_output(&path, &["rev-parse", "--verify", "-q", name])
+                .status
+                .success()
+        };
+
+        // prune
+        let prune = || RemoteSubCommand::Prune(vec!["origin".into()]);
+        let dry_run = run(prune(), vec![RemoteFlags::DryRun(true)]).unwrap();
+        let kept = ref_exists("refs/remotes/origin/old");
+        let pruned = run(prune(), vec![]).unwrap();
+        let deleted = !ref_exists("refs/remotes/origin/old");
+
+        // set-head
+        let set_head = |branch: Option<&str>, flags| {
+            let subcommand = RemoteSubCommand::SetHead("origin".into(), branch.map(String::from));
+            run(subcommand, flags)
+        };
+        set_head(None, vec![RemoteFlags::Auto(true)]).unwrap();
+        let auto_head = git_output(&path, &["symbolic-ref", "refs/remotes/origin/HEAD"]);
+        set_head(Some("feature"), vec![]).unwrap();
+        let manual_head = git_output(&path, &["symbolic-ref", "refs/remotes/origin/HEAD"]);
+        let missing_branch = set_head(Some("missing"), vec![]);
+        let no_branch = set_head(None, vec![]);
+        set_head(None, vec![RemoteFlags::Delete(true)]).unwrap();
+        let deleted_head = !ref_exists("refs/remotes/origin/HEAD");
+
+        // update, a group first and then the default remotes
+        git_output(
+            &path,
+            &[&remote_git[..], &["update-ref", "refs/heads/extra", "main"]].concat(),
+        );
+        let update = |groups: Vec<&str>| {
+            let groups = groups.into_iter().map(String::from).collect();
+            run(RemoteSubCommand::Update(groups), vec![])
+        };
+        update(vec!["mirrors"]).unwrap();
+        let group_fetched = ref_exists("refs/remotes/mirror/extra");
+        let group_skipped = !ref_exists("refs/remotes/origin/extra");
+        git_output(
+            &path,
+            &["config", "remote.mirror.skipDefaultUpdate", "true"],
+        );
+        git_output(
+            &path,
+            &[&remote_git[..], &["update-ref", "refs/heads/later", "main"]].concat(),
+        );
+        update(vec![]).unwrap();
+        let default_fetched = ref_exists("refs/remotes/origin/later");
+        let default_skipped = !ref_exists("refs/remotes/mirror/later");
+        let unknown_group = update(vec!["unknown"]);
+        git_output(
+            &path,
+            &[&remote_git[..], &["update-ref", "-d", "refs/heads/later"]].concat(),
+        );
+        let update_prune = RemoteSubCommand::Update(vec!["origin".into()]);
+        let update_pruned = run(update_prune, vec![RemoteFlags::Prune(true)]).unwrap();
+
+        // set-branches
+        let set_branches = |branches: Vec<&str>, flags| {
+            let branches = branches.into_iter().map(String::from).collect();
+            run(
+                RemoteSubCommand::SetBranches("origin".into(), branches),
+                flags,
+            )
+        };
+        set_branches(vec!["main"], vec![]).unwrap();
+        set_branches(vec!["feature"], vec![RemoteFlags::Add(true)]).unwrap();
+        let fetch = git_output(&path, &["config", "--get-all", "remote.origin.fetch"]);
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+        Command::new("rm")
+            .args(["-rf", remote_name])
+            .output()
+            .unwrap();
+
+        let old = vec!["refs/remotes/origin/old".to_string()];
+        assert_eq!(dry_run, RemoteOutput::Pruned(old.clone()));
+        assert!(kept);
+        assert_eq!(pruned, RemoteOutput::Pruned(old));
+        assert!(deleted);
+
+        assert_eq!(
+            String::from_utf8_lossy(&auto_head.stdout),
+            "refs/remotes/origin/main\n"
+        );
+        assert_eq!(
+            String::from_utf8_lossy(&manual_head.stdout),
+            "refs/remotes/origin/feature\n"
+        );
+        assert_eq!(
+            missing_branch.unwrap_err().message(),
+            "Not a valid ref: refs/remotes/origin/missing"
+        );
+        assert!(no_branch.is_err());
+        assert!(deleted_head);
+
+        assert!(group_fetched);
+        assert!(group_skipped);
+        assert!(default_fetched);
+        assert!(default_skipped);
+        assert_eq!(
+            unknown_group.unwrap_err().message(),
+            "No such remote or remote group: unknown"
+        );
+        assert_eq!(
+            update_pruned,
+            RemoteOutput::Pruned(vec!["refs/remotes/origin/later".to_string()])
+        );
+
+        assert_eq!(
+            String::from_utf8_lossy(&fetch.stdout),
+            "+refs/heads/main:refs/remotes/origin/main\n\
+             +refs/heads/feature:refs/remotes/origin/feature\n"
+        );
+    }
 }
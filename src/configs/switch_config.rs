@@ -0,0 +1,309 @@
+use git2::{BranchType, Error, Repository, build::CheckoutBuilder};
+
+use crate::{CheckoutConfig, CheckoutFlags, GitRepository, configs::checkout_config::tracking_ref};
+
+/// A struct used to specify various details about the `git switch` command.
+#[derive(Clone)]
+pub struct SwitchConfig {
+    branch: String,
+    flags: SwitchFlagsInternal,
+}
+
+impl SwitchConfig {
+    /// Creates a SwitchConfig object switching to `branch`, `-` switches back to the branch
+    /// checked out before. With `SwitchFlags::Create`, `SwitchFlags::ForceCreate` or
+    /// `SwitchFlags::Detach` it is the start point instead, an empty one starts at `HEAD`.
+    pub fn new(branch: impl Into<String>) -> Self {
+        SwitchConfig {
+            branch: branch.into(),
+            flags: SwitchFlagsInternal::default(),
+        }
+    }
+
+    pub fn get_branch(&self) -> &str {
+        &self.branch
+    }
+
+    pub fn add_flag(&mut self, flag: SwitchFlags) -> &Self {
+        match flag {
+            SwitchFlags::Create(name) => self.flags.create = Some((name, false)),
+            SwitchFlags::ForceCreate(name) => self.flags.create = Some((name, true)),
+            SwitchFlags::Detach(detach) => self.flags.detach = detach,
+            SwitchFlags::Guess(guess) => self.flags.guess = Some(guess),
+            SwitchFlags::NoGuess(no_guess) => self.flags.guess = Some(!no_guess),
+            SwitchFlags::DiscardChanges(discard) => self.flags.discard_changes = discard,
+            SwitchFlags::Merge(merge) => self.flags.merge = merge,
+            SwitchFlags::Orphan(name) => self.flags.orphan = Some(name),
+        };
+        self
+    }
+}
+
+#[derive(Default, Clone)]
+pub(crate) struct SwitchFlagsInternal {
+    create: Option<(String, bool)>,
+    detach: bool,
+    guess: Option<bool>,
+    discard_changes: bool,
+    merge: bool,
+    orphan: Option<String>,
+}
+
+#[derive(Clone, Debug)]
+pub enum SwitchFlags {
+    /// `-c <new-branch>` or `--create <new-branch>` flag for `git switch`.
+    /// Creates the branch at the start point and switches to it, fails if it already exists.
+    Create(String),
+
+    /// `-C <new-branch>` or `--force-create <new-branch>` flag for `git switch`.
+    /// Same as `Create` except an existing branch is reset to the start point.
+    ForceCreate(String),
+
+    /// `-d` or `--detach` flag for `git switch`.
+    /// Detaches `HEAD` at the start point, needed to switch to anything but a branch.
+    /// Defaults to false.
+    Detach(bool),
+
+    /// `--guess` flag for `git switch`.
+    /// Creates a local branch tracking the remote-tracking branch of the same name when
+    /// exactly one remote has it.
+    /// Defaults to `checkout.guess` or true.
+    Guess(bool),
+
+    /// `--no-guess` flag for `git switch`.
+    /// Defaults to false.
+    NoGuess(bool),
+
+    /// `--discard-changes` or `-f` flag for `git switch`.
+    /// Throws away local changes instead of refusing to switch.
+    /// Defaults to false.
+    DiscardChanges(bool),
+
+    /// `-m` or `--merge` flag for `git switch`.
+    /// Carries local changes that conflict with the switch across with a three-way merge.
+    /// Defaults to false.
+    Merge(bool),
+
+    /// `--orphan <new-branch>` flag for `git switch`.
+    /// Switches to a new unborn branch, all tracked files are removed.
+    Orphan(String),
+}
+
+impl GitRepository {
+    /// Switches branches, same as `git switch`. Returns the branch that was checked out
+    /// before, `None` if `HEAD` was detached, so it can be switched back to.
+    pub fn git_switch(&self, config: SwitchConfig) -> Result<Option<String>, Error> {
+        if let Some(repository) = &self.repository {
+            let previous = current_branch(repository)?;
+            let flags = &config.flags;
+
+            // `-` is the branch or commit checked out before
+            let mut detach = flags.detach;
+            let spec = if config.branch == "-" {
+                match repository.revparse_ext("@{-1}") {
+                    Ok((_, Some(reference))) if reference.is_branch() => {
+                        reference.shorthand().unwrap_or_default().to_string()
+                    }
+                    Ok((object, _)) if detach => object.id().to_string(),
+                    Ok((object, _)) => {
+                        let id = object.id().to_string();
+                        let err_msg = format!("a branch is expected, got commit '{}'", &id[..7]);
+                        return Err(Error::from_str(&err_msg));
+                    }
+                    Err(_) => return Err(Error::from_str("no previous branch")),
+                }
+            } else {
+                config.branch.clone()
+            };
+
+            if let Some(name) = &flags.orphan {
+                self.switch_orphan(repository, name, flags.discard_changes)?;
+                return Ok(previous);
+            }
+
+            let mut checkout = CheckoutConfig::new(spec.clone());
+            match &flags.create {
+                Some((name, false)) => checkout.add_flags(CheckoutFlags::NewBranch(name.clone())),
+                Some((name, true)) => checkout.add_flags(CheckoutFlags::ResetBranch(name.clone())),
+                None if !detach => {
+                    expect_branch(repository, &spec, flags.guess)?;
+                }
+                None => {}
+            }
+            if flags.create.is_some() {
+                detach = false;
+            }
+            checkout.add_flags(CheckoutFlags::Detach(detach));
+            checkout.add_flags(CheckoutFlags::Force(flags.discard_changes));
+            checkout.add_flags(CheckoutFlags::Merge(flags.merge));
+            if let Some(guess) = flags.guess {
+                checkout.add_flags(CheckoutFlags::Guess(guess));
+            }
+            self.git_checkout(checkout)?;
+            return Ok(previous);
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    fn switch_orphan(
+        &self,
+        repository: &Repository,
+        name: &str,
+        discard_changes: bool,
+    ) -> Result<(), Error> {
+        if repository.find_branch(name, BranchType::Local).is_ok() {
+            let err_msg = format!("a branch named '{}' already exists", name);
+            return Err(Error::from_str(&err_msg));
+        }
+        // an empty tree removes every tracked file
+        let empty = repository.find_tree(repository.treebuilder(None)?.write()?)?;
+        let mut checkout_builder = CheckoutBuilder::new();
+        if discard_changes {
+            checkout_builder.force();
+        } else {
+            checkout_builder.safe();
+        }
+        repository.checkout_tree(empty.as_object(), Some(&mut checkout_builder))?;
+        repository.set_head(&format!("refs/heads/{}", name))
+    }
+}
+
+/// Returns the name of the branch `HEAD` points to, even when it is unborn.
+fn current_branch(repository: &Repository) -> Result<Option<String>, Error> {
+    let head = repository.find_reference("HEAD")?;
+    Ok(head
+        .symbolic_target()
+        .and_then(|target| target.strip_prefix("refs/heads/"))
+        .map(str::to_string))
+}
+
+/// Refuses to switch to anything but a local branch or a remote-tracking branch that can be
+/// guessed, since that would detach `HEAD`.
+fn expect_branch(repository: &Repository, spec: &str, guess: Option<bool>) -> Result<(), Error> {
+    if repository.find_branch(spec, BranchType::Local).is_ok() {
+        return Ok(());
+    }
+    let guess = match guess {
+        Some(guess) => guess,
+        None => repository
+            .config()?
+            .get_bool("checkout.guess")
+            .unwrap_or(true),
+    };
+    if guess {
+        for remote in repository.remotes()?.iter().flatten() {
+            if tracking_ref(repository, remote, spec)?.is_some() {
+                return Ok(());
+            }
+        }
+    }
+
+    let err_msg = if repository
+        .find_reference(&format!("refs/tags/{}", spec))
+        .is_ok()
+    {
+        format!("a branch is expected, got tag '{}'", spec)
+    } else if repository.find_branch(spec, BranchType::Remote).is_ok() {
+        format!("a branch is expected, got remote branch '{}'", spec)
+    } else if repository.revparse_single(spec).is_ok() {
+        format!("a branch is expected, got commit '{}'", spec)
+    } else {
+        format!("invalid reference: {}", spec)
+    };
+    Err(Error::from_str(&err_msg))
+}
+
+#[cfg(test)]
+mod switch_test {
+    use std::{fs, path::Path};
+
+    use super::{SwitchConfig, SwitchFlags};
+    use crate::{GitRepository, helpers::test_util::CliComparison};
+
+    #[test]
+    fn git_switch_matches_cli_test() {
+        let cli = CliComparison::new("switch");
+        cli.write("base", "a.txt", "a\n");
+        cli.git("base", &["add", "."]);
+        cli.git("base", &["commit", "-m", "root"]);
+        cli.git("base", &["tag", "v1"]);
+        cli.write("base", "a.txt", "b\n");
+        cli.git("base", &["commit", "-am", "second"]);
+        cli.git("base", &["remote", "add", "origin", "../missing"]);
+        cli.git(
+            "base",
+            &["update-ref", "refs/remotes/origin/feature", "HEAD~1"],
+        );
+        cli.fork();
+
+        let repo = GitRepository::open(Path::new(&cli.path("ours"))).unwrap();
+        let switch = |branch: &str, flags: Vec<SwitchFlags>| {
+            let mut config = SwitchConfig::new(branch);
+            for flag in flags {
+                config.add_flag(flag);
+            }
+            repo.git_switch(config)
+        };
+        let state = |cli: &CliComparison, dir: &str| {
+            [
+                cli.git(dir, &["status", "--porcelain", "--branch"]),
+                cli.git(dir, &["branch", "-vv"]),
+                cli.git(dir, &["ls-files", "-s"]),
+            ]
+            .join("\n")
+        };
+
+        let created = switch("", vec![SwitchFlags::Create("topic".into())]).unwrap();
+        cli.git("theirs", &["switch", "-c", "topic"]);
+        cli.record("-c", state);
+
+        let guessed = switch("feature", vec![]).unwrap();
+        cli.git("theirs", &["switch", "feature"]);
+        cli.record("--guess", state);
+
+        // tags and commits need --detach
+        let tag = switch("v1", vec![]).unwrap_err();
+        let commit = switch("main~1", vec![]).unwrap_err();
+        switch("v1", vec![SwitchFlags::Detach(true)]).unwrap();
+        cli.git("theirs", &["switch", "--detach", "v1"]);
+        cli.record("--detach", state);
+        let detached = switch("main", vec![]).unwrap();
+        cli.git("theirs", &["switch", "main"]);
+
+        switch("main", vec![SwitchFlags::ForceCreate("topic".into())]).unwrap();
+        cli.git("theirs", &["switch", "-C", "topic", "main"]);
+        cli.record("-C", state);
+
+        let previous = switch("-", vec![]).unwrap();
+        cli.git("theirs", &["switch", "-"]);
+        cli.record("-", state);
+
+        cli.write_both("a.txt", "local\n");
+        let refused = switch("feature", vec![]).is_err();
+        switch("feature", vec![SwitchFlags::DiscardChanges(true)]).unwrap();
+        cli.git("theirs", &["switch", "--discard-changes", "feature"]);
+        cli.record("--discard-changes", state);
+
+        switch("", vec![SwitchFlags::Orphan("fresh".into())]).unwrap();
+        cli.git("theirs", &["switch", "--orphan", "fresh"]);
+        cli.record("--orphan", state);
+        let files = fs::read_dir(cli.path("ours")).unwrap().count();
+
+        cli.assert_matches();
+        assert_eq!(created.as_deref(), Some("main"));
+        assert_eq!(guessed.as_deref(), Some("topic"));
+        assert_eq!(tag.message(), "a branch is expected, got tag 'v1'");
+        assert_eq!(
+            commit.message(),
+            "a branch is expected, got commit 'main~1'"
+        );
+        assert_eq!(detached, None);
+        assert_eq!(previous.as_deref(), Some("topic"));
+        assert!(refused);
+        // only .git is left
+        assert_eq!(files, 1);
+    }
+}
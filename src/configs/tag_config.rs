@@ -0,0 +1,770 @@
+use std::{cmp::Ordering, fmt::Display};
+
+use crate::{
+    GitRepository,
+    configs::commit_config::CommitConfig,
+    helpers::{
+        pattern::wildmatch,
+        signing::{self, Signer},
+    },
+};
+use git2::{Error, ObjectType, Oid, Reference, Repository, Signature};
+
+/// A struct used to specify various details about the `git tag` command.
+pub struct TagConfig {
+    subcommand: Option<TagSubCommand>,
+    flags: TagFlagsInternal,
+    tagger: Option<(String, String)>,
+    signer: Option<Box<dyn Signer>>,
+}
+
+impl TagConfig {
+    /// Creates a TagConfig object with the passed subcommand, `None` lists all tags.
+    ///
+    /// Example of tag config for creating an annotated tag on `HEAD`.
+    /// ```ignore
+    /// let mut tag_config = TagConfig::new(Some(TagSubCommand::Create("v1.0.0".to_string(), None)));
+    /// tag_config.add_flag(TagFlags::Message("release 1.0.0".to_string()))?;
+    /// ```
+    pub fn new(subcommand: Option<TagSubCommand>) -> Self {
+        TagConfig {
+            subcommand,
+            flags: TagFlagsInternal::default(),
+            tagger: None,
+            signer: None,
+        }
+    }
+
+    /// Set a subcommand to the config.
+    ///
+    /// *NOTE:* setting a subcommand will RESET any flags applied previously.
+    pub fn set_subcommand(&mut self, subcommand: Option<TagSubCommand>) {
+        self.subcommand = subcommand;
+        self.flags = TagFlagsInternal::default();
+    }
+
+    /// Sets the identity recorded as the tagger of annotated tags. Defaults to the committer
+    /// identity from the environment and git config.
+    pub fn set_tagger(&mut self, name: impl Into<String>, email: impl Into<String>) {
+        self.tagger = Some((name.into(), email.into()));
+    }
+
+    /// Sets a custom `Signer` used whenever the tag is signed, instead of the signer configured
+    /// through `gpg.format` and `user.signingkey`.
+    pub fn set_signer(&mut self, signer: impl Signer + 'static) {
+        self.signer = Some(Box::new(signer));
+    }
+
+    /// Set a flag to the config.
+    /// Sets the flag if it is valid for a given subcommand, else returns an error.
+    pub fn add_flag(&mut self, flag: TagFlags) -> Result<(), Error> {
+        let error = format!("No flag '{}' for subcommand '{:?}'.", flag, self.subcommand);
+        let error = Error::from_str(&error);
+        match &self.subcommand {
+            Some(TagSubCommand::Create(_, _)) => match flag {
+                TagFlags::Annotate(annotate) => self.flags.annotate = annotate,
+                TagFlags::Message(message) => self.flags.message = Some(message),
+                TagFlags::Force(force) => self.flags.force = force,
+                TagFlags::Sign(key) => {
+                    self.flags.sign = true;
+                    self.flags.signing_key = key;
+                }
+                _ => return Err(error),
+            },
+            Some(TagSubCommand::List(_)) | None => match flag {
+                TagFlags::Sort(sort) => self.flags.sort = Some(TagSort::parse(&sort)?),
+                TagFlags::Contains(commit) => self.flags.contains = commit,
+                TagFlags::PointsAt(object) => self.flags.points_at = object,
+                TagFlags::Lines(lines) => self.flags.lines = lines,
+                _ => return Err(error),
+            },
+            Some(TagSubCommand::Push(_, _)) => match flag {
+                TagFlags::Force(force) => self.flags.force = force,
+                TagFlags::NoVerify(no_verify) => self.flags.no_verify = no_verify,
+                _ => return Err(error),
+            },
+            Some(TagSubCommand::DeleteRemote(_, _)) => match flag {
+                TagFlags::NoVerify(no_verify) => self.flags.no_verify = no_verify,
+                _ => return Err(error),
+            },
+            Some(TagSubCommand::Delete(_)) => {
+                return Err(error);
+            }
+        };
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum TagSubCommand {
+    /// Create a tag, same as `git tag <name> [<commit>]`.
+    /// Takes the name of the tag and an optional commit (defaults to `HEAD`) in this order.
+    Create(String, Option<String>),
+
+    /// Delete tags, same as `git tag -d <name>...`.
+    Delete(Vec<String>),
+
+    /// List tags, same as `git tag -l [<pattern>...]`.
+    /// Only tags matching at least one of the glob patterns are listed, pass an empty vector to
+    /// list all tags.
+    List(Vec<String>),
+
+    /// Push a single tag to a remote, same as `git push <remote> refs/tags/<name>`.
+    /// Takes the remote name and the tag name in this order.
+    Push(String, String),
+
+    /// Delete a single tag from a remote, same as `git push --delete <remote> refs/tags/<name>`.
+    /// Takes the remote name and the tag name in this order.
+    DeleteRemote(String, String),
+}
+
+#[derive(Default)]
+pub(crate) struct TagFlagsInternal {
+    annotate: bool,
+    message: Option<String>,
+    force: bool,
+    sign: bool,
+    signing_key: Option<String>,
+    sort: Option<TagSort>,
+    contains: Option<String>,
+    points_at: Option<String>,
+    lines: Option<usize>,
+    no_verify: bool,
+}
+
+#[derive(Clone, Debug)]
+pub enum TagFlags {
+    /// `-a` or `--annotate` flag for `git tag <name>`.
+    /// `true` creates an annotated tag, which requires a message.
+    /// Defaults to false.
+    Annotate(bool),
+
+    /// `-m <msg>` or `--message <msg>` flag for `git tag <name>`.
+    /// Creates an annotated tag with the given message.
+    Message(String),
+
+    /// `-f` or `--force` flag for `git tag <name>` and pushing tags.
+    /// `true` replaces an existing tag with the same name.
+    /// Defaults to false.
+    Force(bool),
+
+    /// `-s` or `-u <key>` flag for `git tag <name>`.
+    /// Creates a signed annotated tag, `Some(key)` signs with the given key, `None` uses
+    /// `user.signingkey` or the tagger identity.
+    /// Tags are signed by default when `tag.gpgSign` is set.
+    Sign(Option<String>),
+
+    /// `--sort=<key>` flag for `git tag -l`.
+    /// Supported keys are `refname`, `version:refname` (or `v:refname`), `creatordate` and
+    /// `taggerdate`, prefix with `-` to sort in descending order.
+    Sort(String),
+
+    /// `--contains <commit>` flag for `git tag -l`.
+    /// Only list tags which contain the commit. Passing `None` unsets the flag.
+    Contains(Option<String>),
+
+    /// `--points-at <object>` flag for `git tag -l`.
+    /// Only list tags which point at the object. Passing `None` unsets the flag.
+    PointsAt(Option<String>),
+
+    /// `-n<num>` flag for `git tag -l`.
+    /// Fills `TagInfo::lines` with the first `num` lines of the annotation, or of the commit
+    /// message for lightweight tags. Passing `None` unsets the flag.
+    Lines(Option<usize>),
+
+    /// `--no-verify` flag for pushing and deleting tags on a remote.
+    /// `true` bypasses the `pre-push` hook. Hooks are only run when enabled with
+    /// `GitRepository::run_hooks`.
+    /// Defaults to false.
+    NoVerify(bool),
+}
+
+impl Display for TagFlags {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TagFlags::Annotate(annotate) => write!(f, "--annotate {}", annotate),
+            TagFlags::Message(message) => write!(f, "--message {}", message),
+            TagFlags::Force(force) => write!(f, "--force {}", force),
+            TagFlags::Sign(key) => write!(f, "--sign {:?}", key),
+            TagFlags::Sort(sort) => write!(f, "--sort={}", sort),
+            TagFlags::Contains(commit) => write!(f, "--contains {:?}", commit),
+            TagFlags::PointsAt(object) => write!(f, "--points-at {:?}", object),
+            TagFlags::Lines(lines) => write!(f, "-n {:?}", lines),
+            TagFlags::NoVerify(no_verify) => write!(f, "--no-verify {}", no_verify),
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TagSortKey {
+    Refname,
+    VersionRefname,
+    CreatorDate,
+}
+
+#[derive(Clone, Copy)]
+struct TagSort {
+    key: TagSortKey,
+    descending: bool,
+}
+
+impl TagSort {
+    fn parse(sort: &str) -> Result<Self, Error> {
+        let (descending, key) = match sort.strip_prefix('-') {
+            Some(key) => (true, key),
+            None => (false, sort),
+        };
+        let key = match key {
+            "refname" => TagSortKey::Refname,
+            "version:refname" | "v:refname" => TagSortKey::VersionRefname,
+            "creatordate" | "taggerdate" => TagSortKey::CreatorDate,
+            _ => {
+                let err_msg = format!("unsupported sort key '{}'", sort);
+                return Err(Error::from_str(&err_msg));
+            }
+        };
+        Ok(TagSort { key, descending })
+    }
+}
+
+/// A tag as returned by `git_tag`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagInfo {
+    /// Short name of the tag, e.g. "v1.0.0".
+    pub name: String,
+
+    /// Id the tag reference points to, the tag object for annotated tags.
+    pub id: Oid,
+
+    /// Id of the object the tag finally points to, after peeling nested tags.
+    pub target: Oid,
+
+    /// `true` for annotated tags.
+    pub annotated: bool,
+
+    /// Tagger of annotated tags as "Name <email>".
+    pub tagger: Option<String>,
+
+    /// Message of annotated tags.
+    pub message: Option<String>,
+
+    /// Annotation lines requested with `TagFlags::Lines`.
+    pub lines: Vec<String>,
+}
+
+impl GitRepository {
+    /// Runs `git tag` with the given config.
+    ///
+    /// Returns the created tag for `Create` and `Push`, the deleted tags for `Delete`, the
+    /// matching tags for `List` and an empty vector for `DeleteRemote`. To verify a tag, use
+    /// `GitRepository::verify_tag`.
+    pub fn git_tag(&self, config: TagConfig) -> Result<Vec<TagInfo>, Error> {
+        if let Some(repository) = &self.repository {
+            let lines = config.flags.lines;
+            match &config.subcommand {
+                Some(TagSubCommand::Create(name, commit)) => {
+                    // git tag <name> [<commit>]
+                    self.create_tag(repository, &config, name, commit.as_deref())?;
+                    let reference = repository.find_reference(&format!("refs/tags/{}", name))?;
+                    return Ok(vec![GitRepository::tag_info(
+                        repository, name, &reference, lines,
+                    )?]);
+                }
+                Some(TagSubCommand::Delete(names)) => {
+                    // git tag -d
+                    let mut deleted = vec![];
+                    for name in names {
+                        let full = format!("refs/tags/{}", name);
+                        let mut reference = match repository.find_reference(&full) {
+                            Ok(reference) => reference,
+                            Err(ref e) if e.code() == git2::ErrorCode::NotFound => {
+                                let err_msg = format!("tag '{}' not found.", name);
+                                return Err(Error::from_str(&err_msg));
+                            }
+                            Err(e) => return Err(e),
+                        };
+                        deleted.push(GitRepository::tag_info(
+                            repository, name, &reference, lines,
+                        )?);
+                        reference.delete()?;
+                    }
+                    return Ok(deleted);
+                }
+                Some(TagSubCommand::List(patterns)) => {
+                    // git tag -l
+                    return GitRepository::list_tags(repository, &config, patterns);
+                }
+                None => {
+                    // git tag
+                    return GitRepository::list_tags(repository, &config, &[]);
+                }
+                Some(TagSubCommand::Push(remote, name)) => {
+                    // git push <remote> refs/tags/<name>
+                    let full = format!("refs/tags/{}", name);
+                    let reference = repository.find_reference(&full)?;
+                    let force = if config.flags.force { "+" } else { "" };
+                    let refspec = format!("{}{}:{}", force, full, full);
+                    self.push_refspecs(repository, remote, &[refspec], config.flags.no_verify)?;
+                    return Ok(vec![GitRepository::tag_info(
+                        repository, name, &reference, lines,
+                    )?]);
+                }
+                Some(TagSubCommand::DeleteRemote(remote, name)) => {
+                    // git push --delete <remote> refs/tags/<name>
+                    let refspec = format!(":refs/tags/{}", name);
+                    self.push_refspecs(repository, remote, &[refspec], config.flags.no_verify)?;
+                    return Ok(vec![]);
+                }
+            }
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    fn create_tag(
+        &self,
+        repository: &Repository,
+        config: &TagConfig,
+        name: &str,
+        commit: Option<&str>,
+    ) -> Result<(), Error> {
+        let full = format!("refs/tags/{}", name);
+        if !Reference::is_valid_name(&full) {
+            let err_msg = format!("'{}' is not a valid tag name.", name);
+            return Err(Error::from_str(&err_msg));
+        }
+        if !config.flags.force && repository.find_reference(&full).is_ok() {
+            let err_msg = format!("tag '{}' already exists", name);
+            return Err(Error::from_str(&err_msg));
+        }
+
+        let target = repository.revparse_single(commit.unwrap_or("HEAD"))?;
+        let sign = config.flags.sign
+            || (config.flags.annotate || config.flags.message.is_some())
+                && repository
+                    .config()?
+                    .get_bool("tag.gpgSign")
+                    .unwrap_or(false);
+        let annotated = config.flags.annotate || config.flags.message.is_some() || sign;
+
+        // lightweight tag
+        if !annotated {
+            repository.tag_lightweight(name, &target, config.flags.force)?;
+            return Ok(());
+        }
+
+        let message = match &config.flags.message {
+            Some(message) => git2::message_prettify(message, Some(b'#'))?,
+            None => String::new(),
+        };
+        if message.is_empty() {
+            return Err(Error::from_str("no tag message given"));
+        }
+
+        let (tagger_name, tagger_email) = match &config.tagger {
+            Some(tagger) => tagger.clone(),
+            None => {
+                let committer = CommitConfig::from_git_config(self)?;
+                (committer.get_name(), committer.get_email())
+            }
+        };
+        let tagger = Signature::now(&tagger_name, &tagger_email)?;
+
+        // annotated tag
+        if !sign {
+            repository.tag(name, &target, &tagger, &message, config.flags.force)?;
+            return Ok(());
+        }
+
+        // signed tag
+        let signer = match &config.signer {
+            Some(signer) => signer,
+            None => {
+                let identity = format!("{} <{}>", tagger_name, tagger_email);
+                &signing::signer_from_config(
+                    &repository.config()?,
+                    config.flags.signing_key.clone(),
+                    identity,
+                )?
+            }
+        };
+        signing::tag_signed(
+            repository,
+            name,
+            &target,
+            &tagger,
+            &message,
+            signer.as_ref(),
+            config.flags.force,
+        )?;
+        Ok(())
+    }
+
+    fn list_tags(
+        repository: &Repository,
+        config: &TagConfig,
+        patterns: &[String],
+    ) -> Result<Vec<TagInfo>, Error> {
+        let points_at = match &config.flags.points_at {
+            Some(spec) => Some(repository.revparse_single(spec)?.id()),
+            None => None,
+        };
+        let contains = match &config.flags.contains {
+            Some(spec) => Some(repository.revparse_single(spec)?.peel_to_commit()?.id()),
+            None => None,
+        };
+
+        let mut tags: Vec<(i64, TagInfo)> = vec![];
+        for name in repository.tag_names(None)?.iter().flatten() {
+            if !patterns.is_empty() && !patterns.iter().any(|p| wildmatch(p, name)) {
+                continue;
+            }
+            let reference = repository.find_reference(&format!("refs/tags/{}", name))?;
+            let info = GitRepository::tag_info(repository, name, &reference, config.flags.lines)?;
+
+            // points-at
+            if let Some(oid) = points_at
+                && info.id != oid
+                && info.target != oid
+            {
+                continue;
+            }
+
+            // contains
+            if let Some(oid) = contains {
+                let Ok(commit) = reference.peel_to_commit() else {
+                    continue;
+                };
+                if commit.id() != oid && !repository.graph_descendant_of(commit.id(), oid)? {
+                    continue;
+                }
+            }
+
+            let created = match reference.peel_to_tag() {
+                Ok(tag) => tag.tagger().map(|t| t.when().seconds()),
+                Err(_) => None,
+            };
+            let created = match created {
+                Some(created) => created,
+                None => match reference.peel_to_commit() {
+                    Ok(commit) => commit.committer().when().seconds(),
+                    Err(_) => 0,
+                },
+            };
+            tags.push((created, info));
+        }
+
+        // sort
+        let sort = config.flags.sort.unwrap_or(TagSort {
+            key: TagSortKey::Refname,
+            descending: false,
+        });
+        tags.sort_by(|(a_date, a), (b_date, b)| {
+            let order = match sort.key {
+                TagSortKey::Refname => a.name.cmp(&b.name),
+                TagSortKey::VersionRefname => version_cmp(&a.name, &b.name),
+                TagSortKey::CreatorDate => a_date.cmp(b_date).then(a.name.cmp(&b.name)),
+            };
+            if sort.descending {
+                order.reverse()
+            } else {
+                order
+            }
+        });
+
+        Ok(tags.into_iter().map(|(_, info)| info).collect())
+    }
+
+    fn tag_info(
+        repository: &Repository,
+        name: &str,
+        reference: &Reference,
+        lines: Option<usize>,
+    ) -> Result<TagInfo, Error> {
+        let id = match reference.target() {
+            Some(id) => id,
+            None => reference.resolve()?.target().unwrap_or(Oid::zero()),
+        };
+        let target = reference.peel(ObjectType::Any)?.id();
+
+        let (annotated, tagger, message) = match repository.find_tag(id) {
+            Ok(tag) => {
+                let tagger = tag.tagger().map(|tagger| {
+                    format!(
+                        "{} <{}>",
+                        String::from_utf8_lossy(tagger.name_bytes()),
+                        String::from_utf8_lossy(tagger.email_bytes())
+                    )
+                });
+                let message = tag
+                    .message_bytes()
+                    .map(|message| String::from_utf8_lossy(message).to_string());
+                (true, tagger, message)
+            }
+            Err(_) => (false, None, None),
+        };
+
+        // -n, the annotation without its signature, or the commit message
+        let lines = match lines {
+            Some(count) => {
+                let text = match &message {
+                    Some(message) => {
+                        let message = message.as_bytes();
+                        match signing::split_tag_signature(message) {
+                            Some((text, _)) => String::from_utf8_lossy(text).to_string(),
+                            None => String::from_utf8_lossy(message).to_string(),
+                        }
+                    }
+                    None => match reference.peel_to_commit() {
+                        Ok(commit) => String::from_utf8_lossy(commit.message_bytes()).to_string(),
+                        Err(_) => String::new(),
+                    },
+                };
+                text.lines()
+                    .take(count)
+                    .map(|line| line.to_string())
+                    .collect()
+            }
+            None => vec![],
+        };
+
+        Ok(TagInfo {
+            name: name.to_string(),
+            id,
+            target,
+            annotated,
+            tagger,
+            message,
+            lines,
+        })
+    }
+}
+
+/// Compares tag names treating runs of digits as numbers, so that "v1.10" sorts after "v1.9".
+fn version_cmp(a: &str, b: &str) -> Ordering {
+    let mut a = a.chars().peekable();
+    let mut b = b.chars().peekable();
+    loop {
+        match (a.peek().copied(), b.peek().copied()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let mut x_num = String::new();
+                while let Some(c) = a.next_if(|c| c.is_ascii_digit()) {
+                    x_num.push(c);
+                }
+                let mut y_num = String::new();
+                while let Some(c) = b.next_if(|c| c.is_ascii_digit()) {
+                    y_num.push(c);
+                }
+                let x_num = x_num.trim_start_matches('0');
+                let y_num = y_num.trim_start_matches('0');
+                let order = x_num.len().cmp(&y_num.len()).then(x_num.cmp(y_num));
+                if order != Ordering::Equal {
+                    return order;
+                }
+            }
+            (Some(x), Some(y)) => {
+                if x != y {
+                    return x.cmp(&y);
+                }
+                a.next();
+                b.next();
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tag_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{TagConfig, TagFlags, TagSubCommand};
+    use crate::{
+        GitRepository, VerifyConfig, helpers::signing::SshSigner, helpers::test_util::git_output,
+    };
+
+    #[test]
+    fn git_tag_create_list_delete_push_test() {
+        let dir_name = "./temp_test/tag/";
+        let remote_name = "./temp_test/tag_remote.git";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with two commits, a bare remote and a throwaway ssh key
+        let path = env::current_dir().unwrap().join(dir_name);
+        let remote = env::current_dir().unwrap().join(remote_name);
+        git_output(&path, &["init"]);
+        git_output(&path, &["init", "--bare", remote.to_str().unwrap()]);
+        git_output(
+            &path,
+            &["remote", "add", "origin", remote.to_str().unwrap()],
+        );
+        git_output(&path, &["config", "user.name", "Tagger"]);
+        git_output(&path, &["config", "user.email", "tagger@example.com"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "first\n\nbody"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "second"]);
+        let _ = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-f"])
+            .arg(path.join("key"))
+            .output()
+            .expect("ssh-keygen needs to be installed for signing tests");
+        let public_key = fs::read_to_string(path.join("key.pub")).unwrap();
+        fs::write(
+            path.join("allowed_signers"),
+            format!("tagger@example.com {}", public_key),
+        )
+        .unwrap();
+        git_output(
+            &path,
+            &["config", "gpg.ssh.allowedSignersFile", "allowed_signers"],
+        );
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let create = |name: &str, commit: Option<&str>, flags: Vec<TagFlags>| {
+            let subcommand = TagSubCommand::Create(name.to_string(), commit.map(String::from));
+            let mut config = TagConfig::new(Some(subcommand));
+            for flag in flags {
+                config.add_flag(flag).unwrap();
+            }
+            repo.git_tag(config)
+        };
+
+        // lightweight, annotated and signed tags
+        let light = create("v1.9", Some("HEAD~1"), vec![]).unwrap();
+        let annotated = create("v1.10", None, vec![TagFlags::Message("ten".into())]).unwrap();
+        let duplicate = create("v1.10", None, vec![]);
+        let subcommand = TagSubCommand::Create("v2.0".to_string(), None);
+        let mut config = TagConfig::new(Some(subcommand));
+        config.add_flag(TagFlags::Message("signed".into())).unwrap();
+        config.add_flag(TagFlags::Sign(None)).unwrap();
+        config.set_signer(SshSigner::new(path.join("key").to_str().unwrap()));
+        repo.git_tag(config).unwrap();
+        let verified = repo.verify_tag(VerifyConfig::new("v2.0")).unwrap();
+        let git_verified = git_output(&path, &["tag", "-v", "v2.0"]);
+
+        // listing
+        let list = |patterns: Vec<&str>, flags: Vec<TagFlags>| {
+            let patterns = patterns.into_iter().map(String::from).collect();
+            let mut config = TagConfig::new(Some(TagSubCommand::List(patterns)));
+            for flag in flags {
+                config.add_flag(flag).unwrap();
+            }
+            let tags = repo.git_tag(config).unwrap();
+            tags.into_iter().map(|t| t.name).collect::<Vec<String>>()
+        };
+        let by_name = list(vec![], vec![]);
+        let by_version = list(vec![], vec![TagFlags::Sort("version:refname".into())]);
+        let by_version_desc = list(vec!["v1.*"], vec![TagFlags::Sort("-v:refname".into())]);
+        let points_at = list(vec![], vec![TagFlags::PointsAt(Some("HEAD~1".into()))]);
+        let contains = list(vec![], vec![TagFlags::Contains(Some("HEAD".into()))]);
+        let mut config = TagConfig::new(None);
+        config.add_flag(TagFlags::Lines(Some(1))).unwrap();
+        let lines = repo.git_tag(config).unwrap();
+        let invalid_flag = TagConfig::new(None).add_flag(TagFlags::Force(true));
+
+        // pushing and deleting on the remote
+        let push = TagSubCommand::Push("origin".to_string(), "v1.10".to_string());
+        repo.git_tag(TagConfig::new(Some(push))).unwrap();
+        let pushed = git_output(&path, &["ls-remote", "--tags", "origin"]);
+        let delete = TagSubCommand::DeleteRemote("origin".to_string(), "v1.10".to_string());
+        repo.git_tag(TagConfig::new(Some(delete))).unwrap();
+        let deleted_remote = git_output(&path, &["ls-remote", "--tags", "origin"]);
+
+        // the pre-push hook sees tag pushes and deletions and can reject them
+        let log = path.join("pre-push.log");
+        let hook = path.join(".git/hooks/pre-push");
+        fs::write(
+            &hook,
+            format!(
+                "#!/bin/sh\ninput=$(cat)\necho \"$1 $input\" >> {}\ncase \"$input\" in *v2.0*) exit 1;; esac\n",
+                log.display()
+            ),
+        )
+        .unwrap();
+        Command::new("chmod").arg("+x").arg(&hook).output().unwrap();
+        let mut hooked = GitRepository::open(Path::new(&path)).unwrap();
+        hooked.run_hooks(true);
+        let push = |name: &str, no_verify: bool| {
+            let push = TagSubCommand::Push("origin".to_string(), name.to_string());
+            let mut config = TagConfig::new(Some(push));
+            config.add_flag(TagFlags::NoVerify(no_verify)).unwrap();
+            hooked.git_tag(config)
+        };
+        push("v1.10", false).unwrap();
+        let delete = TagSubCommand::DeleteRemote("origin".to_string(), "v1.10".to_string());
+        hooked.git_tag(TagConfig::new(Some(delete))).unwrap();
+        let rejected = push("v2.0", false);
+        let bypassed = push("v2.0", true);
+        let hook_log = fs::read_to_string(&log).unwrap();
+        let rev_parse = |rev: &str| {
+            let out = git_output(&path, &["rev-parse", rev]);
+            String::from_utf8_lossy(&out.stdout).trim().to_string()
+        };
+        let (ten, two) = (rev_parse("v1.10"), rev_parse("v2.0"));
+        let pushed_hooked = git_output(&path, &["ls-remote", "--tags", "origin"]);
+
+        // deleting locally
+        let delete = TagSubCommand::Delete(vec!["v1.9".to_string()]);
+        let deleted = repo.git_tag(TagConfig::new(Some(delete))).unwrap();
+        let missing = repo.git_tag(TagConfig::new(Some(TagSubCommand::Delete(vec![
+            "v1.9".to_string(),
+        ]))));
+        let remaining = git_output(&path, &["tag", "-l"]);
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+        Command::new("rm")
+            .args(["-rf", remote_name])
+            .output()
+            .unwrap();
+
+        assert!(!light[0].annotated);
+        assert!(annotated[0].annotated);
+        assert_eq!(
+            annotated[0].tagger.as_deref(),
+            Some("Tagger <tagger@example.com>")
+        );
+        assert_eq!(annotated[0].message.as_deref(), Some("ten\n"));
+        assert_eq!(
+            duplicate.unwrap_err().message(),
+            "tag 'v1.10' already exists"
+        );
+        assert!(verified.is_good());
+        assert!(git_verified.status.success());
+
+        assert_eq!(by_name, vec!["v1.10", "v1.9", "v2.0"]);
+        assert_eq!(by_version, vec!["v1.9", "v1.10", "v2.0"]);
+        assert_eq!(by_version_desc, vec!["v1.10", "v1.9"]);
+        assert_eq!(points_at, vec!["v1.9"]);
+        assert_eq!(contains, vec!["v1.10", "v2.0"]);
+        assert_eq!(lines[0].lines, vec!["ten"]);
+        assert_eq!(lines[1].lines, vec!["first"]);
+        assert_eq!(lines[2].lines, vec!["signed"]);
+        assert!(invalid_flag.is_err());
+
+        assert!(String::from_utf8_lossy(&pushed.stdout).contains("refs/tags/v1.10"));
+        assert!(deleted_remote.stdout.is_empty());
+        let zero = "0".repeat(40);
+        assert_eq!(
+            hook_log,
+            format!(
+                "origin refs/tags/v1.10 {ten} refs/tags/v1.10 {zero}\n\
+                 origin (delete) {zero} refs/tags/v1.10 {ten}\n\
+                 origin refs/tags/v2.0 {two} refs/tags/v2.0 {zero}\n"
+            )
+        );
+        assert!(crate::is_hook_error(&rejected.unwrap_err()));
+        assert!(bypassed.is_ok());
+        assert!(String::from_utf8_lossy(&pushed_hooked.stdout).contains("refs/tags/v2.0"));
+        assert_eq!(deleted[0].name, "v1.9");
+        assert_eq!(missing.unwrap_err().message(), "tag 'v1.9' not found.");
+        assert_eq!(String::from_utf8_lossy(&remaining.stdout), "v1.10\nv2.0\n");
+    }
+}
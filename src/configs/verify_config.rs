@@ -0,0 +1,339 @@
+use git2::{Error, ErrorCode, ObjectType, Oid, RevparseMode};
+
+use crate::{
+    GitRepository,
+    helpers::signing::{self, ConfigVerifier, SignatureCheck, Verifier},
+};
+
+/// Specifies details for `git verify-commit` and `git verify-tag`.
+pub struct VerifyConfig {
+    spec: String,
+    verifier: Option<Box<dyn Verifier>>,
+}
+
+impl VerifyConfig {
+    /// Creates a `VerifyConfig` for the commit, tag or revision range `spec`.
+    ///
+    /// By default signatures are checked with the program matching their type, configured
+    /// through `gpg.program`, `gpg.x509.program`, `gpg.ssh.program` and
+    /// `gpg.ssh.allowedSignersFile`.
+    pub fn new(spec: impl Into<String>) -> Self {
+        VerifyConfig {
+            spec: spec.into(),
+            verifier: None,
+        }
+    }
+
+    /// Returns the spec to be verified.
+    pub fn get_spec(&self) -> &str {
+        &self.spec
+    }
+
+    /// Sets a custom `Verifier` used to check every signature.
+    pub fn set_verifier(&mut self, verifier: impl Verifier + 'static) {
+        self.verifier = Some(Box::new(verifier));
+    }
+}
+
+impl GitRepository {
+    /// Checks the signature of the commit the spec resolves to, same as `git verify-commit`.
+    pub fn verify_commit(&self, config: VerifyConfig) -> Result<SignatureCheck, Error> {
+        if let Some(repository) = &self.repository {
+            let commit = repository.revparse_single(&config.spec)?.peel_to_commit()?;
+            let verifier = self.get_verifier(config.verifier)?;
+            return self.check_commit(verifier.as_ref(), commit.id());
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    /// Checks the signature of an annotated tag, same as `git verify-tag`.
+    pub fn verify_tag(&self, config: VerifyConfig) -> Result<SignatureCheck, Error> {
+        if let Some(repository) = &self.repository {
+            let object = match repository.revparse_single(&format!("refs/tags/{}", config.spec)) {
+                Ok(object) => object,
+                Err(_) => repository.revparse_single(&config.spec)?,
+            };
+            if object.kind() != Some(ObjectType::Tag) {
+                let err_msg = format!("{}: cannot verify a non-tag object", config.spec);
+                return Err(Error::from_str(&err_msg));
+            }
+
+            let odb = repository.odb()?;
+            let raw = odb.read(object.id())?;
+            let Some((payload, signature)) = signing::split_tag_signature(raw.data()) else {
+                return Ok(SignatureCheck::unsigned());
+            };
+            let verifier = self.get_verifier(config.verifier)?;
+            return verifier.verify(payload, signature);
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    /// Checks the signature of every commit in a revision range (e.g. `main..feature`), or of
+    /// every commit reachable from a single revision. The commits are returned newest first.
+    pub fn verify_commits(
+        &self,
+        config: VerifyConfig,
+    ) -> Result<Vec<(Oid, SignatureCheck)>, Error> {
+        if let Some(repository) = &self.repository {
+            let mut revwalk = repository.revwalk()?;
+            let revspec = repository.revparse(&config.spec)?;
+            let from = match revspec.from() {
+                Some(from) => Some(from.peel_to_commit()?.id()),
+                None => None,
+            };
+            let to = match revspec.to() {
+                Some(to) => Some(to.peel_to_commit()?.id()),
+                None => None,
+            };
+            match (from, to) {
+                // A...B, commits reachable from either side but not from both
+                (Some(from), Some(to)) if revspec.mode().contains(RevparseMode::MERGE_BASE) => {
+                    revwalk.push(from)?;
+                    revwalk.push(to)?;
+                    // criss-cross histories have several bases, all of them are excluded
+                    for base in repository.merge_bases(from, to)?.iter() {
+                        revwalk.hide(*base)?;
+                    }
+                }
+                (Some(from), Some(to)) if revspec.mode().contains(RevparseMode::RANGE) => {
+                    revwalk.push(to)?;
+                    revwalk.hide(from)?;
+                }
+                (Some(from), _) => revwalk.push(from)?,
+                (None, Some(to)) => revwalk.push(to)?,
+                (None, None) => {}
+            }
+
+            let verifier = self.get_verifier(config.verifier)?;
+            let mut checks = vec![];
+            for oid in revwalk {
+                let oid = oid?;
+                checks.push((oid, self.check_commit(verifier.as_ref(), oid)?));
+            }
+            return Ok(checks);
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+
+    fn check_commit(&self, verifier: &dyn Verifier, oid: Oid) -> Result<SignatureCheck, Error> {
+        let repository = self.get_repository()?;
+        match repository.extract_signature(&oid, None) {
+            Ok((signature, payload)) => verifier.verify(&payload, &signature),
+            Err(ref e) if e.code() == ErrorCode::NotFound => Ok(SignatureCheck::unsigned()),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn get_verifier(
+        &self,
+        verifier: Option<Box<dyn Verifier>>,
+    ) -> Result<Box<dyn Verifier>, Error> {
+        if let Some(verifier) = verifier {
+            return Ok(verifier);
+        }
+        let repository = self.get_repository()?;
+        let base = match repository.workdir() {
+            Some(workdir) => workdir,
+            None => repository.path(),
+        };
+        Ok(Box::new(ConfigVerifier::new(&repository.config()?, base)))
+    }
+}
+
+#[cfg(test)]
+mod verify_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::VerifyConfig;
+    use crate::{GitRepository, helpers::signing::SignatureStatus, helpers::test_util::git_output};
+
+    #[test]
+    fn verify_commits_and_tag_test() {
+        let dir_name = "./temp_test/verify/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository, a trusted and an untrusted throwaway ssh key
+        let path = env::current_dir().unwrap().join(dir_name);
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        for key in ["trusted_key", "untrusted_key"] {
+            let _ = Command::new("ssh-keygen")
+                .args(["-q", "-t", "ed25519", "-N", "", "-C", key, "-f"])
+                .arg(path.join(key))
+                .output()
+                .expect("ssh-keygen needs to be installed for signing tests");
+        }
+        let public_key = fs::read_to_string(path.join("trusted_key.pub")).unwrap();
+        fs::write(
+            path.join("allowed_signers"),
+            format!("signer@example.com {}", public_key),
+        )
+        .unwrap();
+        for (key, value) in [
+            ("user.name", "Signer"),
+            ("user.email", "signer@example.com"),
+            ("gpg.format", "ssh"),
+            ("gpg.ssh.allowedSignersFile", "allowed_signers"),
+        ] {
+            let _ = Command::new("git")
+                .args(["-C", dir_name, "config", key, value])
+                .output()
+                .unwrap();
+        }
+
+        // an unsigned commit, a signed commit and a signed tag, and a commit by an unknown key
+        let trusted = path.join("trusted_key");
+        let untrusted = path.join("untrusted_key");
+        let trusted = format!("user.signingkey={}", trusted.display());
+        let untrusted = format!("user.signingkey={}", untrusted.display());
+        git_output(&path, &["commit", "--allow-empty", "-m", "unsigned"]);
+        git_output(
+            &path,
+            &[
+                "-c",
+                &trusted,
+                "commit",
+                "-S",
+                "--allow-empty",
+                "-m",
+                "signed",
+            ],
+        );
+        git_output(
+            &path,
+            &["-c", &trusted, "tag", "-s", "-m", "signed tag", "v1"],
+        );
+        git_output(
+            &path,
+            &[
+                "-c",
+                &untrusted,
+                "commit",
+                "-S",
+                "--allow-empty",
+                "-m",
+                "unknown",
+            ],
+        );
+
+        // a side branch forking at the signed commit, for ranges
+        git_output(&path, &["branch", "side", "HEAD~1"]);
+        git_output(&path, &["checkout", "-q", "side"]);
+        git_output(&path, &["commit", "--allow-empty", "-m", "side"]);
+        git_output(&path, &["checkout", "-q", "-"]);
+        let rev_list = |range: &str| {
+            let out = git_output(&path, &["rev-list", range]);
+            String::from_utf8_lossy(&out.stdout).to_string()
+        };
+        let expected_range = rev_list("HEAD~2..HEAD");
+        let expected_symmetric = rev_list("HEAD...side");
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let commits = repo.verify_commits(VerifyConfig::new("HEAD")).unwrap();
+        let range = repo
+            .verify_commits(VerifyConfig::new("HEAD~2..HEAD"))
+            .unwrap();
+        let symmetric = repo
+            .verify_commits(VerifyConfig::new("HEAD...side"))
+            .unwrap();
+        let signed = repo.verify_commit(VerifyConfig::new("HEAD~1")).unwrap();
+        let tag = repo.verify_tag(VerifyConfig::new("v1")).unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        let statuses: Vec<SignatureStatus> = commits.iter().map(|(_, c)| c.status).collect();
+        assert_eq!(
+            statuses,
+            vec![
+                SignatureStatus::UnknownKey,
+                SignatureStatus::Good,
+                SignatureStatus::Unsigned
+            ]
+        );
+        let oids = |checks: &[(git2::Oid, crate::SignatureCheck)]| {
+            let mut oids: Vec<String> = checks.iter().map(|(oid, _)| oid.to_string()).collect();
+            oids.sort();
+            oids
+        };
+        let sorted = |expected: &str| {
+            let mut oids: Vec<String> = expected.lines().map(str::to_string).collect();
+            oids.sort();
+            oids
+        };
+        assert_eq!(range.len(), 2);
+        assert_eq!(oids(&range), sorted(&expected_range));
+        assert_eq!(symmetric.len(), 2);
+        assert_eq!(oids(&symmetric), sorted(&expected_symmetric));
+        assert_eq!(signed.signer.as_deref(), Some("signer@example.com"));
+        assert!(signed.is_good());
+        assert!(tag.is_good());
+    }
+
+    #[test]
+    fn verify_commits_criss_cross_test() {
+        let dir_name = "./temp_test/verify_criss_cross/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // two branches merging each other, so "a...b" has two merge bases
+        let path = env::current_dir().unwrap().join(dir_name);
+        for args in [
+            vec!["init", "-q", "-b", "a"],
+            vec!["config", "user.name", "Signer"],
+            vec!["config", "user.email", "signer@example.com"],
+            vec!["commit", "-q", "--allow-empty", "-m", "root"],
+            vec!["branch", "b"],
+            vec!["commit", "-q", "--allow-empty", "-m", "a1"],
+            vec!["checkout", "-q", "b"],
+            vec!["commit", "-q", "--allow-empty", "-m", "b1"],
+            vec!["checkout", "-q", "a"],
+            vec!["merge", "-q", "--no-ff", "-m", "a2", "b~0"],
+            vec!["checkout", "-q", "b"],
+            vec!["merge", "-q", "--no-ff", "-m", "b2", "a~1"],
+            vec!["checkout", "-q", "a"],
+            vec!["commit", "-q", "--allow-empty", "-m", "a3"],
+            vec!["checkout", "-q", "b"],
+            vec!["commit", "-q", "--allow-empty", "-m", "b3"],
+        ] {
+            git_output(&path, &args);
+        }
+        let bases = git_output(&path, &["merge-base", "--all", "a", "b"]);
+        let expected = git_output(&path, &["rev-list", "a...b"]);
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let symmetric = repo.verify_commits(VerifyConfig::new("a...b")).unwrap();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        let mut oids: Vec<String> = symmetric.iter().map(|(oid, _)| oid.to_string()).collect();
+        oids.sort();
+        let mut expected: Vec<String> = String::from_utf8_lossy(&expected.stdout)
+            .lines()
+            .map(str::to_string)
+            .collect();
+        expected.sort();
+        assert_eq!(String::from_utf8_lossy(&bases.stdout).lines().count(), 2);
+        assert_eq!(oids, expected);
+    }
+}
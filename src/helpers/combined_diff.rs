@@ -0,0 +1,693 @@
+use std::collections::HashMap;
+
+use git2::{Commit, DiffOptions, Error, Oid, Patch, Repository};
+
+/// Combined diff of a merge commit against all of its parents, same as `git show` prints for
+/// merges (`--cc`). Only files differing from every parent are listed, and hunks where the
+/// result matches one of the parents are left out.
+pub struct CombinedDiff<'repo> {
+    repository: &'repo Repository,
+    files: Vec<CombinedDiffFile>,
+}
+
+/// A file of a `CombinedDiff`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CombinedDiffFile {
+    pub path: String,
+    /// Blob of the merge result, zero if the file was deleted.
+    pub id: Oid,
+    /// Mode of the merge result, 0 if the file was deleted.
+    pub mode: u32,
+    /// Blob of each parent, zero if the parent does not have the file.
+    pub parent_ids: Vec<Oid>,
+    pub parent_modes: Vec<u32>,
+    pub binary: bool,
+    pub hunks: Vec<CombinedDiffHunk>,
+}
+
+/// A hunk of a `CombinedDiffFile`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CombinedDiffHunk {
+    /// Start line and number of lines in each parent.
+    pub parent_ranges: Vec<(usize, usize)>,
+    pub new_start: usize,
+    pub new_lines: usize,
+    /// Function context shown after the hunk header.
+    pub function: Option<String>,
+    pub lines: Vec<CombinedDiffLine>,
+}
+
+/// A line of a `CombinedDiffHunk`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CombinedDiffLine {
+    /// One column per parent: `-` if the line was removed from that parent, `+` if it was
+    /// added compared to it and ` ` otherwise.
+    pub markers: String,
+    /// Content of the line, without the newline.
+    pub content: String,
+}
+
+impl<'repo> CombinedDiff<'repo> {
+    /// Computes the combined diff of `commit` against its parents.
+    pub(crate) fn new(
+        repository: &'repo Repository,
+        commit: &Commit<'repo>,
+    ) -> Result<CombinedDiff<'repo>, Error> {
+        let tree = commit.tree()?;
+        let parents: Vec<Commit> = commit.parents().collect();
+        let context = repository
+            .config()?
+            .get_i32("diff.context")
+            .unwrap_or(3)
+            .max(0) as usize;
+
+        // paths changed compared to every parent, in the order of the first parent's diff
+        let mut changed: Vec<ChangedPath> = vec![];
+        for (index, parent) in parents.iter().enumerate() {
+            let diff = repository.diff_tree_to_tree(Some(&parent.tree()?), Some(&tree), None)?;
+            let mut entries = HashMap::new();
+            for delta in diff.deltas() {
+                let file = delta.new_file().path().or(delta.old_file().path());
+                let path = file.map(|p| p.to_string_lossy().to_string());
+                let entry = (
+                    delta.new_file().id(),
+                    u32::from(delta.new_file().mode()),
+                    delta.old_file().id(),
+                    u32::from(delta.old_file().mode()),
+                );
+                entries.insert(path.unwrap_or_default(), entry);
+            }
+            if index == 0 {
+                let mut paths: Vec<&String> = entries.keys().collect();
+                paths.sort();
+                for path in paths {
+                    let (id, mode, old_id, old_mode) = entries[path];
+                    changed.push((path.clone(), id, mode, vec![old_id], vec![old_mode]));
+                }
+            } else {
+                changed.retain_mut(|(path, _, _, parent_ids, parent_modes)| {
+                    match entries.get(path) {
+                        Some((_, _, old_id, old_mode)) => {
+                            parent_ids.push(*old_id);
+                            parent_modes.push(*old_mode);
+                            true
+                        }
+                        None => false,
+                    }
+                });
+            }
+        }
+
+        let content = |id: Oid| -> Result<Vec<u8>, Error> {
+            if id.is_zero() {
+                return Ok(vec![]);
+            }
+            Ok(repository.find_blob(id)?.content().to_vec())
+        };
+        let mut files = vec![];
+        for (path, id, mode, parent_ids, parent_modes) in changed {
+            let mut file = CombinedDiffFile {
+                path,
+                id,
+                mode,
+                parent_ids,
+                parent_modes,
+                binary: false,
+                hunks: vec![],
+            };
+            let result = content(id)?;
+            let parent_contents: Vec<Vec<u8>> = file
+                .parent_ids
+                .iter()
+                .map(|id| content(*id))
+                .collect::<Result<_, _>>()?;
+            let mode_differs = file.parent_modes.iter().any(|mode| *mode != file.mode);
+
+            file.binary = is_binary(&result) || parent_contents.iter().any(|c| is_binary(c));
+            if file.binary {
+                files.push(file);
+                continue;
+            }
+
+            let show_hunks = if file.mode == 0 {
+                false
+            } else {
+                let (show_hunks, hunks) =
+                    combine(&result, &parent_contents, &file.parent_ids, context)?;
+                file.hunks = hunks;
+                show_hunks
+            };
+            if show_hunks || mode_differs {
+                files.push(file);
+            }
+        }
+
+        Ok(CombinedDiff { repository, files })
+    }
+
+    pub fn files(&self) -> &Vec<CombinedDiffFile> {
+        &self.files
+    }
+
+    /// Renders the combined diff the way `git show` prints it for merges.
+    pub fn patch(&self) -> Result<String, Error> {
+        let abbrev = |id: Oid| -> Result<String, Error> {
+            if id.is_zero() {
+                return Ok("0".repeat(7));
+            }
+            let object = self.repository.find_object(id, None)?;
+            Ok(object.short_id()?.as_str().unwrap_or_default().to_string())
+        };
+
+        let mut out = String::new();
+        for file in &self.files {
+            out.push_str(&format!("diff --cc {}\n", file.path));
+            let parents: Vec<String> = file
+                .parent_ids
+                .iter()
+                .map(|id| abbrev(*id))
+                .collect::<Result<_, _>>()?;
+            out.push_str(&format!(
+                "index {}..{}\n",
+                parents.join(","),
+                abbrev(file.id)?
+            ));
+
+            let deleted = file.mode == 0;
+            // added if no parent had the file
+            let added = !deleted && file.parent_modes.iter().all(|mode| *mode == 0);
+            if file.parent_modes.iter().any(|mode| *mode != file.mode) {
+                if added {
+                    out.push_str(&format!("new file mode {:06o}\n", file.mode));
+                } else {
+                    if deleted {
+                        out.push_str("deleted file ");
+                    }
+                    let modes: Vec<String> = file
+                        .parent_modes
+                        .iter()
+                        .map(|mode| format!("{:06o}", mode))
+                        .collect();
+                    out.push_str(&format!("mode {}", modes.join(",")));
+                    if !deleted {
+                        out.push_str(&format!("..{:06o}", file.mode));
+                    }
+                    out.push('\n');
+                }
+            }
+
+            if file.binary {
+                out.push_str("Binary files differ\n");
+                continue;
+            }
+            if added {
+                out.push_str("--- /dev/null\n");
+            } else {
+                out.push_str(&format!("--- a/{}\n", file.path));
+            }
+            if deleted {
+                out.push_str("+++ /dev/null\n");
+            } else {
+                out.push_str(&format!("+++ b/{}\n", file.path));
+            }
+
+            let marker = "@".repeat(file.parent_ids.len() + 1);
+            for hunk in &file.hunks {
+                out.push_str(&marker);
+                for (start, lines) in &hunk.parent_ranges {
+                    out.push_str(&format!(" -{},{}", start, lines));
+                }
+                out.push_str(&format!(
+                    " +{},{} {}",
+                    hunk.new_start, hunk.new_lines, marker
+                ));
+                if let Some(function) = &hunk.function {
+                    out.push_str(&format!(" {}", function));
+                }
+                out.push('\n');
+                for line in &hunk.lines {
+                    out.push_str(&format!("{}{}\n", line.markers, line.content));
+                }
+            }
+        }
+        Ok(out)
+    }
+}
+
+/// Path, blob and mode of the result, blobs and modes of the parents.
+type ChangedPath = (String, Oid, u32, Vec<Oid>, Vec<u32>);
+
+/// Same check as git: a NUL byte within the first 8000 bytes.
+fn is_binary(content: &[u8]) -> bool {
+    content[..content.len().min(8000)].contains(&0)
+}
+
+/// A line removed from some of the parents, shown before the result line it hangs on.
+struct Lost {
+    line: Vec<u8>,
+    parents: u64,
+}
+
+/// A line of the merge result, with the lines lost before it. The lines are followed by one
+/// entry holding the lines lost at the end and one holding the parents' line counts.
+#[derive(Default)]
+struct Line<'a> {
+    content: &'a [u8],
+    // bit n: added compared to parent n, followed by the `mark` and `no_pre_delete` bits
+    flag: u64,
+    lost: Vec<Lost>,
+    // lines lost from the parent currently being compared
+    new_lost: Vec<Lost>,
+    // line number in each parent when a hunk starts at this line
+    parent_lines: Vec<usize>,
+}
+
+/// A port of git's `show_patch_diff`: compares the result with each parent and returns whether
+/// there is something to show and the hunks.
+fn combine(
+    result: &[u8],
+    parents: &[Vec<u8>],
+    parent_ids: &[Oid],
+    context: usize,
+) -> Result<(bool, Vec<CombinedDiffHunk>), Error> {
+    let num_parent = parents.len();
+    let mut lines: Vec<Line> = result
+        .split_inclusive(|b| *b == b'\n')
+        .map(|line| Line {
+            content: line.strip_suffix(b"\n").unwrap_or(line),
+            parent_lines: vec![0; num_parent],
+            ..Default::default()
+        })
+        .collect();
+    let cnt = lines.len();
+    for _ in 0..2 {
+        lines.push(Line {
+            parent_lines: vec![0; num_parent],
+            ..Default::default()
+        });
+    }
+
+    for n in 0..num_parent {
+        match (0..n).find(|j| parent_ids[*j] == parent_ids[n]) {
+            Some(j) => reuse_parent(&mut lines, cnt, n, j),
+            None => compare_parent(&mut lines, cnt, n, &parents[n], result)?,
+        }
+    }
+
+    let show_hunks = make_hunks(&mut lines, cnt, num_parent, context);
+    Ok((show_hunks, hunks(&lines, cnt, num_parent, context)))
+}
+
+/// Compares parent `n` with the result, marking added lines and collecting lost ones.
+fn compare_parent(
+    lines: &mut [Line],
+    cnt: usize,
+    n: usize,
+    parent: &[u8],
+    result: &[u8],
+) -> Result<(), Error> {
+    let nmask = 1u64 << n;
+    let mut options = DiffOptions::new();
+    options
+        .context_lines(0)
+        .indent_heuristic(true)
+        .force_text(true);
+    let patch = Patch::from_buffers(parent, None, result, None, Some(&mut options))?;
+    for hunk_index in 0..patch.num_hunks() {
+        let (hunk, count) = patch.hunk(hunk_index)?;
+        let new_start = hunk.new_start() as usize;
+        let mut lno = new_start;
+        // lines removed without adding any hang on the line after them
+        let bucket = if hunk.new_lines() == 0 {
+            new_start
+        } else {
+            new_start - 1
+        };
+        for line_index in 0..count {
+            let line = patch.line_in_hunk(hunk_index, line_index)?;
+            match line.origin() {
+                '-' => {
+                    let content = line.content();
+                    lines[bucket].new_lost.push(Lost {
+                        line: content.strip_suffix(b"\n").unwrap_or(content).to_vec(),
+                        parents: nmask,
+                    });
+                }
+                '+' => {
+                    lines[lno - 1].flag |= nmask;
+                    lno += 1;
+                }
+                _ => {}
+            }
+        }
+    }
+
+    // line numbers in the parent, merging the lost lines into those of earlier parents
+    let mut parent_line = 1;
+    for (lno, line) in lines.iter_mut().take(cnt + 1).enumerate() {
+        line.parent_lines[n] = parent_line;
+        if !line.new_lost.is_empty() {
+            let new_lost = std::mem::take(&mut line.new_lost);
+            let lost = std::mem::take(&mut line.lost);
+            line.lost = coalesce(lost, new_lost, n);
+        }
+        parent_line += line.lost.iter().filter(|l| l.parents & nmask != 0).count();
+        if lno < cnt && line.flag & nmask == 0 {
+            parent_line += 1;
+        }
+    }
+    lines[cnt + 1].parent_lines[n] = parent_line;
+    Ok(())
+}
+
+/// Parent `n` has the same blob as the already compared parent `j`.
+fn reuse_parent(lines: &mut [Line], cnt: usize, n: usize, j: usize) {
+    let (nmask, jmask) = (1u64 << n, 1u64 << j);
+    for line in lines.iter_mut().take(cnt + 1) {
+        line.parent_lines[n] = line.parent_lines[j];
+        for lost in line.lost.iter_mut() {
+            if lost.parents & jmask != 0 {
+                lost.parents |= nmask;
+            }
+        }
+        if line.flag & jmask != 0 {
+            line.flag |= nmask;
+        }
+    }
+    lines[cnt + 1].parent_lines[n] = lines[cnt + 1].parent_lines[j];
+}
+
+/// Merges the lines lost from parent `n` into the lines lost from earlier parents, lines in
+/// their longest common subsequence are lost from both.
+fn coalesce(base: Vec<Lost>, new: Vec<Lost>, n: usize) -> Vec<Lost> {
+    if base.is_empty() {
+        return new;
+    }
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Direction {
+        Match,
+        Base,
+        New,
+    }
+    let (base_len, new_len) = (base.len(), new.len());
+    let width = new_len + 1;
+    let mut lcs = vec![0usize; (base_len + 1) * width];
+    let mut directions = vec![Direction::Base; (base_len + 1) * width];
+    for direction in directions.iter_mut().take(width).skip(1) {
+        *direction = Direction::New;
+    }
+    for i in 1..=base_len {
+        for j in 1..=new_len {
+            let index = i * width + j;
+            if base[i - 1].line == new[j - 1].line {
+                lcs[index] = lcs[index - width - 1] + 1;
+                directions[index] = Direction::Match;
+            } else if lcs[index - 1] >= lcs[index - width] {
+                lcs[index] = lcs[index - 1];
+                directions[index] = Direction::New;
+            } else {
+                lcs[index] = lcs[index - width];
+                directions[index] = Direction::Base;
+            }
+        }
+    }
+
+    let mut base: Vec<Option<Lost>> = base.into_iter().map(Some).collect();
+    let mut new: Vec<Option<Lost>> = new.into_iter().map(Some).collect();
+    let mut merged = vec![];
+    let (mut i, mut j) = (base_len, new_len);
+    while i != 0 || j != 0 {
+        match directions[i * width + j] {
+            Direction::Match => {
+                let mut lost = base[i - 1].take().unwrap_or_else(|| unreachable!());
+                lost.parents |= 1 << n;
+                merged.push(lost);
+                i -= 1;
+                j -= 1;
+            }
+            Direction::New => {
+                merged.extend(new[j - 1].take());
+                j -= 1;
+            }
+            Direction::Base => {
+                merged.extend(base[i - 1].take());
+                i -= 1;
+            }
+        }
+    }
+    merged.reverse();
+    merged
+}
+
+fn interesting(line: &Line, all_mask: u64) -> bool {
+    line.flag & all_mask != 0 || !line.lost.is_empty()
+}
+
+/// `i` is the first uninteresting line, steps back if the hunk only ends with lost lines as
+/// their line is shown anyway.
+fn adjust_hunk_tail(lines: &[Line], all_mask: u64, hunk_begin: usize, i: usize) -> usize {
+    if hunk_begin < i && lines[i - 1].flag & all_mask == 0 {
+        return i - 1;
+    }
+    i
+}
+
+fn find_next(lines: &[Line], mark: u64, mut i: usize, cnt: usize, uninteresting: bool) -> usize {
+    while i <= cnt {
+        if (lines[i].flag & mark == 0) == uninteresting {
+            return i;
+        }
+        i += 1;
+    }
+    i
+}
+
+/// Marks the lines to show, leaving out hunks where the result matches one of the parents.
+fn make_hunks(lines: &mut [Line], cnt: usize, num_parent: usize, context: usize) -> bool {
+    let all_mask = (1u64 << num_parent) - 1;
+    let mark = 1u64 << num_parent;
+    for line in lines.iter_mut().take(cnt + 1) {
+        if interesting(line, all_mask) {
+            line.flag |= mark;
+        } else {
+            line.flag &= !mark;
+        }
+    }
+
+    let mut i = 0;
+    while i <= cnt {
+        while i <= cnt && lines[i].flag & mark == 0 {
+            i += 1;
+        }
+        if cnt < i {
+            break;
+        }
+        let hunk_begin = i;
+        let mut j = i + 1;
+        while j <= cnt {
+            if lines[j].flag & mark == 0 {
+                // continue the hunk if there is an interesting line within the context
+                let mut lookahead = adjust_hunk_tail(lines, all_mask, hunk_begin, j);
+                lookahead = (lookahead + context).min(cnt + 1);
+                let mut contin = false;
+                while lookahead > 0 {
+                    lookahead -= 1;
+                    if j > lookahead {
+                        break;
+                    }
+                    if lines[lookahead].flag & mark != 0 {
+                        contin = true;
+                        break;
+                    }
+                }
+                if !contin {
+                    break;
+                }
+                j = lookahead;
+            }
+            j += 1;
+        }
+        let hunk_end = j;
+
+        // the hunk is only interesting if there are more than two versions, or the result
+        // matches none of the parents
+        let mut same_diff = 0;
+        let mut has_interesting = false;
+        'lines: for line in &lines[i..hunk_end] {
+            let this_diff = line.flag & all_mask;
+            if this_diff != 0 {
+                if same_diff == 0 {
+                    same_diff = this_diff;
+                } else if same_diff != this_diff {
+                    has_interesting = true;
+                    break;
+                }
+            }
+            for lost in &line.lost {
+                if same_diff == 0 {
+                    same_diff = lost.parents;
+                } else if same_diff != lost.parents {
+                    has_interesting = true;
+                    break 'lines;
+                }
+            }
+        }
+        if !has_interesting && same_diff != all_mask {
+            for line in &mut lines[hunk_begin..hunk_end] {
+                line.flag &= !mark;
+            }
+        }
+        i = hunk_end;
+    }
+
+    give_context(lines, cnt, num_parent, context)
+}
+
+/// Marks the context lines around the interesting ones, joining groups with small gaps.
+fn give_context(lines: &mut [Line], cnt: usize, num_parent: usize, context: usize) -> bool {
+    let all_mask = (1u64 << num_parent) - 1;
+    let mark = 1u64 << num_parent;
+    let no_pre_delete = 2u64 << num_parent;
+
+    let mut i = find_next(lines, mark, 0, cnt, false);
+    if cnt < i {
+        return false;
+    }
+    'outer: while i <= cnt {
+        // a few lines before the first interesting line
+        let mut j = i.saturating_sub(context);
+        while j < i {
+            if lines[j].flag & mark == 0 {
+                lines[j].flag |= no_pre_delete;
+            }
+            lines[j].flag |= mark;
+            j += 1;
+        }
+
+        loop {
+            let mut j = find_next(lines, mark, i, cnt, true);
+            if cnt < j {
+                // the rest are all interesting
+                break 'outer;
+            }
+            let k = find_next(lines, mark, j, cnt, false);
+            j = adjust_hunk_tail(lines, all_mask, i, j);
+            if k < j + context {
+                // small gap to the next interesting line
+                while j < k {
+                    lines[j].flag |= mark;
+                    j += 1;
+                }
+                i = k;
+                continue;
+            }
+
+            // trailing context
+            i = k;
+            let k = (j + context).min(cnt + 1);
+            while j < k {
+                lines[j].flag |= mark;
+                j += 1;
+            }
+            break;
+        }
+    }
+    true
+}
+
+/// Collects the marked lines into hunks, a port of git's `dump_sline`.
+fn hunks(lines: &[Line], cnt: usize, num_parent: usize, context: usize) -> Vec<CombinedDiffHunk> {
+    let mark = 1u64 << num_parent;
+    let no_pre_delete = 2u64 << num_parent;
+    let mut hunks = vec![];
+    let mut lno = 0;
+    loop {
+        let mut hunk_comment: Option<&[u8]> = None;
+        while lno <= cnt && lines[lno].flag & mark == 0 {
+            let first = lines[lno].content.first();
+            if lno < cnt && first.is_some_and(|c| c.is_ascii_alphabetic() || b"_$".contains(c)) {
+                hunk_comment = Some(lines[lno].content);
+            }
+            lno += 1;
+        }
+        if cnt < lno {
+            break;
+        }
+        let mut hunk_end = lno + 1;
+        while hunk_end <= cnt && lines[hunk_end].flag & mark != 0 {
+            hunk_end += 1;
+        }
+        let mut new_lines = hunk_end - lno;
+        if cnt < hunk_end {
+            // pointing at the last lost lines
+            new_lines -= 1;
+        }
+        let mut null_context = 0;
+        if context == 0 {
+            null_context = (lno..hunk_end)
+                .filter(|j| lines[*j].flag & (mark - 1) == 0)
+                .count();
+            new_lines -= null_context;
+        }
+
+        let parent_ranges = (0..num_parent)
+            .map(|n| {
+                let start = lines[lno].parent_lines[n];
+                let end = lines[hunk_end].parent_lines[n];
+                (start, end - start - null_context)
+            })
+            .collect();
+        // git shows the first 40 bytes of the line, up to its last non-space character
+        let function = hunk_comment.and_then(|comment| {
+            let comment = &comment[..comment.len().min(40)];
+            let end = comment.iter().rposition(|c| !c.is_ascii_whitespace())?;
+            (end > 0).then(|| String::from_utf8_lossy(&comment[..end]).to_string())
+        });
+        let mut hunk = CombinedDiffHunk {
+            parent_ranges,
+            new_start: lno + 1,
+            new_lines,
+            function,
+            lines: vec![],
+        };
+
+        while lno < hunk_end {
+            let line = &lines[lno];
+            lno += 1;
+            if line.flag & no_pre_delete == 0 {
+                for lost in &line.lost {
+                    let markers = (0..num_parent)
+                        .map(|n| {
+                            if lost.parents & (1 << n) != 0 {
+                                '-'
+                            } else {
+                                ' '
+                            }
+                        })
+                        .collect();
+                    hunk.lines.push(CombinedDiffLine {
+                        markers,
+                        content: String::from_utf8_lossy(&lost.line).to_string(),
+                    });
+                }
+            }
+            if cnt < lno {
+                break;
+            }
+            if line.flag & (mark - 1) == 0 && context == 0 {
+                // only here to hang the lost lines on
+                continue;
+            }
+            let markers = (0..num_parent)
+                .map(|n| if line.flag & (1 << n) != 0 { '+' } else { ' ' })
+                .collect();
+            hunk.lines.push(CombinedDiffLine {
+                markers,
+                content: String::from_utf8_lossy(line.content).to_string(),
+            });
+        }
+        hunks.push(hunk);
+    }
+    hunks
+}
@@ -0,0 +1,376 @@
+use git2::{Error, Signature, Time};
+
+const MONTHS: [&str; 12] = [
+    "Jan", "Feb", "Mar", "Apr", "May", "Jun", "Jul", "Aug", "Sep", "Oct", "Nov", "Dec",
+];
+
+const WEEKDAYS: [&str; 7] = ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"];
+
+/// Parses a date the way `git commit --date` does. Supported formats are:
+/// - git internal format: `<unix timestamp> <time zone offset>` or `@<unix timestamp>`
+/// - ISO 8601: `2005-04-07T22:13:13`, optionally followed by `Z` or an offset like `+02:00`
+/// - RFC 2822: `Thu, 07 Apr 2005 22:13:13 +0200`
+/// - relative dates: `now`, `yesterday` or `<n> <unit> ago`, e.g. `2 weeks ago`
+///
+/// Dates without a time zone are interpreted in the local time zone, dates without a time are
+/// interpreted as midnight.
+pub(crate) fn parse_date(date: &str) -> Result<Time, Error> {
+    let date = date.trim();
+    let error = || Error::from_str(&format!("invalid date format: {}", date));
+
+    // @<timestamp>
+    if let Some(timestamp) = date.strip_prefix('@') {
+        let seconds = timestamp.trim().parse::<i64>().map_err(|_| error())?;
+        return Ok(Time::new(seconds, 0));
+    }
+
+    // <timestamp> <offset>
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() == 2 && parts[0].chars().all(|c| c.is_ascii_digit()) {
+        let seconds = parts[0].parse::<i64>().map_err(|_| error())?;
+        let offset = parse_offset(parts[1]).ok_or_else(error)?;
+        return Ok(Time::new(seconds, offset));
+    }
+
+    if let Some(time) = parse_iso(date) {
+        return Ok(time);
+    }
+
+    if let Some(time) = parse_rfc2822(date) {
+        return Ok(time);
+    }
+
+    if let Some(time) = parse_relative(date) {
+        return Ok(time);
+    }
+
+    Err(error())
+}
+
+/// Returns the offset of the local time zone in minutes.
+pub(crate) fn local_offset() -> i32 {
+    match Signature::now("gittwo", "gittwo") {
+        Ok(signature) => signature.when().offset_minutes(),
+        Err(_) => 0,
+    }
+}
+
+/// Returns the current unix timestamp.
+pub(crate) fn now() -> i64 {
+    match std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH) {
+        Ok(duration) => duration.as_secs() as i64,
+        Err(_) => 0,
+    }
+}
+
+/// Formats a time the way git does by default, e.g. `Thu Apr 7 22:13:13 2005 +0200`.
+pub(crate) fn format_default(time: &Time) -> String {
+    let (year, month, day, hour, minute, second, weekday) = broken_down(time);
+    format!(
+        "{} {} {} {:02}:{:02}:{:02} {} {}",
+        WEEKDAYS[weekday],
+        MONTHS[month - 1],
+        day,
+        hour,
+        minute,
+        second,
+        year,
+        format_offset(time.offset_minutes(), false)
+    )
+}
+
+/// Formats a time as `2005-04-07 22:13:13 +0200`, same as `--date=iso`.
+pub(crate) fn format_iso(time: &Time) -> String {
+    let (year, month, day, hour, minute, second, _) = broken_down(time);
+    format!(
+        "{:04}-{:02}-{:02} {:02}:{:02}:{:02} {}",
+        year,
+        month,
+        day,
+        hour,
+        minute,
+        second,
+        format_offset(time.offset_minutes(), false)
+    )
+}
+
+/// Formats a time as `2005-04-07T22:13:13+02:00`, same as `--date=iso-strict`.
+pub(crate) fn format_iso_strict(time: &Time) -> String {
+    let (year, month, day, hour, minute, second, _) = broken_down(time);
+    let offset = if time.offset_minutes() == 0 {
+        "Z".to_string()
+    } else {
+        format_offset(time.offset_minutes(), true)
+    };
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}{}",
+        year, month, day, hour, minute, second, offset
+    )
+}
+
+/// Formats a time as `Thu, 7 Apr 2005 22:13:13 +0200`, same as `--date=rfc`.
+pub(crate) fn format_rfc2822(time: &Time) -> String {
+    let (year, month, day, hour, minute, second, weekday) = broken_down(time);
+    format!(
+        "{}, {} {} {} {:02}:{:02}:{:02} {}",
+        WEEKDAYS[weekday],
+        day,
+        MONTHS[month - 1],
+        year,
+        hour,
+        minute,
+        second,
+        format_offset(time.offset_minutes(), false)
+    )
+}
+
+/// Formats a time as `2005-04-07`, same as `--date=short`.
+pub(crate) fn format_short(time: &Time) -> String {
+    let (year, month, day, _, _, _, _) = broken_down(time);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Formats a time relative to `now`, e.g. `3 days ago`, same as `--date=relative`.
+pub(crate) fn format_relative(time: &Time, now: i64) -> String {
+    let seconds = time.seconds();
+    if seconds > now {
+        return "in the future".to_string();
+    }
+    let diff = (now - seconds) as u64;
+    if diff < 90 {
+        return plural(diff, "second");
+    }
+    let diff = (diff + 30) / 60;
+    if diff < 90 {
+        return plural(diff, "minute");
+    }
+    let diff = (diff + 30) / 60;
+    if diff < 36 {
+        return plural(diff, "hour");
+    }
+    let diff = (diff + 12) / 24;
+    if diff < 14 {
+        return plural(diff, "day");
+    }
+    if diff < 70 {
+        return plural((diff + 3) / 7, "week");
+    }
+    if diff < 365 {
+        return plural((diff + 15) / 30, "month");
+    }
+    if diff < 1825 {
+        let total_months = (diff * 12 * 2 + 365) / (365 * 2);
+        let years = total_months / 12;
+        let months = total_months % 12;
+        if months > 0 {
+            let years = if years == 1 {
+                "1 year".to_string()
+            } else {
+                format!("{} years", years)
+            };
+            let months = if months == 1 {
+                "1 month".to_string()
+            } else {
+                format!("{} months", months)
+            };
+            return format!("{}, {} ago", years, months);
+        }
+        return plural(years, "year");
+    }
+    plural((diff + 183) / 365, "year")
+}
+
+fn plural(count: u64, unit: &str) -> String {
+    if count == 1 {
+        format!("{} {} ago", count, unit)
+    } else {
+        format!("{} {}s ago", count, unit)
+    }
+}
+
+/// Formats a time zone offset in minutes as `+0200` or, with `colon` set, `+02:00`.
+pub(crate) fn format_offset(offset: i32, colon: bool) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    if colon {
+        format!("{}{:02}:{:02}", sign, offset / 60, offset % 60)
+    } else {
+        format!("{}{:02}{:02}", sign, offset / 60, offset % 60)
+    }
+}
+
+/// Splits a time into (year, month, day, hour, minute, second, weekday) in its own time zone.
+fn broken_down(time: &Time) -> (i64, usize, u32, u32, u32, u32, usize) {
+    let local = time.seconds() + time.offset_minutes() as i64 * 60;
+    let days = local.div_euclid(86_400);
+    let secs = local.rem_euclid(86_400);
+    let (year, month, day) = civil_from_days(days);
+    // 1970-01-01 was a thursday
+    let weekday = (days + 4).rem_euclid(7) as usize;
+    (
+        year,
+        month as usize,
+        day,
+        (secs / 3600) as u32,
+        ((secs % 3600) / 60) as u32,
+        (secs % 60) as u32,
+        weekday,
+    )
+}
+
+/// Number of days since the unix epoch for a given date in the proleptic gregorian calendar.
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year - era * 400;
+    let month = month as i64;
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146_097 + doe - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+/// Parses `+hhmm`, `-hhmm`, `+hh:mm`, `-hh:mm` and `Z` into minutes.
+fn parse_offset(offset: &str) -> Option<i32> {
+    if offset == "Z" || offset == "z" {
+        return Some(0);
+    }
+    let (sign, rest) = match offset.split_at_checked(1)? {
+        ("+", rest) => (1, rest),
+        ("-", rest) => (-1, rest),
+        _ => return None,
+    };
+    let rest = rest.replace(':', "");
+    if rest.len() != 4 || !rest.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let hours: i32 = rest[..2].parse().ok()?;
+    let minutes: i32 = rest[2..].parse().ok()?;
+    Some(sign * (hours * 60 + minutes))
+}
+
+/// Parses `HH:MM[:SS]` into seconds since midnight.
+fn parse_clock(clock: &str) -> Option<i64> {
+    let parts: Vec<&str> = clock.split(':').collect();
+    if parts.len() < 2 || parts.len() > 3 {
+        return None;
+    }
+    let hour: i64 = parts[0].parse().ok()?;
+    let minute: i64 = parts[1].parse().ok()?;
+    let second: i64 = match parts.get(2) {
+        Some(second) => second.split('.').next()?.parse().ok()?,
+        None => 0,
+    };
+    if hour > 23 || minute > 59 || second > 60 {
+        return None;
+    }
+    Some(hour * 3600 + minute * 60 + second)
+}
+
+fn to_time(year: i64, month: u32, day: u32, clock: i64, offset: Option<i32>) -> Option<Time> {
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+    let offset = offset.unwrap_or_else(local_offset);
+    let local = days_from_civil(year, month, day) * 86_400 + clock;
+    Some(Time::new(local - offset as i64 * 60, offset))
+}
+
+fn parse_iso(date: &str) -> Option<Time> {
+    if date.len() < 10 {
+        return None;
+    }
+    let (day_part, rest) = date.split_at_checked(10)?;
+    let fields: Vec<&str> = day_part.split('-').collect();
+    if fields.len() != 3 {
+        return None;
+    }
+    let year: i64 = fields[0].parse().ok()?;
+    let month: u32 = fields[1].parse().ok()?;
+    let day: u32 = fields[2].parse().ok()?;
+
+    let rest = rest.trim_start_matches(['T', ' ']);
+    if rest.is_empty() {
+        return to_time(year, month, day, 0, None);
+    }
+
+    // split the clock from the time zone
+    let zone_start = rest.find(['Z', 'z', '+', '-', ' ']);
+    let (clock, zone) = match zone_start {
+        Some(index) => (&rest[..index], rest[index..].trim()),
+        None => (rest, ""),
+    };
+    let clock = parse_clock(clock)?;
+    let offset = if zone.is_empty() {
+        None
+    } else {
+        Some(parse_offset(zone)?)
+    };
+    to_time(year, month, day, clock, offset)
+}
+
+fn parse_rfc2822(date: &str) -> Option<Time> {
+    let date = match date.split_once(',') {
+        Some((_, rest)) => rest,
+        None => date,
+    };
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    if parts.len() < 4 || parts.len() > 5 {
+        return None;
+    }
+    let day: u32 = parts[0].parse().ok()?;
+    let month = MONTHS
+        .iter()
+        .position(|m| m.eq_ignore_ascii_case(parts[1]))? as u32
+        + 1;
+    let year: i64 = parts[2].parse().ok()?;
+    let clock = parse_clock(parts[3])?;
+    let offset = match parts.get(4) {
+        Some(offset) => Some(parse_offset(offset)?),
+        None => None,
+    };
+    to_time(year, month, day, clock, offset)
+}
+
+/// Parses `now`, `yesterday` and `<n> <unit> ago` (units from seconds to years, singular or
+/// plural, optionally joined with dots like `2.weeks.ago`) relative to the current time.
+fn parse_relative(date: &str) -> Option<Time> {
+    let date = date.to_ascii_lowercase().replace('.', " ");
+    let parts: Vec<&str> = date.split_whitespace().collect();
+    let seconds = match parts.as_slice() {
+        ["now"] => 0,
+        ["yesterday"] => 86_400,
+        [count, unit, "ago"] => {
+            let count: i64 = count.parse().ok()?;
+            let unit = match unit.strip_suffix('s') {
+                Some(unit) => unit,
+                None => unit,
+            };
+            let unit = match unit {
+                "second" | "sec" => 1,
+                "minute" | "min" => 60,
+                "hour" => 3_600,
+                "day" => 86_400,
+                "week" => 604_800,
+                "month" => 2_592_000,
+                "year" => 31_536_000,
+                _ => return None,
+            };
+            count * unit
+        }
+        _ => return None,
+    };
+    Some(Time::new(now() - seconds, local_offset()))
+}
@@ -0,0 +1,760 @@
+use std::fmt::Display;
+
+use git2::Oid;
+
+/// Kind of a line of graph output, following the states of git's graph layout.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphLineKind {
+    /// Branch lines continuing unchanged, used for the lines of a commit after the first.
+    Padding,
+
+    /// Extra rows making room for an octopus merge before its commit line.
+    PreCommit,
+
+    /// The row holding the commit itself.
+    Commit,
+
+    /// The row after a merge, with an edge leading to each parent.
+    PostMerge,
+
+    /// Rows moving branch lines to the left after lanes were freed.
+    Collapsing,
+}
+
+/// A single character of graph output.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphCell {
+    /// One of `*`, `|`, `/`, `\`, `_`, `-`, `.` or a space.
+    pub ch: char,
+
+    /// The commit the edge is heading to, i.e. the commit of the lane it belongs to. `None`
+    /// for spaces.
+    pub commit: Option<Oid>,
+}
+
+/// A line of graph output.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GraphLine {
+    pub kind: GraphLineKind,
+    pub cells: Vec<GraphCell>,
+}
+
+impl Display for GraphLine {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for cell in &self.cells {
+            write!(f, "{}", cell.ch)?;
+        }
+        Ok(())
+    }
+}
+
+/// The graph output of a single commit, as returned by `GraphLayout::next`.
+#[derive(Clone, Debug)]
+pub struct GraphEntry {
+    pub commit: Oid,
+
+    /// Lane (column) of the commit.
+    pub lane: usize,
+
+    /// Commits each lane is heading to after this commit, i.e. the lanes of the next row.
+    pub lanes: Vec<Oid>,
+
+    /// Line output between this and the previous commit when separators are enabled.
+    pub separator: Option<GraphLine>,
+
+    /// All lines of this commit, the line at `commit_line` holds the commit.
+    pub lines: Vec<GraphLine>,
+
+    /// Index of the commit line in `lines`.
+    pub commit_line: usize,
+
+    /// Line used for further lines of the commit message once `lines` are used up.
+    pub padding: GraphLine,
+}
+
+impl GraphEntry {
+    /// Prefixes the lines of `text` with the graph, the first line of text goes on the commit
+    /// line. Every line of the result ends with a newline.
+    pub fn render(&self, text: &str) -> String {
+        let mut out = String::new();
+        if let Some(separator) = &self.separator {
+            out.push_str(&format!("{}\n", separator));
+        }
+        for line in &self.lines[..self.commit_line] {
+            out.push_str(&format!("{}\n", line));
+        }
+
+        let mut graph = self.lines[self.commit_line..].iter();
+        let mut text = text.lines();
+        loop {
+            match (graph.next(), text.next()) {
+                (Some(line), Some(text)) => out.push_str(&format!("{}{}\n", line, text)),
+                (Some(line), None) => out.push_str(&format!("{}\n", line)),
+                (None, Some(text)) => out.push_str(&format!("{}{}\n", self.padding, text)),
+                (None, None) => break,
+            }
+        }
+        out
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum State {
+    Padding,
+    PreCommit,
+    Commit,
+    PostMerge,
+    Collapsing,
+}
+
+const MERGE_CHARS: [char; 3] = ['/', '|', '\\'];
+
+/// Assigns lanes and edge characters to commits the way `git log --graph` does.
+///
+/// Commits have to be passed in the order they are output, together with their parents that
+/// are output as well.
+pub struct GraphLayout {
+    commit: Option<Oid>,
+    parents: Vec<Oid>,
+    width: usize,
+    expansion_row: usize,
+    state: State,
+    prev_state: State,
+    commit_index: usize,
+    prev_commit_index: usize,
+    merge_layout: i32,
+    edges_added: i32,
+    prev_edges_added: i32,
+    columns: Vec<Oid>,
+    new_columns: Vec<Oid>,
+    // screen column -> lane of new_columns, -1 for none
+    mapping: Vec<i32>,
+    old_mapping: Vec<i32>,
+    mapping_size: usize,
+    separators: bool,
+}
+
+impl Default for GraphLayout {
+    fn default() -> Self {
+        GraphLayout::new()
+    }
+}
+
+/// Writes characters into a line while tracking its width.
+struct LineBuilder {
+    kind: GraphLineKind,
+    cells: Vec<GraphCell>,
+}
+
+impl LineBuilder {
+    fn new(kind: GraphLineKind) -> Self {
+        LineBuilder {
+            kind,
+            cells: vec![],
+        }
+    }
+
+    fn column(&mut self, commit: Oid, ch: char) {
+        self.cells.push(GraphCell {
+            ch,
+            commit: Some(commit),
+        });
+    }
+
+    fn space(&mut self, count: usize) {
+        for _ in 0..count {
+            self.cells.push(GraphCell {
+                ch: ' ',
+                commit: None,
+            });
+        }
+    }
+
+    fn finish(mut self, width: usize) -> GraphLine {
+        if self.cells.len() < width {
+            self.space(width - self.cells.len());
+        }
+        GraphLine {
+            kind: self.kind,
+            cells: self.cells,
+        }
+    }
+}
+
+impl GraphLayout {
+    pub fn new() -> Self {
+        GraphLayout {
+            commit: None,
+            parents: vec![],
+            width: 0,
+            expansion_row: 0,
+            state: State::Padding,
+            prev_state: State::Padding,
+            commit_index: 0,
+            prev_commit_index: 0,
+            merge_layout: 0,
+            edges_added: 0,
+            prev_edges_added: 0,
+            columns: vec![],
+            new_columns: vec![],
+            mapping: vec![],
+            old_mapping: vec![],
+            mapping_size: 0,
+            separators: false,
+        }
+    }
+
+    /// Adds a separator line between commits, like the blank line `git log` puts between
+    /// commits of the multi-line formats.
+    pub fn separators(&mut self, separators: bool) -> &mut Self {
+        self.separators = separators;
+        self
+    }
+
+    /// Lays out the next commit. `parents` are the parents of the commit that are part of the
+    /// output, all other parents are left out of the graph.
+    pub fn next(&mut self, commit: Oid, parents: &[Oid]) -> GraphEntry {
+        let first = self.commit.is_none();
+        self.update(commit, parents);
+
+        let separator = if self.separators && !first {
+            Some(self.separator_line())
+        } else {
+            None
+        };
+
+        let mut lines = vec![];
+        let commit_line = loop {
+            let kind = self.state;
+            lines.push(self.next_line());
+            if kind == State::Commit {
+                break lines.len() - 1;
+            }
+        };
+        while self.state != State::Padding {
+            lines.push(self.next_line());
+        }
+
+        GraphEntry {
+            commit,
+            lane: self.commit_index,
+            lanes: self.new_columns.clone(),
+            separator,
+            lines,
+            commit_line,
+            padding: self.padding_line(),
+        }
+    }
+
+    fn update(&mut self, commit: Oid, parents: &[Oid]) {
+        self.commit = Some(commit);
+        self.parents = vec![];
+        for parent in parents {
+            if !self.parents.contains(parent) {
+                self.parents.push(*parent);
+            }
+        }
+        self.prev_commit_index = self.commit_index;
+        self.update_columns();
+        self.expansion_row = 0;
+
+        self.state = if self.needs_pre_commit_line() {
+            State::PreCommit
+        } else {
+            State::Commit
+        };
+    }
+
+    fn num_parents(&self) -> usize {
+        self.parents.len()
+    }
+
+    fn update_columns(&mut self) {
+        std::mem::swap(&mut self.columns, &mut self.new_columns);
+        self.new_columns.clear();
+
+        let max_new_columns = self.columns.len() + self.num_parents();
+        if self.mapping.len() < 2 * max_new_columns {
+            self.mapping.resize(2 * max_new_columns, -1);
+            self.old_mapping.resize(2 * max_new_columns, -1);
+        }
+        self.mapping_size = 2 * max_new_columns;
+        for i in 0..self.mapping_size {
+            self.mapping[i] = -1;
+        }
+
+        self.width = 0;
+        self.prev_edges_added = self.edges_added;
+        self.edges_added = 0;
+
+        let commit = self.commit.unwrap_or(Oid::zero());
+        let mut seen_this = false;
+        for i in 0..=self.columns.len() {
+            let col_commit = if i == self.columns.len() {
+                if seen_this {
+                    break;
+                }
+                commit
+            } else {
+                self.columns[i]
+            };
+
+            if col_commit == commit {
+                seen_this = true;
+                self.commit_index = i;
+                self.merge_layout = -1;
+                for parent in self.parents.clone() {
+                    self.insert_into_new_columns(parent, i as i32);
+                }
+                // the commit always takes up at least 2 spaces
+                if self.num_parents() == 0 {
+                    self.width += 2;
+                }
+            } else {
+                self.insert_into_new_columns(col_commit, -1);
+            }
+        }
+
+        while self.mapping_size > 1 && self.mapping[self.mapping_size - 1] < 0 {
+            self.mapping_size -= 1;
+        }
+    }
+
+    fn insert_into_new_columns(&mut self, commit: Oid, idx: i32) {
+        let i = match self.new_columns.iter().position(|c| *c == commit) {
+            Some(i) => i as i32,
+            None => {
+                self.new_columns.push(commit);
+                self.new_columns.len() as i32 - 1
+            }
+        };
+
+        let mapping_idx;
+        if self.num_parents() > 1 && idx > -1 && self.merge_layout == -1 {
+            // first parent of a merge, the layout depends on whether the parent is in a lane
+            // to the left of the merge
+            let dist = idx - i;
+            let shift = if dist > 1 { 2 * dist - 3 } else { 1 };
+
+            self.merge_layout = if dist > 0 { 0 } else { 1 };
+            self.edges_added = self.num_parents() as i32 + self.merge_layout - 2;
+
+            mapping_idx = self.width as i32 + (self.merge_layout - 1) * shift;
+            self.width += 2 * self.merge_layout as usize;
+        } else if self.edges_added > 0 && self.width >= 2 && i == self.mapping[self.width - 2] {
+            // the commit is in the last existing lane, let the two edges join immediately
+            mapping_idx = self.width as i32 - 2;
+            self.edges_added = -1;
+        } else {
+            mapping_idx = self.width as i32;
+            self.width += 2;
+        }
+
+        if mapping_idx >= 0 {
+            let mapping_idx = mapping_idx as usize;
+            if mapping_idx >= self.mapping.len() {
+                self.mapping.resize(mapping_idx + 1, -1);
+                self.old_mapping.resize(mapping_idx + 1, -1);
+            }
+            self.mapping[mapping_idx] = i;
+        }
+    }
+
+    fn num_dashed_parents(&self) -> i32 {
+        self.num_parents() as i32 + self.merge_layout - 3
+    }
+
+    fn needs_pre_commit_line(&self) -> bool {
+        self.num_parents() >= 3
+            && self.commit_index + 1 < self.columns.len()
+            && (self.expansion_row as i32) < self.num_dashed_parents() * 2
+    }
+
+    fn is_mapping_correct(&self) -> bool {
+        (0..self.mapping_size).all(|i| {
+            let target = self.mapping[i];
+            target < 0 || target as usize == i / 2
+        })
+    }
+
+    fn update_state(&mut self, state: State) {
+        self.prev_state = self.state;
+        self.state = state;
+    }
+
+    fn next_line(&mut self) -> GraphLine {
+        let line = match self.state {
+            State::Padding => return self.padding_line(),
+            State::PreCommit => self.pre_commit_line(),
+            State::Commit => self.commit_line(),
+            State::PostMerge => self.post_merge_line(),
+            State::Collapsing => self.collapsing_line(),
+        };
+        line.finish(self.width)
+    }
+
+    fn padding_line(&self) -> GraphLine {
+        let mut line = LineBuilder::new(GraphLineKind::Padding);
+        for column in &self.new_columns {
+            line.column(*column, '|');
+            line.space(1);
+        }
+        line.finish(self.width)
+    }
+
+    /// The line between two commits, output after the layout of the next commit is updated.
+    fn separator_line(&mut self) -> GraphLine {
+        if self.state != State::Commit {
+            return self.next_line();
+        }
+
+        let commit = self.commit.unwrap_or(Oid::zero());
+        let mut line = LineBuilder::new(GraphLineKind::Padding);
+        for column in &self.columns {
+            line.column(*column, '|');
+            if *column == commit && self.num_parents() > 2 {
+                line.space((self.num_parents() - 2) * 2);
+            } else {
+                line.space(1);
+            }
+        }
+        self.prev_state = State::Padding;
+        line.finish(self.width)
+    }
+
+    fn pre_commit_line(&mut self) -> LineBuilder {
+        let commit = self.commit.unwrap_or(Oid::zero());
+        let mut line = LineBuilder::new(GraphLineKind::PreCommit);
+        let mut seen_this = false;
+        for (i, column) in self.columns.iter().enumerate() {
+            if *column == commit {
+                seen_this = true;
+                line.column(*column, '|');
+                line.space(self.expansion_row);
+            } else if seen_this && self.expansion_row == 0 {
+                // continue the '\' edges of a merge right before this commit
+                if self.prev_state == State::PostMerge && self.prev_commit_index < i {
+                    line.column(*column, '\\');
+                } else {
+                    line.column(*column, '|');
+                }
+            } else if seen_this && self.expansion_row > 0 {
+                line.column(*column, '\\');
+            } else {
+                line.column(*column, '|');
+            }
+            line.space(1);
+        }
+
+        self.expansion_row += 1;
+        if !self.needs_pre_commit_line() {
+            self.update_state(State::Commit);
+        }
+        line
+    }
+
+    fn commit_line(&mut self) -> LineBuilder {
+        let commit = self.commit.unwrap_or(Oid::zero());
+        let mut line = LineBuilder::new(GraphLineKind::Commit);
+        let mut seen_this = false;
+        for i in 0..=self.columns.len() {
+            let col_commit = if i == self.columns.len() {
+                if seen_this {
+                    break;
+                }
+                commit
+            } else {
+                self.columns[i]
+            };
+
+            if col_commit == commit {
+                seen_this = true;
+                line.column(commit, '*');
+                if self.num_parents() > 2 {
+                    self.draw_octopus_merge(&mut line);
+                }
+            } else if seen_this && self.edges_added > 1 {
+                line.column(col_commit, '\\');
+            } else if seen_this && self.edges_added == 1 {
+                // keep a '\' edge coming from the previous merge
+                if self.prev_state == State::PostMerge
+                    && self.prev_edges_added > 0
+                    && self.prev_commit_index < i
+                {
+                    line.column(col_commit, '\\');
+                } else {
+                    line.column(col_commit, '|');
+                }
+            } else if self.prev_state == State::Collapsing
+                && self.old_mapping.get(2 * i + 1) == Some(&(i as i32))
+                && self.mapping.get(2 * i).is_some_and(|m| *m < i as i32)
+            {
+                line.column(col_commit, '/');
+            } else {
+                line.column(col_commit, '|');
+            }
+            line.space(1);
+        }
+
+        if self.num_parents() > 1 {
+            self.update_state(State::PostMerge);
+        } else if self.is_mapping_correct() {
+            self.update_state(State::Padding);
+        } else {
+            self.update_state(State::Collapsing);
+        }
+        line
+    }
+
+    fn draw_octopus_merge(&self, line: &mut LineBuilder) {
+        let dashed_parents = self.num_dashed_parents();
+        for i in 0..dashed_parents.max(0) as usize {
+            let j = self.mapping[(self.commit_index + i + 2) * 2];
+            let column = self.new_columns[j.max(0) as usize];
+            line.column(column, '-');
+            let ch = if i as i32 == dashed_parents - 1 {
+                '.'
+            } else {
+                '-'
+            };
+            line.column(column, ch);
+        }
+    }
+
+    fn post_merge_line(&mut self) -> LineBuilder {
+        let commit = self.commit.unwrap_or(Oid::zero());
+        let first_parent = self.parents.first().copied();
+        let mut parent_col: Option<Oid> = None;
+        let mut line = LineBuilder::new(GraphLineKind::PostMerge);
+        let mut seen_this = false;
+        for i in 0..=self.columns.len() {
+            let col_commit = if i == self.columns.len() {
+                if seen_this {
+                    break;
+                }
+                commit
+            } else {
+                self.columns[i]
+            };
+
+            if col_commit == commit {
+                // an edge to the lane of each parent
+                seen_this = true;
+                let mut idx = self.merge_layout.max(0) as usize;
+                for (j, parent) in self.parents.iter().enumerate() {
+                    line.column(*parent, MERGE_CHARS[idx]);
+                    if idx == 2 {
+                        if self.edges_added > 0 || j + 1 < self.num_parents() {
+                            line.space(1);
+                        }
+                    } else {
+                        idx += 1;
+                    }
+                }
+                if self.edges_added == 0 {
+                    line.space(1);
+                }
+            } else if seen_this {
+                if self.edges_added > 0 {
+                    line.column(col_commit, '\\');
+                } else {
+                    line.column(col_commit, '|');
+                }
+                line.space(1);
+            } else {
+                line.column(col_commit, '|');
+                if self.merge_layout != 0 || i + 1 != self.commit_index {
+                    match parent_col {
+                        Some(parent_col) => line.column(parent_col, '_'),
+                        None => line.space(1),
+                    }
+                }
+            }
+
+            if Some(col_commit) == first_parent {
+                parent_col = Some(col_commit);
+            }
+        }
+
+        if self.is_mapping_correct() {
+            self.update_state(State::Padding);
+        } else {
+            self.update_state(State::Collapsing);
+        }
+        line
+    }
+
+    fn collapsing_line(&mut self) -> LineBuilder {
+        let mut used_horizontal = false;
+        let mut horizontal_edge: i32 = -1;
+        let mut horizontal_edge_target: i32 = -1;
+
+        std::mem::swap(&mut self.mapping, &mut self.old_mapping);
+        for i in 0..self.mapping_size {
+            self.mapping[i] = -1;
+        }
+
+        for i in 0..self.mapping_size {
+            let target = self.old_mapping[i];
+            if target < 0 {
+                continue;
+            }
+            // lines only ever move to the left
+            let target_index = target as usize * 2;
+            if target_index == i {
+                self.mapping[i] = target;
+            } else if self.mapping[i - 1] < 0 {
+                // nothing to the left, move left by one
+                self.mapping[i - 1] = target;
+                if horizontal_edge == -1 {
+                    horizontal_edge = i as i32;
+                    horizontal_edge_target = target;
+                    let mut j = target_index + 3;
+                    while j + 2 < i {
+                        self.mapping[j] = target;
+                        j += 2;
+                    }
+                }
+            } else if self.mapping[i - 1] == target {
+                // the line to the left has the same target, the lines merge
+            } else {
+                // cross over the line to the left
+                self.mapping[i - 2] = target;
+                if horizontal_edge == -1 {
+                    horizontal_edge_target = target;
+                    horizontal_edge = i as i32 - 1;
+                    let mut j = target_index + 3;
+                    while j + 2 < i {
+                        self.mapping[j] = target;
+                        j += 2;
+                    }
+                }
+            }
+        }
+
+        let size = self.mapping_size;
+        self.old_mapping[..size].copy_from_slice(&self.mapping[..size]);
+
+        if self.mapping_size > 0 && self.mapping[self.mapping_size - 1] < 0 {
+            self.mapping_size -= 1;
+        }
+
+        let mut line = LineBuilder::new(GraphLineKind::Collapsing);
+        for i in 0..self.mapping_size {
+            let target = self.mapping[i];
+            if target < 0 {
+                line.space(1);
+            } else if target as usize * 2 == i {
+                line.column(self.new_columns[target as usize], '|');
+            } else if target == horizontal_edge_target && i as i32 != horizontal_edge - 1 {
+                // only the first segment of a horizontal edge continues on the next line
+                if i != target as usize * 2 + 3 {
+                    self.mapping[i] = -1;
+                }
+                used_horizontal = true;
+                line.column(self.new_columns[target as usize], '_');
+            } else {
+                if used_horizontal && (i as i32) < horizontal_edge {
+                    self.mapping[i] = -1;
+                }
+                line.column(self.new_columns[target as usize], '/');
+            }
+        }
+
+        if self.is_mapping_correct() {
+            self.update_state(State::Padding);
+        }
+        line
+    }
+}
+
+#[cfg(test)]
+mod graph_test {
+    use std::{env, path::Path, process::Command};
+
+    use crate::{
+        DateMode, GitRepository, LogConfig, LogFlags, LogOrder, PrettyFormat,
+        helpers::test_util::git,
+    };
+
+    #[test]
+    fn graph_matches_cli_test() {
+        let dir_name = "./temp_test/graph/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // branches forking and merging, including an octopus merge, one commit per minute
+        let path = env::current_dir().unwrap().join(dir_name);
+        let script = r#"
+            git init -q -b main
+            git config user.name Committer
+            git config user.email committer@example.com
+            t=1700000000
+            c() {
+                t=$((t+60))
+                GIT_AUTHOR_DATE="@$t +0000" GIT_COMMITTER_DATE="@$t +0000" \
+                    git commit -q --allow-empty -m "$1"
+            }
+            m() {
+                t=$((t+60))
+                GIT_AUTHOR_DATE="@$t +0000" GIT_COMMITTER_DATE="@$t +0000" \
+                    git merge -q --no-ff -m "$@"
+            }
+            c base
+            git checkout -q -b a; c a1
+            git checkout -q main; c m1
+            git checkout -q -b b; c b1
+            git checkout -q a; c a2
+            git checkout -q main; c m2
+            git checkout -q -b d main~1; c d1
+            git checkout -q main; m octopus a b d; c m3
+            git checkout -q -b e main~3; c e1
+            git checkout -q main; m "merge e" e; c top
+        "#;
+        Command::new("sh")
+            .current_dir(&path)
+            .args(["-c", script])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let render = |format: PrettyFormat, abbrev: bool| {
+            let mut config = LogConfig::new(vec![]);
+            config.add_flag(LogFlags::Order(LogOrder::Topo));
+            let commits = repo.git_log(config).unwrap();
+            let mut formatter = repo.pretty_formatter(format, DateMode::Default).unwrap();
+            formatter.abbrev_commit(abbrev);
+            formatter.format_graph(commits).unwrap()
+        };
+        let oneline = render(PrettyFormat::Oneline, true);
+        let medium = render(PrettyFormat::Medium, false);
+        let format = render(PrettyFormat::Format("%s%n%an".into()), false);
+        let git_oneline = git(&path, &["log", "--graph", "--oneline"]);
+        let git_medium = git(&path, &["log", "--graph"]);
+        let git_format = git(&path, &["log", "--graph", "--format=%s%n%an"]);
+
+        // lanes as data
+        let mut config = LogConfig::new(vec![]);
+        config.add_flag(LogFlags::Order(LogOrder::Topo));
+        let mut layout = super::GraphLayout::new();
+        let mut lanes = vec![];
+        for commit in repo.git_log(config).unwrap() {
+            let commit = commit.unwrap();
+            let entry = layout.next(commit.id, &commit.parent_ids);
+            lanes.push((commit.subject(), entry.lane, entry.lanes.len()));
+        }
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(oneline.contains("*---."));
+        assert_eq!(oneline, git_oneline);
+        assert_eq!(medium, git_medium);
+        assert_eq!(format, git_format);
+        assert_eq!(lanes[0], ("top".to_string(), 0, 1));
+        assert_eq!(lanes[2], ("e1".to_string(), 1, 2));
+    }
+}
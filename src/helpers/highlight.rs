@@ -0,0 +1,90 @@
+use std::ops::Range;
+
+/// Line pairs needing more character comparisons than this after trimming the common prefix
+/// and suffix are highlighted as a whole.
+const MAX_COMPARISONS: usize = 1_000_000;
+
+/// Changed characters of a modified line pair, as byte ranges into the old and the new line.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineHighlight {
+    pub old: Vec<Range<usize>>,
+    pub new: Vec<Range<usize>>,
+}
+
+/// Compares two versions of a line character by character and returns the ranges that were
+/// removed from `old` and added in `new`. A trailing newline is never highlighted.
+pub fn highlight_line_pair(old: &str, new: &str) -> LineHighlight {
+    let old_chars: Vec<(usize, char)> = old.trim_end_matches('\n').char_indices().collect();
+    let new_chars: Vec<(usize, char)> = new.trim_end_matches('\n').char_indices().collect();
+
+    let prefix = old_chars
+        .iter()
+        .zip(&new_chars)
+        .take_while(|((_, a), (_, b))| a == b)
+        .count();
+    let suffix = old_chars[prefix..]
+        .iter()
+        .rev()
+        .zip(new_chars[prefix..].iter().rev())
+        .take_while(|((_, a), (_, b))| a == b)
+        .count();
+    let old_middle = &old_chars[prefix..old_chars.len() - suffix];
+    let new_middle = &new_chars[prefix..new_chars.len() - suffix];
+
+    let (old_changed, new_changed) = if old_middle.len() * new_middle.len() > MAX_COMPARISONS {
+        (vec![true; old_middle.len()], vec![true; new_middle.len()])
+    } else {
+        changed_chars(old_middle, new_middle)
+    };
+
+    LineHighlight {
+        old: ranges(old_middle, &old_changed),
+        new: ranges(new_middle, &new_changed),
+    }
+}
+
+/// Marks the characters of both sides that are not part of their longest common subsequence.
+fn changed_chars(old: &[(usize, char)], new: &[(usize, char)]) -> (Vec<bool>, Vec<bool>) {
+    // lengths[i][j] is the LCS length of old[i..] and new[j..]
+    let width = new.len() + 1;
+    let mut lengths = vec![0u32; (old.len() + 1) * width];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i * width + j] = if old[i].1 == new[j].1 {
+                lengths[(i + 1) * width + j + 1] + 1
+            } else {
+                lengths[(i + 1) * width + j].max(lengths[i * width + j + 1])
+            };
+        }
+    }
+
+    let mut old_changed = vec![true; old.len()];
+    let mut new_changed = vec![true; new.len()];
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() && j < new.len() {
+        if old[i].1 == new[j].1 {
+            old_changed[i] = false;
+            new_changed[j] = false;
+            i += 1;
+            j += 1;
+        } else if lengths[(i + 1) * width + j] >= lengths[i * width + j + 1] {
+            i += 1;
+        } else {
+            j += 1;
+        }
+    }
+    (old_changed, new_changed)
+}
+
+/// Joins consecutive changed characters into byte ranges.
+fn ranges(chars: &[(usize, char)], changed: &[bool]) -> Vec<Range<usize>> {
+    let mut ranges: Vec<Range<usize>> = vec![];
+    for (&(start, c), _) in chars.iter().zip(changed).filter(|(_, changed)| **changed) {
+        let end = start + c.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
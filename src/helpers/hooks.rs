@@ -0,0 +1,95 @@
+use std::{
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+use git2::{Error, ErrorClass, ErrorCode, Repository};
+
+use super::signing;
+
+/// Runs the hook `name` from `core.hooksPath` or `.git/hooks` with git's arguments, stdin and
+/// environment contract, does nothing if the hook does not exist or is not executable.
+///
+/// Returns an error of class `ErrorClass::Callback` and code `ErrorCode::User` if the hook exits
+/// with a non-zero status, use `is_hook_error` to tell these apart from other errors.
+pub(crate) fn run_hook(
+    repository: &Repository,
+    name: &str,
+    args: &[&str],
+    input: Option<&[u8]>,
+    env: &[(&str, &Path)],
+) -> Result<(), Error> {
+    let Some(hook) = find_hook(repository, name)? else {
+        return Ok(());
+    };
+
+    let mut command = Command::new(&hook);
+    command.args(args);
+    command.current_dir(match repository.workdir() {
+        Some(workdir) => workdir,
+        None => repository.path(),
+    });
+    command.env("GIT_DIR", repository.path());
+    for (key, value) in env {
+        command.env(key, value);
+    }
+
+    let output: Output = match input {
+        Some(input) => signing::run_with_input(command, input)?,
+        None => command
+            .output()
+            .map_err(|e| Error::from_str(&format!("cannot run {} hook: {}", name, e)))?,
+    };
+
+    if !output.status.success() {
+        let code = match output.status.code() {
+            Some(code) => code.to_string(),
+            None => String::from("signal"),
+        };
+        let msg = format!(
+            "{} hook exited with status {}: {}",
+            name,
+            code,
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+        return Err(Error::new(ErrorCode::User, ErrorClass::Callback, msg));
+    }
+    Ok(())
+}
+
+/// Returns true if `error` was returned because a hook rejected the operation.
+pub fn is_hook_error(error: &Error) -> bool {
+    error.code() == ErrorCode::User && error.class() == ErrorClass::Callback
+}
+
+fn find_hook(repository: &Repository, name: &str) -> Result<Option<PathBuf>, Error> {
+    let config = repository.config()?;
+    let dir = match config.get_path("core.hooksPath") {
+        Ok(path) if path.is_absolute() => path,
+        Ok(path) => match repository.workdir() {
+            Some(workdir) => workdir.join(path),
+            None => repository.path().join(path),
+        },
+        Err(_) => repository.path().join("hooks"),
+    };
+
+    let hook = dir.join(name);
+    if hook.is_file() && is_executable(&hook) {
+        return Ok(Some(hook));
+    }
+    Ok(None)
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    match path.metadata() {
+        Ok(metadata) => metadata.permissions().mode() & 0o111 != 0,
+        Err(_) => false,
+    }
+}
+
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
@@ -1,3 +1,15 @@
 pub mod channel;
+pub mod combined_diff;
 pub mod credentials;
+pub mod date;
+pub mod graph;
+pub mod highlight;
+pub mod hooks;
+pub mod pattern;
+pub mod pretty;
 pub mod repository;
+pub mod show;
+pub mod signing;
+#[cfg(test)]
+pub(crate) mod test_util;
+pub mod url;
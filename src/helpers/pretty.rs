@@ -0,0 +1,603 @@
+use std::collections::{HashMap, HashSet};
+
+use git2::{Error, Oid, Repository, Signature};
+
+use crate::{
+    GitRepository,
+    configs::log_config::CommitRecord,
+    helpers::{date, graph::GraphLayout},
+};
+
+/// Format used to render commits, same as `git log --pretty=<format>`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PrettyFormat {
+    /// `<hash> <subject>` on a single line.
+    Oneline,
+
+    /// Hash, author and subject.
+    Short,
+
+    /// Hash, author, author date and full message. This is the default format of `git log`.
+    Medium,
+
+    /// Hash, author, committer and full message.
+    Full,
+
+    /// Hash, author, author date, committer, commit date and full message.
+    Fuller,
+
+    /// A format string with placeholders, same as `git log --format=<format>`.
+    ///
+    /// Supported placeholders are `%H`, `%h`, `%T`, `%t`, `%P`, `%p`, `%an`, `%ae`, `%ad`,
+    /// `%ar`, `%at`, `%ai`, `%aI`, `%as`, the same for the committer with `%c`, `%s`, `%b`,
+    /// `%B`, `%d`, `%D`, `%n`, `%%` and `%(trailers)` with the `only`, `unfold` and `key=<key>`
+    /// options. Unknown placeholders are output as they are.
+    Format(String),
+}
+
+/// Format used to render dates, same as `git log --date=<mode>`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum DateMode {
+    /// `Thu Apr 7 22:13:13 2005 +0200`
+    #[default]
+    Default,
+
+    /// `3 days ago`
+    Relative,
+
+    /// `2005-04-07 22:13:13 +0200`
+    Iso,
+
+    /// `2005-04-07T22:13:13+02:00`
+    IsoStrict,
+
+    /// `Thu, 7 Apr 2005 22:13:13 +0200`
+    Rfc,
+
+    /// `2005-04-07`
+    Short,
+
+    /// `1112904793`
+    Unix,
+
+    /// `1112904793 +0200`
+    Raw,
+}
+
+/// Renders `CommitRecord`s the way `git log` prints them, created with
+/// `GitRepository::pretty_formatter`.
+pub struct PrettyFormatter<'repo> {
+    repository: &'repo Repository,
+    format: PrettyFormat,
+    date_mode: DateMode,
+    abbrev_commit: bool,
+    // ref names pointing at each commit, loaded the first time `%d` or `%D` is used
+    decorations: Option<HashMap<Oid, Vec<String>>>,
+}
+
+impl GitRepository {
+    /// Creates a formatter for the commits of this repository.
+    pub fn pretty_formatter(
+        &self,
+        format: PrettyFormat,
+        date_mode: DateMode,
+    ) -> Result<PrettyFormatter<'_>, Error> {
+        let repository = self.get_repository()?;
+        Ok(PrettyFormatter {
+            repository,
+            format,
+            date_mode,
+            abbrev_commit: false,
+            decorations: None,
+        })
+    }
+}
+
+impl PrettyFormatter<'_> {
+    /// Shows abbreviated commit hashes in the header of the built-in formats, same as
+    /// `--abbrev-commit`. `PrettyFormat::Oneline` with this set is the same as `--oneline`.
+    pub fn abbrev_commit(&mut self, abbrev: bool) -> &mut Self {
+        self.abbrev_commit = abbrev;
+        self
+    }
+
+    /// Renders a single commit, without the separator `git log` puts between commits.
+    pub fn format(&mut self, commit: &CommitRecord) -> Result<String, Error> {
+        let hash = if self.abbrev_commit {
+            self.abbrev(commit.id)?
+        } else {
+            commit.id.to_string()
+        };
+
+        let mut out = String::new();
+        match self.format.clone() {
+            PrettyFormat::Oneline => {
+                out.push_str(&format!("{} {}\n", hash, commit.subject()));
+                return Ok(out);
+            }
+            PrettyFormat::Format(format) => return self.expand(&format, commit),
+            _ => {}
+        }
+
+        out.push_str(&format!("commit {}\n", hash));
+        if commit.is_merge() {
+            let mut parents = vec![];
+            for parent in &commit.parent_ids {
+                parents.push(self.abbrev(*parent)?);
+            }
+            out.push_str(&format!("Merge: {}\n", parents.join(" ")));
+        }
+
+        let identity = |signature: &Signature| {
+            format!(
+                "{} <{}>",
+                String::from_utf8_lossy(signature.name_bytes()),
+                String::from_utf8_lossy(signature.email_bytes())
+            )
+        };
+        match self.format {
+            PrettyFormat::Short => {
+                out.push_str(&format!("Author: {}\n", identity(&commit.author)));
+            }
+            PrettyFormat::Medium => {
+                out.push_str(&format!("Author: {}\n", identity(&commit.author)));
+                let when = self.date(&commit.author);
+                out.push_str(&format!("Date:   {}\n", when));
+            }
+            PrettyFormat::Full => {
+                out.push_str(&format!("Author: {}\n", identity(&commit.author)));
+                out.push_str(&format!("Commit: {}\n", identity(&commit.committer)));
+            }
+            _ => {
+                out.push_str(&format!("Author:     {}\n", identity(&commit.author)));
+                let when = self.date(&commit.author);
+                out.push_str(&format!("AuthorDate: {}\n", when));
+                out.push_str(&format!("Commit:     {}\n", identity(&commit.committer)));
+                let when = self.date(&commit.committer);
+                out.push_str(&format!("CommitDate: {}\n", when));
+            }
+        }
+
+        out.push('\n');
+        let message = commit.message.trim_start_matches('\n').trim_end();
+        let message = match message.find("\n\n") {
+            // only the subject paragraph
+            Some(index) if self.format == PrettyFormat::Short => &message[..index],
+            _ => message,
+        };
+        for line in message.lines() {
+            out.push_str(&format!("    {}\n", line.trim_end()));
+        }
+        Ok(out)
+    }
+
+    /// Renders commits the way `git log` prints them, including the blank lines between
+    /// commits of the multi-line formats.
+    pub fn format_log<I>(&mut self, commits: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = Result<CommitRecord, Error>>,
+    {
+        let separator = !matches!(self.format, PrettyFormat::Oneline | PrettyFormat::Format(_));
+        let mut out = String::new();
+        for (index, commit) in commits.into_iter().enumerate() {
+            if separator && index > 0 {
+                out.push('\n');
+            }
+            out.push_str(&self.format(&commit?)?);
+            if let PrettyFormat::Format(_) = self.format {
+                out.push('\n');
+            }
+        }
+        Ok(out)
+    }
+
+    /// Renders commits with the commit graph on their left, same as `git log --graph`.
+    ///
+    /// Only edges between the given commits are drawn, parents left out of the walk (e.g. by
+    /// filters) end their lane. Use `GraphLayout` directly to get the lanes as data.
+    pub fn format_graph<I>(&mut self, commits: I) -> Result<String, Error>
+    where
+        I: IntoIterator<Item = Result<CommitRecord, Error>>,
+    {
+        let commits = commits
+            .into_iter()
+            .collect::<Result<Vec<CommitRecord>, Error>>()?;
+        let shown: HashSet<Oid> = commits.iter().map(|commit| commit.id).collect();
+
+        let mut layout = GraphLayout::new();
+        layout.separators(!matches!(
+            self.format,
+            PrettyFormat::Oneline | PrettyFormat::Format(_)
+        ));
+        let mut out = String::new();
+        for commit in &commits {
+            let parents: Vec<Oid> = commit
+                .parent_ids
+                .iter()
+                .filter(|parent| shown.contains(parent))
+                .copied()
+                .collect();
+            let entry = layout.next(commit.id, &parents);
+            out.push_str(&entry.render(&self.format(commit)?));
+        }
+        Ok(out)
+    }
+
+    fn expand(&mut self, format: &str, commit: &CommitRecord) -> Result<String, Error> {
+        let mut out = String::new();
+        let mut rest = format;
+        while let Some(index) = rest.find('%') {
+            out.push_str(&rest[..index]);
+            rest = &rest[index..];
+
+            let (value, consumed) = self.placeholder(rest, commit)?;
+            match value {
+                Some(value) => out.push_str(&value),
+                // unknown placeholders are copied as they are
+                None => out.push_str(&rest[..consumed]),
+            }
+            rest = &rest[consumed..];
+        }
+        out.push_str(rest);
+        Ok(out)
+    }
+
+    /// Expands the placeholder at the start of `spec` (which starts with `%`), returning its
+    /// value and the number of bytes it spans.
+    fn placeholder(
+        &mut self,
+        spec: &str,
+        commit: &CommitRecord,
+    ) -> Result<(Option<String>, usize), Error> {
+        let chars: Vec<char> = spec.chars().skip(1).take(2).collect();
+        let Some(first) = chars.first().copied() else {
+            return Ok((None, 1));
+        };
+
+        let value = match first {
+            '%' => Some(String::from("%")),
+            'n' => Some(String::from("\n")),
+            'H' => Some(commit.id.to_string()),
+            'h' => Some(self.abbrev(commit.id)?),
+            'T' => Some(commit.tree_id.to_string()),
+            't' => Some(self.abbrev(commit.tree_id)?),
+            'P' => Some(
+                commit
+                    .parent_ids
+                    .iter()
+                    .map(|parent| parent.to_string())
+                    .collect::<Vec<String>>()
+                    .join(" "),
+            ),
+            'p' => {
+                let mut parents = vec![];
+                for parent in &commit.parent_ids {
+                    parents.push(self.abbrev(*parent)?);
+                }
+                Some(parents.join(" "))
+            }
+            's' => Some(commit.subject()),
+            'b' => Some(commit.body().to_string()),
+            'B' => Some(commit.message.trim_start_matches('\n').to_string()),
+            'd' => {
+                let names = self.decorations(commit.id)?;
+                if names.is_empty() {
+                    Some(String::new())
+                } else {
+                    Some(format!(" ({})", names.join(", ")))
+                }
+            }
+            'D' => Some(self.decorations(commit.id)?.join(", ")),
+            'a' | 'c' => {
+                let signature = if first == 'a' {
+                    &commit.author
+                } else {
+                    &commit.committer
+                };
+                let value = match chars.get(1) {
+                    Some(field) => self.person(signature, *field),
+                    None => None,
+                };
+                return match value {
+                    Some(value) => Ok((Some(value), 3)),
+                    None => Ok((None, 2)),
+                };
+            }
+            '(' => {
+                let Some(end) = spec.find(')') else {
+                    return Ok((None, 1));
+                };
+                let value = self.trailers(&spec[2..end], commit);
+                return Ok((value, end + 1));
+            }
+            _ => None,
+        };
+        Ok((value, 1 + first.len_utf8()))
+    }
+
+    fn person(&self, signature: &Signature, field: char) -> Option<String> {
+        let when = signature.when();
+        let value = match field {
+            'n' => String::from_utf8_lossy(signature.name_bytes()).to_string(),
+            'e' => String::from_utf8_lossy(signature.email_bytes()).to_string(),
+            'd' => self.date(signature),
+            'r' => date::format_relative(&when, date::now()),
+            't' => when.seconds().to_string(),
+            'i' => date::format_iso(&when),
+            'I' => date::format_iso_strict(&when),
+            's' => date::format_short(&when),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Expands `%(trailers[:<options>])`, returns `None` for other `%(...)` placeholders.
+    fn trailers(&self, spec: &str, commit: &CommitRecord) -> Option<String> {
+        let options = match spec.strip_prefix("trailers") {
+            Some("") => "",
+            Some(options) => options.strip_prefix(':')?,
+            None => return None,
+        };
+
+        let mut keys = vec![];
+        for option in options.split(',').filter(|option| !option.is_empty()) {
+            match option.split_once('=') {
+                Some(("key", key)) => keys.push(key.to_lowercase()),
+                Some(("only", _)) | Some(("unfold", _)) => {}
+                None if option == "only" || option == "unfold" => {}
+                _ => return None,
+            }
+        }
+
+        let mut out = String::new();
+        if let Ok(trailers) = git2::message_trailers_strs(&commit.message) {
+            for (key, value) in trailers.iter() {
+                if keys.is_empty() || keys.contains(&key.to_lowercase()) {
+                    out.push_str(&format!("{}: {}\n", key, value));
+                }
+            }
+        }
+        Some(out)
+    }
+
+    pub(crate) fn date(&self, signature: &Signature) -> String {
+        let when = signature.when();
+        match self.date_mode {
+            DateMode::Default => date::format_default(&when),
+            DateMode::Relative => date::format_relative(&when, date::now()),
+            DateMode::Iso => date::format_iso(&when),
+            DateMode::IsoStrict => date::format_iso_strict(&when),
+            DateMode::Rfc => date::format_rfc2822(&when),
+            DateMode::Short => date::format_short(&when),
+            DateMode::Unix => when.seconds().to_string(),
+            DateMode::Raw => format!(
+                "{} {}",
+                when.seconds(),
+                date::format_offset(when.offset_minutes(), false)
+            ),
+        }
+    }
+
+    fn abbrev(&self, oid: Oid) -> Result<String, Error> {
+        let object = self.repository.find_object(oid, None)?;
+        let short = object.short_id()?;
+        Ok(short.as_str().unwrap_or_default().to_string())
+    }
+
+    /// Ref names decorating a commit in git's order: `HEAD -> <branch>` first, followed by
+    /// tags, remote-tracking branches and branches in reverse alphabetical order.
+    fn decorations(&mut self, oid: Oid) -> Result<Vec<String>, Error> {
+        if self.decorations.is_none() {
+            self.decorations = Some(load_decorations(self.repository)?);
+        }
+        Ok(match self.decorations.as_ref().and_then(|d| d.get(&oid)) {
+            Some(names) => names.clone(),
+            None => vec![],
+        })
+    }
+}
+
+fn load_decorations(repository: &Repository) -> Result<HashMap<Oid, Vec<String>>, Error> {
+    let mut refs = vec![];
+    for reference in repository.references()? {
+        let reference = reference?;
+        let Some(name) = reference.name() else {
+            continue;
+        };
+        let short = if let Some(tag) = name.strip_prefix("refs/tags/") {
+            format!("tag: {}", tag)
+        } else if let Some(short) = name
+            .strip_prefix("refs/heads/")
+            .or_else(|| name.strip_prefix("refs/remotes/"))
+        {
+            short.to_string()
+        } else {
+            continue;
+        };
+        if let Ok(commit) = reference.peel_to_commit() {
+            refs.push((name.to_string(), short, commit.id()));
+        }
+    }
+    refs.sort_by(|a, b| b.0.cmp(&a.0));
+
+    let mut decorations: HashMap<Oid, Vec<String>> = HashMap::new();
+    for (_, short, oid) in &refs {
+        decorations.entry(*oid).or_default().push(short.clone());
+    }
+
+    // HEAD goes first, combined with the branch it points to
+    if let Ok(head) = repository.head()
+        && let Ok(commit) = head.peel_to_commit()
+    {
+        let names = decorations.entry(commit.id()).or_default();
+        match head.shorthand() {
+            Some(branch) if head.is_branch() => {
+                names.retain(|name| name != branch);
+                names.insert(0, format!("HEAD -> {}", branch));
+            }
+            _ => names.insert(0, String::from("HEAD")),
+        }
+    }
+    Ok(decorations)
+}
+
+#[cfg(test)]
+mod pretty_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::{DateMode, PrettyFormat};
+    use crate::{GitRepository, LogConfig, helpers::test_util::git};
+
+    #[test]
+    fn pretty_format_matches_cli_test() {
+        let dir_name = "./temp_test/pretty/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // a merged branch, tags, a remote-tracking branch and a message with trailers
+        let path = env::current_dir().unwrap().join(dir_name);
+        // author dates differ from the committer dates in both time and offset
+        let date = "--date=1700003600 -0530";
+        git(&path, &["init", "-b", "main"]);
+        git(&path, &["config", "user.name", "Committer"]);
+        git(&path, &["config", "user.email", "committer@example.com"]);
+        fs::write(path.join("a.txt"), "a").unwrap();
+        git(&path, &["add", "a.txt"]);
+        git(
+            &path,
+            &[
+                "commit",
+                date,
+                "-m",
+                "first\n\nSigned-off-by: A <a@example.com>\nFixes: #1",
+            ],
+        );
+        git(&path, &["tag", "-a", "-m", "annotated", "v1"]);
+        git(&path, &["checkout", "-b", "feature"]);
+        git(
+            &path,
+            &[
+                "commit",
+                date,
+                "--allow-empty",
+                "-m",
+                "feature work\nsecond subject line",
+            ],
+        );
+        git(&path, &["checkout", "main"]);
+        git(
+            &path,
+            &[
+                "commit",
+                date,
+                "--allow-empty",
+                "-m",
+                "main work\n\nbody line\n\n  indented",
+            ],
+        );
+        git(
+            &path,
+            &["merge", "--no-ff", "-m", "merge feature", "feature"],
+        );
+        git(&path, &["update-ref", "refs/remotes/origin/main", "HEAD"]);
+        git(&path, &["tag", "light"]);
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let cases = vec![
+            (
+                PrettyFormat::Oneline,
+                DateMode::Default,
+                "--pretty=oneline",
+                "",
+            ),
+            (PrettyFormat::Short, DateMode::Default, "--pretty=short", ""),
+            (
+                PrettyFormat::Medium,
+                DateMode::Default,
+                "--pretty=medium",
+                "",
+            ),
+            (
+                PrettyFormat::Medium,
+                DateMode::Iso,
+                "--pretty=medium",
+                "--date=iso",
+            ),
+            (PrettyFormat::Full, DateMode::Default, "--pretty=full", ""),
+            (
+                PrettyFormat::Fuller,
+                DateMode::Rfc,
+                "--pretty=fuller",
+                "--date=rfc",
+            ),
+            (
+                PrettyFormat::Format("%H %h %T %t [%P] [%p] %s".into()),
+                DateMode::Default,
+                "--format=%H %h %T %t [%P] [%p] %s",
+                "",
+            ),
+            (
+                PrettyFormat::Format("%an <%ae> %ad|%cn <%ce> %cd".into()),
+                DateMode::Short,
+                "--format=%an <%ae> %ad|%cn <%ce> %cd",
+                "--date=short",
+            ),
+            (
+                PrettyFormat::Format("%ad %cd %ar %at %ai %aI %cs".into()),
+                DateMode::Unix,
+                "--format=%ad %cd %ar %at %ai %aI %cs",
+                "--date=unix",
+            ),
+            (
+                PrettyFormat::Format("%ad%n%cd".into()),
+                DateMode::Relative,
+                "--format=%ad%n%cd",
+                "--date=relative",
+            ),
+            (
+                PrettyFormat::Format("%h%d|%D|%% %x %(trailers)".into()),
+                DateMode::Default,
+                "--format=%h%d|%D|%% %x %(trailers)",
+                "",
+            ),
+            (
+                PrettyFormat::Format("[%b][%B]%(trailers:key=fixes)".into()),
+                DateMode::Default,
+                "--format=[%b][%B]%(trailers:key=fixes)",
+                "",
+            ),
+        ];
+
+        let mut results = vec![];
+        for (format, date_mode, pretty, date) in cases {
+            let mut formatter = repo.pretty_formatter(format, date_mode).unwrap();
+            let commits = repo.git_log(LogConfig::new(vec![])).unwrap();
+            let ours = formatter.format_log(commits).unwrap();
+            let args: Vec<&str> = ["log", pretty, date]
+                .into_iter()
+                .filter(|arg| !arg.is_empty())
+                .collect();
+            let theirs = git(&path, &args);
+            results.push((pretty, ours, theirs));
+        }
+
+        let mut formatter = repo
+            .pretty_formatter(PrettyFormat::Oneline, DateMode::Default)
+            .unwrap();
+        formatter.abbrev_commit(true);
+        let commits = repo.git_log(LogConfig::new(vec![])).unwrap();
+        let oneline = formatter.format_log(commits).unwrap();
+        let git_oneline = git(&path, &["log", "--oneline"]);
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (pretty, ours, theirs) in results {
+            assert_eq!(ours, theirs, "{}", pretty);
+        }
+        assert_eq!(oneline, git_oneline);
+    }
+}
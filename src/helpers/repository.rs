@@ -1,6 +1,6 @@
-use std::path::Path;
+use std::{ffi::OsStr, fs, path::Path};
 
-use git2::{Error, Repository};
+use git2::{Error, ErrorCode, Oid, Repository, RepositoryOpenFlags, RepositoryState};
 
 use super::credentials::{CredType, GitCredentials, GitHttpsCredentials};
 
@@ -9,19 +9,91 @@ pub struct GitRepository {
     pub(crate) cred: GitCredentials,
     pub(crate) skip_owner_validation: bool,
     pub(crate) bypass_certificate_check: bool,
+    pub(crate) run_hooks: bool,
+}
+
+/// The state of `HEAD` in an opened repository.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HeadState {
+    /// `HEAD` points to a branch, holds the full reference name (e.g. "refs/heads/main").
+    Branch(String),
+
+    /// `HEAD` points directly to a commit.
+    Detached(Oid),
+
+    /// `HEAD` points to a branch that does not have any commits yet, holds the full
+    /// reference name of that branch.
+    Unborn(String),
+}
+
+impl Default for GitRepository {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 impl GitRepository {
-    // testtt
     /// Create a `GitRepository` from an existing repository.
     pub fn open(path: &Path) -> Result<Self, Error> {
         let repo = Repository::open(path)?;
-        Ok(GitRepository {
+        Ok(GitRepository::from_repository(repo))
+    }
+
+    /// Create a `GitRepository` by looking for a repository at `path` and then walking up
+    /// through its parent directories, similar to how the git cli finds the repository
+    /// when run from a subdirectory.
+    pub fn discover(path: &Path) -> Result<Self, Error> {
+        let repo = Repository::discover(path)?;
+        Ok(GitRepository::from_repository(repo))
+    }
+
+    /// Create a `GitRepository` from an existing bare repository. `path` must point directly to
+    /// the bare repository.
+    pub fn open_bare(path: &Path) -> Result<Self, Error> {
+        let repo = Repository::open_bare(path)?;
+        Ok(GitRepository::from_repository(repo))
+    }
+
+    /// Create a `GitRepository` the same way the git cli does, respecting `GIT_DIR`,
+    /// `GIT_WORK_TREE`, `GIT_CEILING_DIRECTORIES` and related environment variables.
+    /// With `GIT_DIR` unset, the repository is searched for starting in the current directory.
+    pub fn open_from_env() -> Result<Self, Error> {
+        let repo = Repository::open_from_env()?;
+        Ok(GitRepository::from_repository(repo))
+    }
+
+    /// Create a `GitRepository` with additional control over how the repository is found.
+    ///
+    /// `flags` can be used to disable searching the parent directories (`NO_SEARCH`), to stop
+    /// at filesystem boundaries (`CROSS_FS`), or to force opening it as bare (`BARE`). The
+    /// search through parent directories stops before entering any of the `ceiling_dirs`.
+    ///
+    /// Example of opening a repository without searching its parent directories.
+    /// ```ignore
+    /// let flags = git2::RepositoryOpenFlags::NO_SEARCH;
+    /// let repo = GitRepository::open_ext(Path::new("./repo"), flags, &[] as &[&str])?;
+    /// ```
+    pub fn open_ext<I, O>(
+        path: &Path,
+        flags: RepositoryOpenFlags,
+        ceiling_dirs: I,
+    ) -> Result<Self, Error>
+    where
+        I: IntoIterator<Item = O>,
+        O: AsRef<OsStr>,
+    {
+        let repo = Repository::open_ext(path, flags, ceiling_dirs)?;
+        Ok(GitRepository::from_repository(repo))
+    }
+
+    fn from_repository(repository: Repository) -> Self {
+        GitRepository {
             cred: GitCredentials::Default,
-            repository: Some(repo),
+            repository: Some(repository),
             skip_owner_validation: false,
             bypass_certificate_check: false,
-        })
+            run_hooks: false,
+        }
     }
 
     /// Create an empty `GitRepository` object. Use this for cloning a repository.
@@ -31,6 +103,7 @@ impl GitRepository {
             repository: None,
             skip_owner_validation: false,
             bypass_certificate_check: false,
+            run_hooks: false,
         }
     }
 
@@ -44,6 +117,11 @@ impl GitRepository {
         self.bypass_certificate_check
     }
 
+    /// Returns true if hooks are to be run, false otherwise.
+    pub fn get_run_hooks(&self) -> bool {
+        self.run_hooks
+    }
+
     /// Set true to skip owner validation.
     pub fn skip_owner_validation(&mut self, skip: bool) {
         self.skip_owner_validation = skip;
@@ -54,6 +132,12 @@ impl GitRepository {
         self.bypass_certificate_check = bypass;
     }
 
+    /// Set true to run the scripts in `.git/hooks` (or `core.hooksPath`) around commits,
+    /// checkouts and pushes, like the git cli does. Disabled by default.
+    pub fn run_hooks(&mut self, run: bool) {
+        self.run_hooks = run;
+    }
+
     pub fn get_cred_type(&self) -> Result<CredType, Error> {
         match &self.cred {
             GitCredentials::Https(git_https_credentials) => git_https_credentials.get_cred_type(),
@@ -78,6 +162,125 @@ impl GitRepository {
     /// Returns `true` if the repository is cloned/init-ed and ready for other git operations.
     /// Returns `false` other wise.
     pub fn is_valid(&self) -> bool {
-        return self.repository.is_some();
+        self.repository.is_some()
+    }
+
+    /// Returns the path to the working directory of the repository, `None` for bare
+    /// repositories.
+    pub fn workdir(&self) -> Result<Option<&Path>, Error> {
+        Ok(self.get_repository()?.workdir())
+    }
+
+    /// Returns the path to the `.git` directory for normal repositories, or the repository
+    /// itself for bare repositories.
+    pub fn git_dir(&self) -> Result<&Path, Error> {
+        Ok(self.get_repository()?.path())
+    }
+
+    /// Returns `true` if the repository is bare.
+    pub fn is_bare(&self) -> Result<bool, Error> {
+        Ok(self.get_repository()?.is_bare())
+    }
+
+    /// Returns `true` if the repository is a shallow clone.
+    pub fn is_shallow(&self) -> Result<bool, Error> {
+        Ok(self.get_repository()?.is_shallow())
+    }
+
+    /// Returns the commits at which the history of a shallow repository is cut off, as listed in
+    /// `.git/shallow`. Empty if the repository is not shallow.
+    pub fn shallow_roots(&self) -> Result<Vec<Oid>, Error> {
+        let repository = self.get_repository()?;
+        let shallow = match fs::read_to_string(repository.path().join("shallow")) {
+            Ok(shallow) => shallow,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(e) => {
+                let err_msg = format!("failed reading the shallow file: {}", e);
+                return Err(Error::from_str(&err_msg));
+            }
+        };
+        shallow
+            .lines()
+            .map(|line| Oid::from_str(line.trim()))
+            .collect()
+    }
+
+    /// Returns where `HEAD` currently points to.
+    pub fn head_state(&self) -> Result<HeadState, Error> {
+        let repository = self.get_repository()?;
+        match repository.head() {
+            Ok(head) => {
+                if repository.head_detached()? {
+                    let oid = head.peel_to_commit()?.id();
+                    return Ok(HeadState::Detached(oid));
+                }
+                let name = String::from_utf8_lossy(head.name_bytes()).to_string();
+                Ok(HeadState::Branch(name))
+            }
+            Err(ref e) if e.code() == ErrorCode::UnbornBranch => {
+                let head = repository.find_reference("HEAD")?;
+                let name = match head.symbolic_target_bytes() {
+                    Some(target) => String::from_utf8_lossy(target).to_string(),
+                    None => String::new(),
+                };
+                Ok(HeadState::Unborn(name))
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Returns the operation currently in progress in the repository (merge, rebase,
+    /// cherry-pick, bisect, etc), `RepositoryState::Clean` if there is none.
+    pub fn state(&self) -> Result<RepositoryState, Error> {
+        Ok(self.get_repository()?.state())
+    }
+
+    pub(crate) fn get_repository(&self) -> Result<&Repository, Error> {
+        match &self.repository {
+            Some(repository) => Ok(repository),
+            None => Err(Error::from_str(
+                "Repository not found or created, try opening a valid repository or cloning one",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod repository_test {
+    use std::{env, path::Path, process::Command};
+
+    use super::{GitRepository, HeadState};
+
+    #[test]
+    fn discover_test() {
+        let dir_name = "./temp_test/repository_discover/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", &format!("{}sub/dir", dir_name)])
+            .output()
+            .unwrap();
+
+        // create an empty repository
+        let _ = Command::new("git")
+            .args(["-C", dir_name, "init", "--initial-branch", "main"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+
+        // discover the repository from a subdirectory
+        let path = env::current_dir().unwrap().join(dir_name);
+        let repo = GitRepository::discover(&path.join("sub/dir")).unwrap();
+        let workdir = repo.workdir().unwrap().unwrap().canonicalize().unwrap();
+        let expected = path.canonicalize().unwrap();
+        let bare = repo.is_bare().unwrap();
+        let head = repo.head_state().unwrap();
+        let open = GitRepository::open(Path::new(&path.join("sub/dir")));
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert_eq!(workdir, expected);
+        assert!(!bare);
+        assert_eq!(head, HeadState::Unborn("refs/heads/main".to_string()));
+        assert!(open.is_err());
     }
 }
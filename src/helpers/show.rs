@@ -0,0 +1,342 @@
+use git2::{Error, Object, ObjectType, Oid, Repository, Signature};
+
+use crate::{
+    CombinedDiff, CommitRecord, DiffOutput, GitRepository, PrettyFormatter,
+    configs::diff_config::{DiffFlagsInternal, diff_output},
+};
+
+/// The object a spec passed to `git_show` resolved to.
+pub enum ShowObject<'repo> {
+    Commit(ShowCommit<'repo>),
+    Tag(ShowTag<'repo>),
+    Tree(ShowTree),
+    Blob(ShowBlob),
+}
+
+/// A commit with its changes.
+pub struct ShowCommit<'repo> {
+    pub commit: CommitRecord,
+    pub diff: ShowDiff<'repo>,
+}
+
+/// Changes of a shown commit.
+pub enum ShowDiff<'repo> {
+    /// Diff against the first parent, or the empty tree for root commits.
+    Patch(DiffOutput<'repo>),
+    /// Combined diff of a merge against all of its parents.
+    Combined(CombinedDiff<'repo>),
+}
+
+/// An annotated tag with the object it points to.
+pub struct ShowTag<'repo> {
+    pub name: String,
+    pub id: Oid,
+    pub tagger: Option<Signature<'static>>,
+    pub message: String,
+    pub target: Box<ShowObject<'repo>>,
+}
+
+/// A tree with its entries.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShowTree {
+    /// The spec the tree was resolved from, shown in the header.
+    pub spec: String,
+    pub id: Oid,
+    pub entries: Vec<TreeEntryRecord>,
+}
+
+/// An entry of a `ShowTree`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TreeEntryRecord {
+    pub name: String,
+    pub id: Oid,
+    pub kind: Option<ObjectType>,
+    pub mode: i32,
+}
+
+/// A blob with its contents.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShowBlob {
+    pub id: Oid,
+    pub content: Vec<u8>,
+}
+
+impl GitRepository {
+    /// Resolves `spec` the same way `git rev-parse` does and returns what it names, same as
+    /// `git show <spec>`. `<rev>:<path>` specs name the blob or tree at the path.
+    pub fn git_show(&self, spec: &str) -> Result<ShowObject<'_>, Error> {
+        if let Some(repository) = &self.repository {
+            let object = repository.revparse_single(spec)?;
+            return show_object(repository, spec, object);
+        }
+
+        Err(Error::from_str(
+            "Repository not found or created, try opening a valid repository or cloning one",
+        ))
+    }
+}
+
+fn show_object<'repo>(
+    repository: &'repo Repository,
+    spec: &str,
+    object: Object<'repo>,
+) -> Result<ShowObject<'repo>, Error> {
+    match object.kind() {
+        Some(ObjectType::Commit) => {
+            let commit = object.peel_to_commit()?;
+            let diff = if commit.parent_count() > 1 {
+                ShowDiff::Combined(CombinedDiff::new(repository, &commit)?)
+            } else {
+                let parent = match commit.parent_count() {
+                    0 => None,
+                    _ => Some(commit.parent(0)?.tree()?),
+                };
+                let tree = commit.tree()?;
+                let flags = DiffFlagsInternal::default();
+                ShowDiff::Patch(diff_output(repository, &flags, &[], |options| {
+                    repository.diff_tree_to_tree(parent.as_ref(), Some(&tree), Some(options))
+                })?)
+            };
+            Ok(ShowObject::Commit(ShowCommit {
+                commit: CommitRecord::from_commit(&commit),
+                diff,
+            }))
+        }
+        Some(ObjectType::Tag) => {
+            let tag = object.peel_to_tag()?;
+            let target = tag.target()?;
+            Ok(ShowObject::Tag(ShowTag {
+                name: String::from_utf8_lossy(tag.name_bytes()).to_string(),
+                id: tag.id(),
+                tagger: tag.tagger().map(|tagger| tagger.to_owned()),
+                message: String::from_utf8_lossy(tag.message_bytes().unwrap_or_default())
+                    .to_string(),
+                target: Box::new(show_object(repository, spec, target)?),
+            }))
+        }
+        Some(ObjectType::Tree) => {
+            let tree = object.peel_to_tree()?;
+            let entries = tree
+                .iter()
+                .map(|entry| TreeEntryRecord {
+                    name: String::from_utf8_lossy(entry.name_bytes()).to_string(),
+                    id: entry.id(),
+                    kind: entry.kind(),
+                    mode: entry.filemode(),
+                })
+                .collect();
+            Ok(ShowObject::Tree(ShowTree {
+                spec: spec.to_string(),
+                id: tree.id(),
+                entries,
+            }))
+        }
+        Some(ObjectType::Blob) => {
+            let blob = object.peel_to_blob()?;
+            Ok(ShowObject::Blob(ShowBlob {
+                id: blob.id(),
+                content: blob.content().to_vec(),
+            }))
+        }
+        _ => Err(Error::from_str(&format!(
+            "unknown object type for {}",
+            spec
+        ))),
+    }
+}
+
+impl ShowObject<'_> {
+    /// Renders the object the way `git show` prints it, commit and tag headers use the format
+    /// and date mode of `formatter`.
+    pub fn render(&self, formatter: &mut PrettyFormatter) -> Result<String, Error> {
+        let mut out = String::new();
+        match self {
+            ShowObject::Commit(show) => {
+                out.push_str(&formatter.format(&show.commit)?);
+                // merges are separated from their combined diff even when it is empty
+                let (diff, separate) = match &show.diff {
+                    ShowDiff::Patch(diff) => (diff.patch()?, false),
+                    ShowDiff::Combined(diff) => (diff.patch()?, true),
+                };
+                if separate || !diff.is_empty() {
+                    out.push('\n');
+                    out.push_str(&diff);
+                }
+            }
+            ShowObject::Tag(tag) => {
+                out.push_str(&format!("tag {}\n", tag.name));
+                if let Some(tagger) = &tag.tagger {
+                    out.push_str(&format!(
+                        "Tagger: {} <{}>\n",
+                        String::from_utf8_lossy(tagger.name_bytes()),
+                        String::from_utf8_lossy(tagger.email_bytes())
+                    ));
+                    out.push_str(&format!("Date:   {}\n", formatter.date(tagger)));
+                }
+                out.push('\n');
+                out.push_str(&tag.message);
+                if !tag.message.is_empty() && !tag.message.ends_with('\n') {
+                    out.push('\n');
+                }
+                out.push('\n');
+                out.push_str(&tag.target.render(formatter)?);
+            }
+            ShowObject::Tree(tree) => {
+                out.push_str(&format!("tree {}\n\n", tree.spec));
+                for entry in &tree.entries {
+                    let suffix = if entry.kind == Some(ObjectType::Tree) {
+                        "/"
+                    } else {
+                        ""
+                    };
+                    out.push_str(&format!("{}{}\n", entry.name, suffix));
+                }
+            }
+            ShowObject::Blob(blob) => out.push_str(&String::from_utf8_lossy(&blob.content)),
+        }
+        Ok(out)
+    }
+}
+
+#[cfg(test)]
+mod show_test {
+    use std::{env, fs, path::Path, process::Command};
+
+    use super::ShowObject;
+    use crate::{DateMode, GitRepository, PrettyFormat, helpers::test_util::git_output};
+
+    #[test]
+    fn git_show_matches_cli_test() {
+        let dir_name = "./temp_test/show/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        let path = env::current_dir().unwrap().join(dir_name);
+        let write = |file: &str, lines: &[&str]| {
+            let mut content = lines.join("\n");
+            content.push('\n');
+            fs::write(path.join(file), content).unwrap();
+        };
+        let base: Vec<String> = (1..=30).map(|i| format!("fn line_{}()", i)).collect();
+        let edit = |changes: &[(usize, &str)]| -> Vec<String> {
+            let mut lines = base.clone();
+            for (index, line) in changes.iter().rev() {
+                if line.is_empty() {
+                    lines.remove(*index);
+                } else {
+                    lines[*index] = line.to_string();
+                }
+            }
+            lines
+        };
+        fn refs(lines: &[String]) -> Vec<&str> {
+            lines.iter().map(String::as_str).collect()
+        }
+
+        git_output(&path, &["init", "-b", "main"]);
+        git_output(&path, &["config", "user.name", "Committer"]);
+        git_output(&path, &["config", "user.email", "committer@example.com"]);
+        fs::create_dir_all(path.join("dir")).unwrap();
+        write("a.txt", &refs(&base));
+        write("dir/b.txt", &["b"]);
+        write("c.txt", &["c"]);
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "root"]);
+        write("c.txt", &["c", "more"]);
+        git_output(&path, &["commit", "-am", "second\n\nwith a body"]);
+
+        // diverging edits resolved differently in the merge
+        git_output(&path, &["checkout", "-b", "side"]);
+        let side = edit(&[(2, "fn side_3()"), (14, "fn side_15()"), (25, "")]);
+        write("a.txt", &refs(&side));
+        write("side.txt", &["side"]);
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "side"]);
+        git_output(&path, &["checkout", "-b", "other", "main"]);
+        write("c.txt", &["c", "other"]);
+        git_output(&path, &["commit", "-am", "other"]);
+        git_output(&path, &["checkout", "main"]);
+        let main = edit(&[(2, "fn main_3()"), (20, "fn main_21()")]);
+        write("a.txt", &refs(&main));
+        write("c.txt", &["c", "main"]);
+        git_output(&path, &["commit", "-am", "main"]);
+        git_output(&path, &["merge", "side", "-m", "merge side"]);
+        let merged = edit(&[
+            (2, "fn merged_3()"),
+            (14, "fn side_15()"),
+            (20, "fn main_21()"),
+            (25, ""),
+            (28, "fn evil_29()"),
+        ]);
+        write("a.txt", &refs(&merged));
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "merge side"]);
+        // octopus merge of non conflicting branches
+        git_output(&path, &["checkout", "-b", "x", "main~1"]);
+        write("x.txt", &["x"]);
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "x"]);
+        git_output(&path, &["checkout", "-b", "y", "main~1"]);
+        write("y.txt", &["y"]);
+        git_output(&path, &["add", "."]);
+        git_output(&path, &["commit", "-m", "y"]);
+        git_output(&path, &["checkout", "main"]);
+        git_output(&path, &["merge", "x", "y", "-m", "octopus"]);
+        git_output(
+            &path,
+            &["tag", "-a", "v1", "-m", "release\n\nnotes", "HEAD~1"],
+        );
+        git_output(
+            &path,
+            &["tag", "-a", "tree-tag", "-m", "a tree", "HEAD^{tree}"],
+        );
+
+        let repo = GitRepository::open(Path::new(&path)).unwrap();
+        let specs = [
+            "HEAD~1~1~1~1",
+            "HEAD~1~1~1",
+            "HEAD~1~1",
+            "HEAD~1",
+            "HEAD",
+            "v1",
+            "tree-tag",
+            "HEAD^{tree}",
+            "HEAD:dir",
+            "HEAD:a.txt",
+            "HEAD~1:dir/b.txt",
+        ];
+        let mut results = vec![];
+        for spec in specs {
+            let mut formatter = repo
+                .pretty_formatter(PrettyFormat::Medium, DateMode::Default)
+                .unwrap();
+            let object = repo.git_show(spec).unwrap();
+            let ours = object.render(&mut formatter).unwrap();
+            let output = git_output(&path, &["show", spec]);
+            let theirs = String::from_utf8_lossy(&output.stdout).to_string();
+            let combined = match &object {
+                ShowObject::Commit(commit) => match &commit.diff {
+                    super::ShowDiff::Combined(diff) => diff.files().len(),
+                    super::ShowDiff::Patch(_) => 0,
+                },
+                _ => 0,
+            };
+            results.push((spec, ours, theirs, combined));
+        }
+        let missing = repo.git_show("HEAD:missing.txt").is_err();
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        for (spec, ours, theirs, _) in &results {
+            assert_eq!(ours, theirs, "{}", spec);
+        }
+        // the evil merge shows a.txt only, the octopus merge nothing
+        assert_eq!(results[3].3, 1);
+        assert_eq!(results[4].3, 0);
+        assert!(missing);
+    }
+}
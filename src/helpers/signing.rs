@@ -0,0 +1,807 @@
+use std::{
+    collections::hash_map::RandomState,
+    fs::OpenOptions,
+    hash::{BuildHasher, Hasher},
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[cfg(unix)]
+use std::os::unix::fs::OpenOptionsExt;
+
+use git2::{Config, Error, Object, ObjectType, Oid, Repository, Signature};
+
+use super::date;
+
+/// Signs commit and tag payloads, implement this to plug in a custom signing backend (a
+/// hardware token, a remote signing service, etc).
+pub trait Signer: Send + Sync {
+    /// Signs `payload` and returns the armored detached signature.
+    fn sign(&self, payload: &[u8]) -> Result<String, Error>;
+}
+
+/// Signs payloads using a gpg compatible program, `gpg` for OpenPGP signatures or `gpgsm` for
+/// X.509 signatures. Same as `gpg.format=openpgp` and `gpg.format=x509` in git.
+#[derive(Clone, Debug)]
+pub struct GpgSigner {
+    program: String,
+    key: String,
+}
+
+impl GpgSigner {
+    /// Creates an OpenPGP signer that signs with `key` using `gpg`.
+    pub fn new(key: impl Into<String>) -> Self {
+        GpgSigner {
+            program: String::from("gpg"),
+            key: key.into(),
+        }
+    }
+
+    /// Creates an X.509 signer that signs with `key` using `gpgsm`.
+    pub fn x509(key: impl Into<String>) -> Self {
+        GpgSigner {
+            program: String::from("gpgsm"),
+            key: key.into(),
+        }
+    }
+
+    /// Sets the program to be used instead of `gpg`/`gpgsm`, same as `gpg.program`.
+    pub fn program(&mut self, program: impl Into<String>) -> &mut Self {
+        self.program = program.into();
+        self
+    }
+}
+
+impl Signer for GpgSigner {
+    fn sign(&self, payload: &[u8]) -> Result<String, Error> {
+        let mut command = Command::new(&self.program);
+        command.args(["--status-fd=2", "-bsau", &self.key]);
+        let output = run_with_input(command, payload)?;
+
+        let status = String::from_utf8_lossy(&output.stderr);
+        if !output.status.success() || !status.contains("[GNUPG:] SIG_CREATED ") {
+            return Err(Error::from_str(&format!(
+                "gpg failed to sign the data: {}",
+                status.trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Signs payloads using `ssh-keygen -Y sign`, same as `gpg.format=ssh` in git.
+#[derive(Clone, Debug)]
+pub struct SshSigner {
+    program: String,
+    key: String,
+}
+
+impl SshSigner {
+    /// Creates a signer that signs with `key`. `key` is either the path to a private key, the
+    /// path to a public key whose private key is held by `ssh-agent`, or a literal public key
+    /// prefixed with `key::`.
+    pub fn new(key: impl Into<String>) -> Self {
+        SshSigner {
+            program: String::from("ssh-keygen"),
+            key: key.into(),
+        }
+    }
+
+    /// Sets the program to be used instead of `ssh-keygen`, same as `gpg.ssh.program`.
+    pub fn program(&mut self, program: impl Into<String>) -> &mut Self {
+        self.program = program.into();
+        self
+    }
+}
+
+impl Signer for SshSigner {
+    fn sign(&self, payload: &[u8]) -> Result<String, Error> {
+        // literal keys need to be written to a file for ssh-keygen to pick them up
+        let literal_key = match self.key.strip_prefix("key::") {
+            Some(key) => Some(temp_file("signing_key", key.as_bytes())?),
+            None => None,
+        };
+        let key_file = match &literal_key {
+            Some(path) => path.clone(),
+            None => PathBuf::from(&self.key),
+        };
+
+        let mut command = Command::new(&self.program);
+        command.args(["-Y", "sign", "-n", "git", "-f"]);
+        command.arg(&key_file);
+        let output = run_with_input(command, payload);
+
+        if let Some(path) = literal_key {
+            let _ = std::fs::remove_file(path);
+        }
+
+        let output = output?;
+        if !output.status.success() {
+            return Err(Error::from_str(&format!(
+                "ssh-keygen failed to sign the data: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    }
+}
+
+/// Builds the signer configured through `gpg.format`, `gpg.program` and `user.signingkey`.
+/// `key` overrides `user.signingkey`, `identity` ("Name <email>") is used as the key for
+/// OpenPGP and X.509 when no key is configured.
+pub(crate) fn signer_from_config(
+    config: &Config,
+    key: Option<String>,
+    identity: String,
+) -> Result<Box<dyn Signer>, Error> {
+    let key = match key {
+        Some(key) => Some(key),
+        None => config.get_string("user.signingkey").ok(),
+    };
+    let format = config
+        .get_string("gpg.format")
+        .unwrap_or_else(|_| String::from("openpgp"));
+
+    match format.as_str() {
+        "openpgp" => {
+            let mut signer = GpgSigner::new(key.unwrap_or(identity));
+            if let Ok(program) = config.get_string("gpg.openpgp.program") {
+                signer.program(program);
+            } else if let Ok(program) = config.get_string("gpg.program") {
+                signer.program(program);
+            }
+            Ok(Box::new(signer))
+        }
+        "x509" => {
+            let mut signer = GpgSigner::x509(key.unwrap_or(identity));
+            if let Ok(program) = config.get_string("gpg.x509.program") {
+                signer.program(program);
+            }
+            Ok(Box::new(signer))
+        }
+        "ssh" => {
+            let key = key.ok_or_else(|| {
+                Error::from_str("gpg.format is 'ssh' but no user.signingkey is configured")
+            })?;
+            let mut signer = SshSigner::new(key);
+            if let Ok(program) = config.get_string("gpg.ssh.program") {
+                signer.program(program);
+            }
+            Ok(Box::new(signer))
+        }
+        _ => Err(Error::from_str(&format!(
+            "invalid value for gpg.format: '{}'",
+            format
+        ))),
+    }
+}
+
+/// Creates an annotated tag `name` pointing at `target` and signed by `signer`, the signed
+/// counterpart of `Repository::tag`. The signature is appended to the tag message, same as
+/// `git tag -s`. Returns the id of the tag object.
+pub fn tag_signed(
+    repository: &Repository,
+    name: &str,
+    target: &Object,
+    tagger: &Signature,
+    message: &str,
+    signer: &dyn Signer,
+    force: bool,
+) -> Result<Oid, Error> {
+    let kind = match target.kind() {
+        Some(kind) => kind.str(),
+        None => "commit",
+    };
+    let mut buffer = format!(
+        "object {}\ntype {}\ntag {}\ntagger {} <{}> {} {}\n\n{}",
+        target.id(),
+        kind,
+        name,
+        String::from_utf8_lossy(tagger.name_bytes()),
+        String::from_utf8_lossy(tagger.email_bytes()),
+        tagger.when().seconds(),
+        date::format_offset(tagger.when().offset_minutes(), false),
+        message
+    );
+    buffer.push_str(&signer.sign(buffer.as_bytes())?);
+    let oid = repository
+        .odb()?
+        .write(ObjectType::Tag, buffer.as_bytes())?;
+    let log = format!("tag: tagging {}", target.id());
+    repository.reference(&format!("refs/tags/{}", name), oid, force, &log)?;
+    Ok(oid)
+}
+
+/// Outcome of checking a signature.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureStatus {
+    /// The signature is valid and made by a known key.
+    Good,
+
+    /// The signature does not match the signed data, or was made by an expired or revoked key.
+    Bad,
+
+    /// The key that made the signature is not known (not in the keyring or not in the allowed
+    /// signers file), so the signature cannot be checked.
+    UnknownKey,
+
+    /// There is no signature.
+    Unsigned,
+}
+
+/// Result of checking the signature of a commit or a tag.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SignatureCheck {
+    /// Outcome of the check.
+    pub status: SignatureStatus,
+
+    /// Identity of the signer, the user id for gpg or the principal for ssh, if known.
+    pub signer: Option<String>,
+
+    /// Key id or fingerprint of the key that made the signature, if known.
+    pub key: Option<String>,
+}
+
+impl SignatureCheck {
+    /// A `SignatureCheck` for data without a signature.
+    pub fn unsigned() -> Self {
+        SignatureCheck {
+            status: SignatureStatus::Unsigned,
+            signer: None,
+            key: None,
+        }
+    }
+
+    /// Returns true if the signature is `SignatureStatus::Good`.
+    pub fn is_good(&self) -> bool {
+        self.status == SignatureStatus::Good
+    }
+}
+
+/// Checks signatures of commits and tags, implement this to plug in a custom verification
+/// backend.
+pub trait Verifier: Send + Sync {
+    /// Checks the detached `signature` made over `payload`.
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<SignatureCheck, Error>;
+}
+
+/// Checks OpenPGP signatures using `gpg`, or X.509 signatures using `gpgsm`.
+#[derive(Clone, Debug)]
+pub struct GpgVerifier {
+    program: String,
+}
+
+impl GpgVerifier {
+    /// Creates an OpenPGP verifier using `gpg`.
+    pub fn new() -> Self {
+        GpgVerifier {
+            program: String::from("gpg"),
+        }
+    }
+
+    /// Creates an X.509 verifier using `gpgsm`.
+    pub fn x509() -> Self {
+        GpgVerifier {
+            program: String::from("gpgsm"),
+        }
+    }
+
+    /// Sets the program to be used instead of `gpg`/`gpgsm`, same as `gpg.program`.
+    pub fn program(&mut self, program: impl Into<String>) -> &mut Self {
+        self.program = program.into();
+        self
+    }
+}
+
+impl Default for GpgVerifier {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Verifier for GpgVerifier {
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<SignatureCheck, Error> {
+        let signature_file = temp_file("signature", signature)?;
+        let mut command = Command::new(&self.program);
+        command.args(["--status-fd=1", "--keyid-format=long", "--verify"]);
+        command.arg(&signature_file);
+        command.arg("-");
+        let output = run_with_input(command, payload);
+        let _ = std::fs::remove_file(&signature_file);
+        let output = output?;
+
+        let mut check = SignatureCheck {
+            status: SignatureStatus::Bad,
+            signer: None,
+            key: None,
+        };
+        let status = String::from_utf8_lossy(&output.stdout);
+        for line in status.lines() {
+            let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+                continue;
+            };
+            let mut fields = line.splitn(3, ' ');
+            let keyword = fields.next().unwrap_or_default();
+            let key = fields.next().map(|key| key.to_string());
+            let signer = fields.next().map(|signer| signer.to_string());
+            match keyword {
+                "GOODSIG" => {
+                    check.status = SignatureStatus::Good;
+                    check.key = key;
+                    check.signer = signer;
+                }
+                "BADSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => {
+                    check.status = SignatureStatus::Bad;
+                    check.key = key;
+                    check.signer = signer;
+                    break;
+                }
+                "ERRSIG" => {
+                    check.key = key;
+                }
+                "NO_PUBKEY" => {
+                    check.status = SignatureStatus::UnknownKey;
+                    check.key = key;
+                }
+                _ => {}
+            }
+        }
+        Ok(check)
+    }
+}
+
+/// Checks ssh signatures using `ssh-keygen -Y verify` against an allowed signers file, same as
+/// `gpg.ssh.allowedSignersFile` in git.
+#[derive(Clone, Debug)]
+pub struct SshVerifier {
+    program: String,
+    allowed_signers: PathBuf,
+}
+
+impl SshVerifier {
+    /// Creates a verifier that only trusts the keys listed in `allowed_signers`.
+    pub fn new(allowed_signers: &Path) -> Self {
+        SshVerifier {
+            program: String::from("ssh-keygen"),
+            allowed_signers: allowed_signers.to_path_buf(),
+        }
+    }
+
+    /// Sets the program to be used instead of `ssh-keygen`, same as `gpg.ssh.program`.
+    pub fn program(&mut self, program: impl Into<String>) -> &mut Self {
+        self.program = program.into();
+        self
+    }
+
+    fn check(&self, payload: &[u8], signature_file: &Path) -> Result<SignatureCheck, Error> {
+        // find who made the signature
+        let mut command = Command::new(&self.program);
+        command.args(["-Y", "find-principals", "-f"]);
+        command
+            .arg(&self.allowed_signers)
+            .arg("-s")
+            .arg(signature_file);
+        let output = run_with_input(command, &[])?;
+        let principals = String::from_utf8_lossy(&output.stdout).to_string();
+        let principal = principals
+            .lines()
+            .next()
+            .filter(|_| output.status.success());
+
+        let Some(principal) = principal else {
+            // the key is not allowed, still tell apart broken signatures from unknown keys
+            let mut command = Command::new(&self.program);
+            command.args(["-Y", "check-novalidate", "-n", "git", "-s"]);
+            command.arg(signature_file);
+            let output = run_with_input(command, payload)?;
+            let status = if output.status.success() {
+                SignatureStatus::UnknownKey
+            } else {
+                SignatureStatus::Bad
+            };
+            return Ok(SignatureCheck {
+                status,
+                signer: None,
+                key: SshVerifier::key_of(&output.stdout),
+            });
+        };
+
+        let mut command = Command::new(&self.program);
+        command.args(["-Y", "verify", "-n", "git", "-f"]);
+        command.arg(&self.allowed_signers);
+        command.args(["-I", principal, "-s"]).arg(signature_file);
+        let output = run_with_input(command, payload)?;
+        let status = if output.status.success() {
+            SignatureStatus::Good
+        } else {
+            SignatureStatus::Bad
+        };
+        Ok(SignatureCheck {
+            status,
+            signer: Some(principal.to_string()),
+            key: SshVerifier::key_of(&output.stdout),
+        })
+    }
+
+    /// Extracts the key fingerprint from ssh-keygen output like
+    /// `Good "git" signature for x with ED25519 key SHA256:...`.
+    fn key_of(output: &[u8]) -> Option<String> {
+        let output = String::from_utf8_lossy(output);
+        let (_, key) = output.rsplit_once(" key ")?;
+        Some(key.trim().to_string())
+    }
+}
+
+impl Verifier for SshVerifier {
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<SignatureCheck, Error> {
+        let signature_file = temp_file("signature", signature)?;
+        let check = self.check(payload, &signature_file);
+        let _ = std::fs::remove_file(&signature_file);
+        check
+    }
+}
+
+/// Picks the verifier matching the type of each signature, configured through `gpg.program`,
+/// `gpg.x509.program`, `gpg.ssh.program` and `gpg.ssh.allowedSignersFile`.
+pub(crate) struct ConfigVerifier {
+    openpgp: GpgVerifier,
+    x509: GpgVerifier,
+    ssh: Option<SshVerifier>,
+}
+
+impl ConfigVerifier {
+    /// Reads the verifier configuration, relative paths are resolved against `base`.
+    pub(crate) fn new(config: &Config, base: &Path) -> Self {
+        let mut openpgp = GpgVerifier::new();
+        if let Ok(program) = config.get_string("gpg.openpgp.program") {
+            openpgp.program(program);
+        } else if let Ok(program) = config.get_string("gpg.program") {
+            openpgp.program(program);
+        }
+
+        let mut x509 = GpgVerifier::x509();
+        if let Ok(program) = config.get_string("gpg.x509.program") {
+            x509.program(program);
+        }
+
+        let ssh = config
+            .get_path("gpg.ssh.allowedSignersFile")
+            .ok()
+            .map(|path| {
+                let mut verifier = SshVerifier::new(&base.join(path));
+                if let Ok(program) = config.get_string("gpg.ssh.program") {
+                    verifier.program(program);
+                }
+                verifier
+            });
+
+        ConfigVerifier { openpgp, x509, ssh }
+    }
+}
+
+impl Verifier for ConfigVerifier {
+    fn verify(&self, payload: &[u8], signature: &[u8]) -> Result<SignatureCheck, Error> {
+        if signature.starts_with(b"-----BEGIN SSH SIGNATURE-----") {
+            return match &self.ssh {
+                Some(ssh) => ssh.verify(payload, signature),
+                None => Err(Error::from_str(
+                    "gpg.ssh.allowedSignersFile needs to be configured and exist for ssh signature verification",
+                )),
+            };
+        }
+        if signature.starts_with(b"-----BEGIN SIGNED MESSAGE-----") {
+            return self.x509.verify(payload, signature);
+        }
+        self.openpgp.verify(payload, signature)
+    }
+}
+
+/// Splits a raw tag object into the signed payload and the signature appended to its message.
+pub(crate) fn split_tag_signature(raw: &[u8]) -> Option<(&[u8], &[u8])> {
+    const MARKERS: [&[u8]; 3] = [
+        b"-----BEGIN PGP SIGNATURE-----",
+        b"-----BEGIN SSH SIGNATURE-----",
+        b"-----BEGIN SIGNED MESSAGE-----",
+    ];
+    let mut start = 0;
+    for line in raw.split_inclusive(|byte| *byte == b'\n') {
+        if MARKERS.iter().any(|marker| line.starts_with(marker)) {
+            return Some(raw.split_at(start));
+        }
+        start += line.len();
+    }
+    None
+}
+
+/// Writes `contents` to a new file in the temporary directory and returns its path, the caller
+/// is responsible for removing it. The file gets a random name and is created exclusively, so
+/// a file or symlink planted under a predictable name is never written through. On unix only
+/// the owner can read it.
+pub(crate) fn temp_file(name: &str, contents: &[u8]) -> Result<PathBuf, Error> {
+    const ATTEMPTS: usize = 100;
+    let dir = std::env::temp_dir();
+    for _ in 0..ATTEMPTS {
+        let path = dir.join(format!(".gittwo_{}_{:016x}", name, random_suffix()));
+        let error = |e: std::io::Error| {
+            Error::from_str(&format!("failed writing {}: {}", path.display(), e))
+        };
+
+        let mut options = OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        options.mode(0o600);
+        let mut file = match options.open(&path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(error(e)),
+        };
+        if let Err(e) = file.write_all(contents) {
+            drop(file);
+            let _ = std::fs::remove_file(&path);
+            return Err(error(e));
+        }
+        return Ok(path);
+    }
+    Err(Error::from_str(&format!(
+        "failed creating a temporary file in {}",
+        dir.display()
+    )))
+}
+
+/// Returns a random number for temporary file names, seeded by the randomly keyed std hasher.
+fn random_suffix() -> u64 {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(COUNTER.fetch_add(1, Ordering::SeqCst));
+    hasher.write_u32(std::process::id());
+    if let Ok(time) = SystemTime::now().duration_since(UNIX_EPOCH) {
+        hasher.write_u128(time.as_nanos());
+    }
+    hasher.finish()
+}
+
+/// Runs `command`, writing `input` to its stdin and collecting its output.
+pub(crate) fn run_with_input(mut command: Command, input: &[u8]) -> Result<Output, Error> {
+    let program = command.get_program().to_string_lossy().to_string();
+    let mut child = command
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::from_str(&format!("cannot run {}: {}", program, e)))?;
+
+    // feed stdin from another thread, so a program writing output before it has read all of
+    // its input cannot block on a full pipe while we are still writing
+    let writer = child.stdin.take().map(|mut stdin| {
+        let input = input.to_vec();
+        thread::spawn(move || {
+            // the program may exit without reading everything, the exit status tells what happened
+            let _ = stdin.write_all(&input);
+        })
+    });
+
+    let output = child
+        .wait_with_output()
+        .map_err(|e| Error::from_str(&format!("failed waiting for {}: {}", program, e)));
+    if let Some(writer) = writer {
+        let _ = writer.join();
+    }
+    output
+}
+
+#[cfg(test)]
+mod signing_test {
+    use std::{env, fs, process::Command};
+
+    use git2::{Repository, Signature};
+
+    use super::{GpgSigner, SshSigner, run_with_input, tag_signed, temp_file};
+
+    #[test]
+    fn tag_signed_ssh_test() {
+        let dir_name = "./temp_test/tag_signed/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with a commit and a throwaway ssh key allowed to sign
+        let path = env::current_dir().unwrap().join(dir_name);
+        for args in [
+            vec!["init"],
+            vec!["config", "user.name", "Tagger"],
+            vec!["config", "user.email", "tagger@example.com"],
+            vec!["config", "gpg.ssh.allowedSignersFile", "allowed_signers"],
+            vec!["commit", "--allow-empty", "-m", "root"],
+        ] {
+            let _ = Command::new("git")
+                .current_dir(&path)
+                .args(args)
+                .output()
+                .expect("git cli needs to be installed for comparing test results");
+        }
+        let key = path.join("signing_key");
+        let _ = Command::new("ssh-keygen")
+            .args(["-q", "-t", "ed25519", "-N", "", "-C", "test", "-f"])
+            .arg(&key)
+            .output()
+            .expect("ssh-keygen needs to be installed for signing tests");
+        let public_key = fs::read_to_string(path.join("signing_key.pub")).unwrap();
+        fs::write(
+            path.join("allowed_signers"),
+            format!("tagger@example.com {}", public_key),
+        )
+        .unwrap();
+
+        // perform an action same as "git tag -s -m signed v1"
+        let repository = Repository::open(&path).unwrap();
+        let target = repository.revparse_single("HEAD").unwrap();
+        let tagger = Signature::now("Tagger", "tagger@example.com").unwrap();
+        let signer = SshSigner::new(key.to_str().unwrap());
+        let oid = tag_signed(
+            &repository,
+            "v1",
+            &target,
+            &tagger,
+            "signed\n",
+            &signer,
+            false,
+        )
+        .unwrap();
+
+        // verify the above actions.
+        let out = Command::new("git")
+            .current_dir(&path)
+            .args(["verify-tag", "v1"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        let tag = repository.find_tag(oid).unwrap();
+        let message = tag.message().unwrap().to_string();
+        let exists = tag_signed(
+            &repository,
+            "v1",
+            &target,
+            &tagger,
+            "again\n",
+            &signer,
+            false,
+        );
+
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert!(message.starts_with("signed\n-----BEGIN SSH SIGNATURE-----\n"));
+        assert!(exists.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn tag_signed_gpg_test() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir_name = "./temp_test/tag_signed_gpg/";
+
+        // create temp directories
+        Command::new("mkdir")
+            .args(["-p", dir_name])
+            .output()
+            .unwrap();
+
+        // create a repository with a commit and a throwaway gpg home holding the signing key
+        let path = env::current_dir().unwrap().join(dir_name);
+        let gnupg_home = path.join("gnupg");
+        fs::create_dir(&gnupg_home).unwrap();
+        fs::set_permissions(&gnupg_home, fs::Permissions::from_mode(0o700)).unwrap();
+        for args in [
+            vec!["init"],
+            vec!["config", "user.name", "Tagger"],
+            vec!["config", "user.email", "tagger@example.com"],
+            vec!["commit", "--allow-empty", "-m", "root"],
+        ] {
+            let _ = Command::new("git")
+                .current_dir(&path)
+                .args(args)
+                .output()
+                .expect("git cli needs to be installed for comparing test results");
+        }
+        let _ = Command::new("gpg")
+            .env("GNUPGHOME", &gnupg_home)
+            .args(["--batch", "--passphrase", "", "--quick-gen-key"])
+            .args(["Tagger <tagger@example.com>", "ed25519", "sign", "never"])
+            .output()
+            .expect("gpg needs to be installed for signing tests");
+
+        // sign through a gpg.program wrapper so only the throwaway home is used
+        let program = path.join("gpg-wrapper");
+        fs::write(
+            &program,
+            format!(
+                "#!/bin/sh\nGNUPGHOME='{}' exec gpg \"$@\"\n",
+                gnupg_home.display()
+            ),
+        )
+        .unwrap();
+        fs::set_permissions(&program, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // perform an action same as "git tag -s -m signed v1"
+        let repository = Repository::open(&path).unwrap();
+        let target = repository.revparse_single("HEAD").unwrap();
+        let tagger = Signature::now("Tagger", "tagger@example.com").unwrap();
+        let mut signer = GpgSigner::new("tagger@example.com");
+        signer.program(program.to_str().unwrap());
+        let oid = tag_signed(
+            &repository,
+            "v1",
+            &target,
+            &tagger,
+            "signed\n",
+            &signer,
+            false,
+        )
+        .unwrap();
+
+        // verify the above actions.
+        let out = Command::new("git")
+            .current_dir(&path)
+            .env("GNUPGHOME", &gnupg_home)
+            .args(["verify-tag", "v1"])
+            .output()
+            .expect("git cli needs to be installed for comparing test results");
+        let tag = repository.find_tag(oid).unwrap();
+        let message = tag.message().unwrap().to_string();
+
+        let _ = Command::new("gpgconf")
+            .env("GNUPGHOME", &gnupg_home)
+            .args(["--kill", "gpg-agent"])
+            .output();
+        Command::new("rm").args(["-rf", dir_name]).output().unwrap();
+
+        assert!(
+            out.status.success(),
+            "{}",
+            String::from_utf8_lossy(&out.stderr)
+        );
+        assert!(message.starts_with("signed\n-----BEGIN PGP SIGNATURE-----\n"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn run_with_input_large_payload_test() {
+        // cat writes while it reads, so a payload larger than the pipe buffers only goes
+        // through when stdin is fed concurrently with reading stdout
+        let payload = vec![b'x'; 4 * 1024 * 1024];
+        let output = run_with_input(Command::new("cat"), &payload).unwrap();
+
+        assert!(output.status.success());
+        assert_eq!(output.stdout.len(), payload.len());
+    }
+
+    #[test]
+    fn temp_file_test() {
+        let first = temp_file("test", b"secret").unwrap();
+        let second = temp_file("test", b"secret").unwrap();
+        let contents = std::fs::read(&first).unwrap();
+        #[cfg(unix)]
+        let mode = {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::metadata(&first).unwrap().permissions().mode() & 0o777
+        };
+        let _ = std::fs::remove_file(&first);
+        let _ = std::fs::remove_file(&second);
+
+        assert_ne!(first, second);
+        assert_eq!(contents, b"secret");
+        #[cfg(unix)]
+        assert_eq!(mode, 0o600);
+    }
+}
@@ -0,0 +1,124 @@
+use std::{
+    cell::RefCell,
+    env, fs,
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+/// Returns a git cli command running in `dir`, with fixed author and committer dates so the
+/// same commits created in different directories get the same ids.
+pub(crate) fn git_command(dir: impl AsRef<Path>) -> Command {
+    let mut command = Command::new("git");
+    command
+        .current_dir(dir)
+        .env("GIT_AUTHOR_DATE", "@1700000000 +0100")
+        .env("GIT_COMMITTER_DATE", "@1700000000 +0100");
+    command
+}
+
+/// Runs the git cli in `dir`, see `git_command`.
+pub(crate) fn git_output(dir: impl AsRef<Path>, args: &[&str]) -> Output {
+    git_command(dir)
+        .args(args)
+        .output()
+        .expect("git cli needs to be installed for comparing test results")
+}
+
+/// Runs the git cli in `dir` and returns its stdout, see `git_command`.
+pub(crate) fn git(dir: impl AsRef<Path>, args: &[&str]) -> String {
+    String::from_utf8_lossy(&git_output(dir, args).stdout).to_string()
+}
+
+/// A repository `base` under `./temp_test/<name>/` set up with the git cli, which is copied to
+/// `ours`, changed through gittwo, and `theirs`, changed through the git cli, so the two can be
+/// compared after every step. The directory is removed when dropped.
+pub(crate) struct CliComparison {
+    dir_name: String,
+    root: PathBuf,
+    results: RefCell<Vec<(String, String, String)>>,
+}
+
+impl CliComparison {
+    /// Creates `base` with a repository on `main` and a committer identity.
+    pub(crate) fn new(name: &str) -> Self {
+        let dir_name = format!("./temp_test/{}/", name);
+        let root = env::current_dir().unwrap().join(&dir_name);
+        fs::create_dir_all(root.join("base")).unwrap();
+        let comparison = CliComparison {
+            dir_name,
+            root,
+            results: RefCell::new(vec![]),
+        };
+        comparison.git("base", &["init", "-b", "main"]);
+        comparison.git("base", &["config", "user.name", "Committer"]);
+        comparison.git("base", &["config", "user.email", "committer@example.com"]);
+        comparison
+    }
+
+    pub(crate) fn path(&self, dir: &str) -> PathBuf {
+        self.root.join(dir)
+    }
+
+    /// Runs the git cli in `dir` and returns its stdout.
+    pub(crate) fn git(&self, dir: &str, args: &[&str]) -> String {
+        git(self.path(dir), args)
+    }
+
+    /// Writes `content` to `file` in `dir`, creating missing parent directories.
+    pub(crate) fn write(&self, dir: &str, file: &str, content: &str) {
+        let path = self.path(dir).join(file);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    /// Reads `file` in `dir`, empty if it doesn't exist.
+    pub(crate) fn read(&self, dir: &str, file: &str) -> String {
+        fs::read_to_string(self.path(dir).join(file)).unwrap_or_default()
+    }
+
+    /// Writes `content` to `file` in both `ours` and `theirs`.
+    pub(crate) fn write_both(&self, file: &str, content: &str) {
+        self.write("ours", file, content);
+        self.write("theirs", file, content);
+    }
+
+    /// Runs the git cli in both `ours` and `theirs`, for steps that aren't being compared.
+    pub(crate) fn git_both(&self, args: &[&str]) {
+        self.git("ours", args);
+        self.git("theirs", args);
+    }
+
+    /// Copies `base` to `ours` and `theirs`.
+    pub(crate) fn fork(&self) {
+        for dir in ["ours", "theirs"] {
+            Command::new("cp")
+                .args(["-r", "base", dir])
+                .current_dir(&self.root)
+                .output()
+                .unwrap();
+        }
+    }
+
+    /// Records the states of `ours` and `theirs` after the step `label`, as rendered by
+    /// `state`.
+    pub(crate) fn record(&self, label: &str, state: impl Fn(&CliComparison, &str) -> String) {
+        let ours = state(self, "ours");
+        let theirs = state(self, "theirs");
+        self.results
+            .borrow_mut()
+            .push((label.to_string(), ours, theirs));
+    }
+
+    /// Asserts that `ours` and `theirs` matched after every recorded step.
+    pub(crate) fn assert_matches(&self) {
+        for (label, ours, theirs) in self.results.borrow().iter() {
+            assert_eq!(ours, theirs, "{}", label);
+        }
+    }
+}
+
+impl Drop for CliComparison {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir_name);
+    }
+}
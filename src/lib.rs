@@ -5,20 +5,92 @@ mod helpers;
 
 pub use self::configs::add_config::AddConfig;
 pub use self::configs::add_config::AddFlags;
+pub use self::configs::blame_config::BlameConfig;
+pub use self::configs::blame_config::BlameFlags;
+pub use self::configs::blame_config::BlameLineRecord;
+pub use self::configs::blame_config::BlameOutput;
 pub use self::configs::checkout_config::CheckoutConfig;
 pub use self::configs::checkout_config::CheckoutFlags;
+pub use self::configs::checkout_config::ConflictStyle;
 pub use self::configs::clone_config::CloneConfig;
 pub use self::configs::clone_config::CloneFlags;
+pub use self::configs::commit_config::CommitCleanup;
 pub use self::configs::commit_config::CommitConfig;
 pub use self::configs::commit_config::CommitFlags;
+pub use self::configs::diff_config::DiffConfig;
+pub use self::configs::diff_config::DiffFileRecord;
+pub use self::configs::diff_config::DiffFlags;
+pub use self::configs::diff_config::DiffHunkRecord;
+pub use self::configs::diff_config::DiffLinePair;
+pub use self::configs::diff_config::DiffLineRecord;
+pub use self::configs::diff_config::DiffOutput;
+pub use self::configs::diff_config::WordDiffMode;
 pub use self::configs::fetch_config::FetchConfig;
 pub use self::configs::fetch_config::FetchFlags;
 pub use self::configs::init_config::InitConfig;
 pub use self::configs::init_config::InitFlags;
+pub use self::configs::log_config::CommitRecord;
+pub use self::configs::log_config::LogConfig;
+pub use self::configs::log_config::LogFlags;
+pub use self::configs::log_config::LogIter;
+pub use self::configs::log_config::LogOrder;
+pub use self::configs::ls_remote_config::LsRemoteConfig;
+pub use self::configs::ls_remote_config::LsRemoteFlags;
+pub use self::configs::ls_remote_config::RemoteRef;
 pub use self::configs::push_config::PushConfig;
 pub use self::configs::push_config::PushFlags;
+pub use self::configs::remote_config::RemoteBranchInfo;
+pub use self::configs::remote_config::RemoteBranchState;
 pub use self::configs::remote_config::RemoteConfig;
 pub use self::configs::remote_config::RemoteFlags;
+pub use self::configs::remote_config::RemoteInfo;
+pub use self::configs::remote_config::RemoteOutput;
+pub use self::configs::remote_config::RemotePullInfo;
+pub use self::configs::remote_config::RemotePushInfo;
+pub use self::configs::remote_config::RemotePushState;
+pub use self::configs::remote_config::RemoteShow;
 pub use self::configs::remote_config::RemoteSubCommand;
+pub use self::configs::switch_config::SwitchConfig;
+pub use self::configs::switch_config::SwitchFlags;
+pub use self::configs::tag_config::TagConfig;
+pub use self::configs::tag_config::TagFlags;
+pub use self::configs::tag_config::TagInfo;
+pub use self::configs::tag_config::TagSubCommand;
+pub use self::configs::verify_config::VerifyConfig;
+pub use self::helpers::combined_diff::CombinedDiff;
+pub use self::helpers::combined_diff::CombinedDiffFile;
+pub use self::helpers::combined_diff::CombinedDiffHunk;
+pub use self::helpers::combined_diff::CombinedDiffLine;
 pub use self::helpers::credentials::CredType;
+pub use self::helpers::graph::GraphCell;
+pub use self::helpers::graph::GraphEntry;
+pub use self::helpers::graph::GraphLayout;
+pub use self::helpers::graph::GraphLine;
+pub use self::helpers::graph::GraphLineKind;
+pub use self::helpers::highlight::LineHighlight;
+pub use self::helpers::highlight::highlight_line_pair;
+pub use self::helpers::hooks::is_hook_error;
+pub use self::helpers::pretty::DateMode;
+pub use self::helpers::pretty::PrettyFormat;
+pub use self::helpers::pretty::PrettyFormatter;
 pub use self::helpers::repository::GitRepository;
+pub use self::helpers::repository::HeadState;
+pub use self::helpers::show::ShowBlob;
+pub use self::helpers::show::ShowCommit;
+pub use self::helpers::show::ShowDiff;
+pub use self::helpers::show::ShowObject;
+pub use self::helpers::show::ShowTag;
+pub use self::helpers::show::ShowTree;
+pub use self::helpers::show::TreeEntryRecord;
+pub use self::helpers::signing::GpgSigner;
+pub use self::helpers::signing::GpgVerifier;
+pub use self::helpers::signing::SignatureCheck;
+pub use self::helpers::signing::SignatureStatus;
+pub use self::helpers::signing::Signer;
+pub use self::helpers::signing::SshSigner;
+pub use self::helpers::signing::SshVerifier;
+pub use self::helpers::signing::Verifier;
+pub use self::helpers::signing::tag_signed;
+pub use self::helpers::url::GitUrl;
+pub use self::helpers::url::GitUrlScheme;
+pub use self::helpers::url::guess_dir_name;